@@ -0,0 +1,161 @@
+#![allow(unused)]
+use criterion::{BenchmarkId, Criterion};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const ENTITY_COUNT: usize = 50_000;
+const PASSES: usize = 1_000;
+
+// Both worlds hold `ENTITY_COUNT` entities with a single `Pane { width, height }`
+// component, so each pass walks the same amount of data in one archetype/table
+trait IterBench {
+    const NAME: &'static str;
+
+    fn build(n: usize) -> Self;
+
+    // One full pass over the query, summing widths so the loop can't be optimized out
+    fn iterate(&mut self) -> u64;
+}
+
+mod flax_backend {
+    use super::*;
+    use flax::*;
+
+    pub struct Pane {
+        width: u32,
+        height: u32,
+    }
+
+    component! {
+        pane: Pane,
+    }
+
+    pub struct FlaxIter {
+        world: World,
+        query: Query<Component<Pane>>,
+    }
+
+    impl IterBench for FlaxIter {
+        const NAME: &'static str = "flax";
+
+        fn build(n: usize) -> Self {
+            let mut world = World::new();
+            for _ in 0..n {
+                Entity::builder()
+                    .set(
+                        pane(),
+                        Pane {
+                            width: 100,
+                            height: 200,
+                        },
+                    )
+                    .spawn(&mut world);
+            }
+            Self {
+                world,
+                query: Query::new(pane()),
+            }
+        }
+
+        fn iterate(&mut self) -> u64 {
+            self.query
+                .borrow(&self.world)
+                .iter()
+                .map(|pane| pane.width as u64)
+                .sum()
+        }
+    }
+}
+
+mod bevy_backend {
+    use super::*;
+    use bevy_ecs::prelude::*;
+
+    #[derive(Component)]
+    struct Pane {
+        width: u32,
+        height: u32,
+    }
+
+    pub struct BevyIter {
+        world: World,
+        query: QueryState<&'static Pane>,
+    }
+
+    impl IterBench for BevyIter {
+        const NAME: &'static str = "bevy_ecs";
+
+        fn build(n: usize) -> Self {
+            let mut world = World::new();
+            for _ in 0..n {
+                world.spawn(Pane {
+                    width: 100,
+                    height: 200,
+                });
+            }
+            // Built once, like the Flax query, so only iteration is timed
+            let query = world.query::<&Pane>();
+            Self { world, query }
+        }
+
+        fn iterate(&mut self) -> u64 {
+            self.query
+                .iter(&self.world)
+                .map(|pane| pane.width as u64)
+                .sum()
+        }
+    }
+}
+
+use bevy_backend::BevyIter;
+use flax_backend::FlaxIter;
+
+fn bench_backend<B: IterBench>(c: &mut Criterion) {
+    let mut group = c.benchmark_group("iter");
+    let mut backend = B::build(ENTITY_COUNT);
+    group.bench_function(BenchmarkId::new(B::NAME, ENTITY_COUNT), |b| {
+        b.iter(|| black_box(backend.iterate()))
+    });
+    group.finish();
+}
+
+// Runs `PASSES` full passes and returns the total time
+fn measure<B: IterBench>() -> (&'static str, Duration) {
+    let mut backend = B::build(ENTITY_COUNT);
+    let start = Instant::now();
+    for _ in 0..PASSES {
+        black_box(backend.iterate());
+    }
+    (B::NAME, start.elapsed())
+}
+
+fn print_comparison_table(results: &[(&'static str, Duration)]) {
+    let visited = (ENTITY_COUNT * PASSES) as f64;
+
+    println!(
+        "\n=== Query Iteration ({} entities x {} passes) ===",
+        ENTITY_COUNT, PASSES
+    );
+    println!("{:<10} {:>12} {:>14}", "Library", "Total (ms)", "ns/entity");
+    for (name, elapsed) in results {
+        println!(
+            "{:<10} {:>12.3} {:>14.3}",
+            name,
+            elapsed.as_secs_f64() * 1000.0,
+            elapsed.as_nanos() as f64 / visited
+        );
+    }
+}
+
+fn main() {
+    let mut criterion = Criterion::default().configure_from_args();
+
+    bench_backend::<FlaxIter>(&mut criterion);
+    bench_backend::<BevyIter>(&mut criterion);
+
+    criterion.final_summary();
+
+    // Fixed pass count per backend so the per-entity cost can be compared side by side
+    let results = [measure::<FlaxIter>(), measure::<BevyIter>()];
+    print_comparison_table(&results);
+}
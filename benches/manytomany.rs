@@ -0,0 +1,228 @@
+#![allow(unused)]
+use criterion::{BenchmarkId, Criterion};
+use std::hint::black_box;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+#[path = "../src/bevy_ecs_example.rs"]
+mod bevy_ecs_example;
+#[path = "../src/flax_example.rs"]
+mod flax_example;
+#[path = "../src/hecs_example.rs"]
+mod hecs_example;
+
+const PANE_COUNT: usize = 1_000;
+const DATASETS_PER_PANE: usize = 8;
+const DATASET_COUNT: usize = 64;
+
+// Shared dataset ids; pane i subscribes to datasets i, i + 1, ..., i + K - 1 (mod D)
+fn dataset_ids() -> &'static [&'static str] {
+    static IDS: OnceLock<Vec<&'static str>> = OnceLock::new();
+    IDS.get_or_init(|| {
+        (0..DATASET_COUNT)
+            .map(|i| &*Box::leak(format!("dataset_{}", i).into_boxed_str()))
+            .collect()
+    })
+}
+
+fn pane_datasets(pane: usize, per_pane: usize) -> impl Iterator<Item = &'static str> {
+    (0..per_pane).map(move |j| dataset_ids()[(pane + j) % DATASET_COUNT])
+}
+
+// Each backend builds the pane/dataset graph with its example's
+// `create_pane_with_datasets`, then answers "which panes use dataset D" for every dataset
+trait ManyToManyBench: Sized {
+    const NAME: &'static str;
+
+    fn build(panes: usize, per_pane: usize) -> (Self, Duration);
+
+    fn query_all(&self) -> Duration;
+}
+
+mod flax_backend {
+    use super::flax_example::{self, DatasetHandle, DatasetId, dataset};
+    use super::*;
+    use flax::*;
+
+    pub struct FlaxGraph {
+        world: World,
+    }
+
+    impl ManyToManyBench for FlaxGraph {
+        const NAME: &'static str = "flax";
+
+        fn build(panes: usize, per_pane: usize) -> (Self, Duration) {
+            let mut world = World::new();
+            let start = Instant::now();
+            for pane in 0..panes {
+                let datasets = pane_datasets(pane, per_pane)
+                    .map(|id| (DatasetId(id), flax_example::DEFAULT_DATASET_WEIGHT))
+                    .collect();
+                flax_example::create_pane_with_datasets(&mut world, datasets, 100, 200);
+            }
+            (Self { world }, start.elapsed())
+        }
+
+        fn query_all(&self) -> Duration {
+            let datasets: Vec<DatasetHandle> = Query::new(entity_ids())
+                .with(dataset::id())
+                .borrow(&self.world)
+                .iter()
+                .map(DatasetHandle::new)
+                .collect();
+
+            let start = Instant::now();
+            for dataset in datasets {
+                black_box(flax_example::get_panes_for_dataset(&self.world, dataset));
+            }
+            start.elapsed()
+        }
+    }
+}
+
+mod bevy_backend {
+    use super::bevy_ecs_example::{self, DatasetHandle, DatasetId};
+    use super::*;
+    use bevy_ecs::prelude::*;
+
+    pub struct BevyGraph {
+        world: World,
+    }
+
+    impl ManyToManyBench for BevyGraph {
+        const NAME: &'static str = "bevy_ecs";
+
+        fn build(panes: usize, per_pane: usize) -> (Self, Duration) {
+            let mut world = World::new();
+            let start = Instant::now();
+            for pane in 0..panes {
+                let ids = pane_datasets(pane, per_pane).map(DatasetId).collect();
+                bevy_ecs_example::create_pane_with_datasets(&mut world, ids);
+            }
+            (Self { world }, start.elapsed())
+        }
+
+        fn query_all(&self) -> Duration {
+            let datasets: Vec<DatasetHandle> = self
+                .world
+                .try_query_filtered::<Entity, With<DatasetId>>()
+                .map(|mut query| query.iter(&self.world).map(DatasetHandle::new).collect())
+                .unwrap_or_default();
+
+            let start = Instant::now();
+            for dataset in datasets {
+                black_box(bevy_ecs_example::get_panes_for_dataset(
+                    &self.world,
+                    dataset,
+                ));
+            }
+            start.elapsed()
+        }
+    }
+}
+
+mod hecs_backend {
+    use super::hecs_example::{self, DatasetHandle, DatasetId};
+    use super::*;
+    use hecs::*;
+
+    pub struct HecsGraph {
+        world: World,
+    }
+
+    impl ManyToManyBench for HecsGraph {
+        const NAME: &'static str = "hecs";
+
+        fn build(panes: usize, per_pane: usize) -> (Self, Duration) {
+            let mut world = World::new();
+            let pane_root = world.spawn(());
+            let dataset_root = world.spawn(());
+
+            let start = Instant::now();
+            for pane in 0..panes {
+                let ids = pane_datasets(pane, per_pane).map(DatasetId).collect();
+                hecs_example::create_pane_with_datasets(&mut world, ids, pane_root, dataset_root);
+            }
+            (Self { world }, start.elapsed())
+        }
+
+        fn query_all(&self) -> Duration {
+            let datasets: Vec<DatasetHandle> = self
+                .world
+                .query::<&DatasetId>()
+                .iter()
+                .map(|(entity, _)| DatasetHandle::new(entity))
+                .collect();
+
+            let start = Instant::now();
+            for dataset in datasets {
+                black_box(hecs_example::get_panes_for_dataset(&self.world, dataset));
+            }
+            start.elapsed()
+        }
+    }
+}
+
+use bevy_backend::BevyGraph;
+use flax_backend::FlaxGraph;
+use hecs_backend::HecsGraph;
+
+fn bench_backend<B: ManyToManyBench>(c: &mut Criterion) {
+    let mut group = c.benchmark_group("manytomany");
+    group.sample_size(10);
+    group.bench_function(BenchmarkId::new("build", B::NAME), |b| {
+        b.iter_custom(|iters| {
+            (0..iters)
+                .map(|_| B::build(PANE_COUNT, DATASETS_PER_PANE).1)
+                .sum()
+        })
+    });
+
+    let (graph, _) = B::build(PANE_COUNT, DATASETS_PER_PANE);
+    group.bench_function(BenchmarkId::new("query", B::NAME), |b| {
+        b.iter_custom(|iters| (0..iters).map(|_| graph.query_all()).sum())
+    });
+    group.finish();
+}
+
+fn measure<B: ManyToManyBench>() -> (&'static str, Duration, Duration) {
+    let (graph, build_time) = B::build(PANE_COUNT, DATASETS_PER_PANE);
+    (B::NAME, build_time, graph.query_all())
+}
+
+fn print_comparison_table(results: &[(&'static str, Duration, Duration)]) {
+    println!(
+        "\n=== Many-to-Many Relations ({} panes x {} datasets, {} distinct) ===",
+        PANE_COUNT, DATASETS_PER_PANE, DATASET_COUNT
+    );
+    println!(
+        "{:<10} {:>16} {:>20}",
+        "Library", "Build (ms)", "Query all (µs)"
+    );
+    for (name, build_time, query_time) in results {
+        println!(
+            "{:<10} {:>16.3} {:>20.1}",
+            name,
+            build_time.as_secs_f64() * 1000.0,
+            query_time.as_secs_f64() * 1_000_000.0
+        );
+    }
+}
+
+fn main() {
+    let mut criterion = Criterion::default().configure_from_args();
+
+    bench_backend::<FlaxGraph>(&mut criterion);
+    bench_backend::<BevyGraph>(&mut criterion);
+    bench_backend::<HecsGraph>(&mut criterion);
+
+    criterion.final_summary();
+
+    // One extra timed run per backend so build and query cost can be compared side by side
+    let results = [
+        measure::<FlaxGraph>(),
+        measure::<BevyGraph>(),
+        measure::<HecsGraph>(),
+    ];
+    print_comparison_table(&results);
+}
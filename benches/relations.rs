@@ -0,0 +1,49 @@
+#![allow(unused)]
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use flax::*;
+use std::hint::black_box;
+
+#[path = "../src/traversal.rs"]
+mod traversal;
+
+use traversal::traverse_child_of;
+
+const SIZES: [usize; 3] = [100, 1_000, 10_000];
+const BRANCHING: usize = 4;
+
+component! {
+    has_child(child): (),
+}
+
+// Balanced tree of `n` entities: entity i is the child of entity (i - 1) / BRANCHING,
+// linked both ways like the REPL's set-relation
+fn build_tree(n: usize) -> World {
+    let mut world = World::new();
+    let mut entities: Vec<Entity> = Vec::with_capacity(n);
+    for i in 0..n {
+        let entity = Entity::builder()
+            .set(components::name(), format!("node{}", i))
+            .spawn(&mut world);
+        if i > 0 {
+            let parent = entities[(i - 1) / BRANCHING];
+            world.set(entity, components::child_of(parent), ()).unwrap();
+            world.set(parent, has_child(entity), ()).unwrap();
+        }
+        entities.push(entity);
+    }
+    world
+}
+
+fn bench_traversal(c: &mut Criterion) {
+    let mut group = c.benchmark_group("traverse_child_of");
+    for n in SIZES {
+        let world = build_tree(n);
+        group.bench_with_input(BenchmarkId::from_parameter(n), &world, |b, world| {
+            b.iter(|| black_box(traverse_child_of(world)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_traversal);
+criterion_main!(benches);
@@ -0,0 +1,237 @@
+#![allow(unused)]
+use criterion::{BenchmarkId, Criterion};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const ENTITY_COUNT: usize = 100_000;
+
+// Each backend spawns `n` entities carrying its equivalent of the `Pane` component
+// and reports how long the spawn loop took
+trait SpawnBench {
+    const NAME: &'static str;
+
+    fn spawn_n(n: usize) -> Duration;
+}
+
+mod flax_backend {
+    use super::*;
+    use flax::*;
+
+    component! {
+        width: u32,
+        height: u32,
+    }
+
+    pub struct FlaxSpawn;
+
+    impl SpawnBench for FlaxSpawn {
+        const NAME: &'static str = "flax";
+
+        fn spawn_n(n: usize) -> Duration {
+            let mut world = World::new();
+            let start = Instant::now();
+            for _ in 0..n {
+                Entity::builder()
+                    .set(width(), 100)
+                    .set(height(), 200)
+                    .spawn(&mut world);
+            }
+            let elapsed = start.elapsed();
+            black_box(&world);
+            elapsed
+        }
+    }
+}
+
+mod bevy_backend {
+    use super::*;
+    use bevy_ecs::prelude::*;
+
+    #[derive(Component)]
+    struct Pane {
+        width: u32,
+        height: u32,
+    }
+
+    pub struct BevySpawn;
+
+    impl SpawnBench for BevySpawn {
+        const NAME: &'static str = "bevy_ecs";
+
+        fn spawn_n(n: usize) -> Duration {
+            let mut world = World::new();
+            let start = Instant::now();
+            for _ in 0..n {
+                world.spawn(Pane {
+                    width: 100,
+                    height: 200,
+                });
+            }
+            let elapsed = start.elapsed();
+            black_box(&world);
+            elapsed
+        }
+    }
+}
+
+mod hecs_backend {
+    use super::*;
+    use hecs::*;
+
+    struct Pane {
+        width: u32,
+        height: u32,
+    }
+
+    pub struct HecsSpawn;
+
+    impl SpawnBench for HecsSpawn {
+        const NAME: &'static str = "hecs";
+
+        fn spawn_n(n: usize) -> Duration {
+            let mut world = World::new();
+            let start = Instant::now();
+            for _ in 0..n {
+                world.spawn((Pane {
+                    width: 100,
+                    height: 200,
+                },));
+            }
+            let elapsed = start.elapsed();
+            black_box(&world);
+            elapsed
+        }
+    }
+}
+
+mod evenio_backend {
+    use super::*;
+    use evenio::prelude::*;
+
+    #[derive(Component)]
+    struct Pane {
+        width: u32,
+        height: u32,
+    }
+
+    pub struct EvenioSpawn;
+
+    impl SpawnBench for EvenioSpawn {
+        const NAME: &'static str = "evenio";
+
+        fn spawn_n(n: usize) -> Duration {
+            let mut world = World::new();
+            let start = Instant::now();
+            for _ in 0..n {
+                let entity = world.spawn();
+                world.insert(
+                    entity,
+                    Pane {
+                        width: 100,
+                        height: 200,
+                    },
+                );
+            }
+            let elapsed = start.elapsed();
+            black_box(&world);
+            elapsed
+        }
+    }
+}
+
+mod sparsey_backend {
+    use super::*;
+    use sparsey::component::GroupLayout;
+    use sparsey::*;
+
+    struct Pane {
+        width: u32,
+        height: u32,
+    }
+
+    pub struct SparseySpawn;
+
+    impl SpawnBench for SparseySpawn {
+        const NAME: &'static str = "sparsey";
+
+        fn spawn_n(n: usize) -> Duration {
+            // Pane is ungrouped here, so it has to be registered up front
+            let mut world = World::new(&GroupLayout::default());
+            world.register::<Pane>();
+
+            let start = Instant::now();
+            for _ in 0..n {
+                world.create((Pane {
+                    width: 100,
+                    height: 200,
+                },));
+            }
+            let elapsed = start.elapsed();
+            black_box(&world);
+            elapsed
+        }
+    }
+}
+
+use bevy_backend::BevySpawn;
+use evenio_backend::EvenioSpawn;
+use flax_backend::FlaxSpawn;
+use hecs_backend::HecsSpawn;
+use sparsey_backend::SparseySpawn;
+
+fn bench_backend<B: SpawnBench>(c: &mut Criterion) {
+    let mut group = c.benchmark_group("spawn");
+    group.sample_size(10);
+    group.bench_with_input(
+        BenchmarkId::new(B::NAME, ENTITY_COUNT),
+        &ENTITY_COUNT,
+        |b, &n| b.iter_custom(|iters| (0..iters).map(|_| B::spawn_n(n)).sum()),
+    );
+    group.finish();
+}
+
+fn print_comparison_table(results: &[(&'static str, Duration)]) {
+    let fastest = results
+        .iter()
+        .map(|(_, elapsed)| *elapsed)
+        .min()
+        .unwrap_or_default();
+
+    println!("\n=== Spawn Throughput ({} entities) ===", ENTITY_COUNT);
+    println!(
+        "{:<10} {:>12} {:>16} {:>10}",
+        "Library", "Time (ms)", "Entities/sec", "Relative"
+    );
+    for (name, elapsed) in results {
+        let secs = elapsed.as_secs_f64();
+        println!(
+            "{:<10} {:>12.3} {:>16.0} {:>9.2}x",
+            name,
+            secs * 1000.0,
+            ENTITY_COUNT as f64 / secs,
+            secs / fastest.as_secs_f64()
+        );
+    }
+}
+
+fn main() {
+    let mut criterion = Criterion::default().configure_from_args();
+
+    bench_backend::<FlaxSpawn>(&mut criterion);
+    bench_backend::<BevySpawn>(&mut criterion);
+    bench_backend::<HecsSpawn>(&mut criterion);
+    bench_backend::<EvenioSpawn>(&mut criterion);
+    bench_backend::<SparseySpawn>(&mut criterion);
+
+    criterion.final_summary();
+
+    // One extra timed run per backend so the results can be compared side by side
+    let results = [
+        (FlaxSpawn::NAME, FlaxSpawn::spawn_n(ENTITY_COUNT)),
+        (BevySpawn::NAME, BevySpawn::spawn_n(ENTITY_COUNT)),
+        (HecsSpawn::NAME, HecsSpawn::spawn_n(ENTITY_COUNT)),
+        (EvenioSpawn::NAME, EvenioSpawn::spawn_n(ENTITY_COUNT)),
+        (SparseySpawn::NAME, SparseySpawn::spawn_n(ENTITY_COUNT)),
+    ];
+    print_comparison_table(&results);
+}
@@ -0,0 +1,107 @@
+use criterion::{BenchmarkId, Criterion};
+use flax::*;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+#[path = "../src/totals.rs"]
+mod totals;
+
+use totals::{Totals, par_totals};
+
+const SIZES: [usize; 2] = [100_000, 1_000_000];
+
+// Stands in for the REPL's mana, of which the aggregation only reads `current`
+struct Mana {
+    current: i32,
+}
+
+component! {
+    health: i32,
+    mana: Mana,
+}
+
+// `n` entities with health, every other one with mana, spread over two archetypes
+fn populate(n: usize) -> World {
+    let mut world = World::new();
+    for i in 0..n {
+        let mut builder = Entity::builder();
+        builder.set(health(), (i % 100) as i32);
+        if i % 2 == 0 {
+            builder.set(
+                mana(),
+                Mana {
+                    current: (i % 50) as i32,
+                },
+            );
+        }
+        builder.spawn(&mut world);
+    }
+    world
+}
+
+fn current(mana: &Mana) -> i32 {
+    mana.current
+}
+
+// Single-threaded baseline for `par_totals`
+fn serial_totals(world: &World) -> Totals {
+    let mut totals = Totals::default();
+    Query::new(health()).borrow(world).for_each(|&value| {
+        totals.health += value as i64;
+        totals.with_health += 1;
+    });
+    Query::new(mana()).borrow(world).for_each(|value| {
+        totals.mana += current(value) as i64;
+        totals.with_mana += 1;
+    });
+    totals
+}
+
+fn bench_totals(c: &mut Criterion) {
+    let mut group = c.benchmark_group("totals");
+    group.sample_size(10);
+    for n in SIZES {
+        let world = populate(n);
+        group.bench_with_input(BenchmarkId::new("serial", n), &world, |b, world| {
+            b.iter(|| black_box(serial_totals(world)))
+        });
+        group.bench_with_input(BenchmarkId::new("parallel", n), &world, |b, world| {
+            b.iter(|| black_box(par_totals(world, health(), mana(), current)))
+        });
+    }
+    group.finish();
+}
+
+fn time(f: impl Fn() -> Totals) -> (Totals, Duration) {
+    let start = Instant::now();
+    let totals = f();
+    (totals, start.elapsed())
+}
+
+fn print_comparison_table() {
+    println!("\n=== Health/Mana Totals (serial vs parallel) ===");
+    println!(
+        "{:<10} {:>12} {:>14} {:>10}",
+        "Entities", "Serial (ms)", "Parallel (ms)", "Speedup"
+    );
+    for n in SIZES {
+        let world = populate(n);
+        let (_, serial_time) = time(|| serial_totals(&world));
+        let (_, parallel_time) = time(|| par_totals(&world, health(), mana(), current));
+        println!(
+            "{:<10} {:>12.3} {:>14.3} {:>9.2}x",
+            n,
+            serial_time.as_secs_f64() * 1000.0,
+            parallel_time.as_secs_f64() * 1000.0,
+            serial_time.as_secs_f64() / parallel_time.as_secs_f64()
+        );
+    }
+}
+
+fn main() {
+    let mut criterion = Criterion::default().configure_from_args();
+    bench_totals(&mut criterion);
+    criterion.final_summary();
+
+    print_comparison_table();
+}
@@ -0,0 +1,101 @@
+use super::report::{OVERLAPPING_PANES, SubscriptionReport};
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+// Datasets requested by each pane in the shared demo, in creation order
+pub const DEMO_PANES: [&[&str]; 3] = [
+    &["temperature_sensor_1", "humidity_sensor_1"],
+    &["humidity_sensor_1"],
+    &["temperature_sensor_1", "pressure_sensor_1"],
+];
+
+// Common surface every ECS library implements for the pane/dataset demo
+pub trait EcsBackend {
+    type PaneHandle: Copy + Debug + PartialEq;
+
+    fn create_pane_with_datasets(&mut self, dataset_ids: &[&'static str]) -> Self::PaneHandle;
+
+    fn delete_pane(&mut self, pane: Self::PaneHandle);
+
+    fn unsubscribe_dataset(&mut self, pane: Self::PaneHandle, dataset_id: &'static str);
+
+    fn panes_for_dataset(&self, dataset_id: &str) -> Vec<Self::PaneHandle>;
+
+    // Lists every pane with its size and the datasets it uses
+    fn dump_panes(&self);
+
+    fn dump_subscriptions(&self);
+
+    // Number of dataset entities currently in the world
+    fn dataset_count(&self) -> usize;
+}
+
+// Shared driver: create the demo panes, list them, dump, delete the third pane,
+// dump again. Returns every pane that was created, in creation order.
+pub fn run_pane_demo<B: EcsBackend>(backend: &mut B) -> Vec<B::PaneHandle> {
+    println!("Creating panes through the backend...");
+    let panes: Vec<B::PaneHandle> = DEMO_PANES
+        .iter()
+        .map(|dataset_ids| backend.create_pane_with_datasets(dataset_ids))
+        .collect();
+
+    backend.dump_panes();
+    backend.dump_subscriptions();
+
+    println!("\n=== Demonstrating Command-Based Deletion ===");
+    println!("Deleting pane 3...");
+    backend.delete_pane(panes[2]);
+
+    backend.dump_subscriptions();
+
+    panes
+}
+
+// Creates every pane in `OVERLAPPING_PANES` and returns how many dataset entities
+// the backend ended up with, which should equal the number of distinct ids
+pub fn overlapping_dataset_count<B: EcsBackend>(backend: &mut B) -> usize {
+    for dataset_ids in OVERLAPPING_PANES {
+        backend.create_pane_with_datasets(dataset_ids);
+    }
+    backend.dataset_count()
+}
+
+// Normalized subscriptions: dataset id -> sorted indices (into `panes`) of subscribing panes
+pub fn subscription_map<B: EcsBackend>(backend: &B, panes: &[B::PaneHandle]) -> SubscriptionReport {
+    let mut subscriptions = BTreeMap::new();
+    for &dataset_id in DEMO_PANES.iter().flat_map(|ids| ids.iter()) {
+        let mut indices: Vec<usize> = backend
+            .panes_for_dataset(dataset_id)
+            .iter()
+            .filter_map(|pane| panes.iter().position(|p| p == pane))
+            .collect();
+        indices.sort();
+        subscriptions.insert(dataset_id, indices);
+    }
+    subscriptions
+}
+
+// Unsubscribes the first demo pane from its second dataset and dumps the result.
+// Returns whether the pane left that dataset while keeping its first one.
+pub fn run_unsubscribe_demo<B: EcsBackend>(backend: &mut B, panes: &[B::PaneHandle]) -> bool {
+    let pane = panes[0];
+    let [kept, dropped] = DEMO_PANES[0] else {
+        unreachable!("the first demo pane subscribes to two datasets");
+    };
+
+    println!("\n=== Demonstrating Command-Based Unsubscribe ===");
+    println!("Unsubscribing pane 1 from {}...", dropped);
+    backend.unsubscribe_dataset(pane, dropped);
+
+    backend.dump_subscriptions();
+
+    let unsubscribed = !backend.panes_for_dataset(dropped).contains(&pane)
+        && backend.panes_for_dataset(kept).contains(&pane);
+    println!(
+        "[Check] pane 1 left {} and kept {}: {}",
+        dropped,
+        kept,
+        if unsubscribed { "ok" } else { "FAILED" }
+    );
+    unsubscribed
+}
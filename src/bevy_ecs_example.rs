@@ -1,48 +1,24 @@
 #![allow(unused)]
+#[macro_use]
+mod handles;
+mod model;
+mod report;
+
+use bevy_ecs::component::{Mutable, StorageType};
 use bevy_ecs::prelude::*;
 use bevy_ecs::schedule::Schedule;
-use std::collections::{HashMap, VecDeque};
-
-// Macro to create type-safe entity handles
-macro_rules! entity_handles {
-    ($($handle_name:ident),* $(,)?) => {
-        $(
-            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-            pub struct $handle_name(Entity);
-
-            impl $handle_name {
-                pub fn new(entity: Entity) -> Self {
-                    Self(entity)
-                }
-
-                pub fn entity(&self) -> Entity {
-                    self.0
-                }
-            }
-
-            impl From<Entity> for $handle_name {
-                fn from(entity: Entity) -> Self {
-                    Self(entity)
-                }
-            }
-
-            impl From<$handle_name> for Entity {
-                fn from(handle: $handle_name) -> Self {
-                    handle.0
-                }
-            }
-        )*
-    };
-}
+use model::DatasetId;
+use report::{OVERLAPPING_PANES, SubscriptionReport};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 
 // Create type-safe handles
-entity_handles! {
-    PaneHandle,
-    DatasetHandle,
-}
+entity_handles!(Entity; PaneHandle, DatasetHandle);
 
-#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct DatasetId(&'static str);
+// What `#[derive(Component)]` would generate; the shared type can't carry the derive
+impl Component for DatasetId {
+    const STORAGE_TYPE: StorageType = StorageType::Table;
+    type Mutability = Mutable;
+}
 
 #[derive(Component, Debug, Clone)]
 struct Pane {
@@ -50,7 +26,9 @@ struct Pane {
     height: u32,
 }
 
-// Relationship components - Bevy ECS built-in relationships
+// Relationship components - Bevy ECS built-in relationships.
+// A relationship source holds a single target, so each pane -> dataset subscription
+// is its own entity carrying both SubscriptionOf(pane) and UsesDataset(dataset).
 #[derive(Component, Debug, Clone)]
 #[relationship(relationship_target = DatasetSubscribers)]
 struct UsesDataset {
@@ -58,10 +36,23 @@ struct UsesDataset {
     dataset: Entity,
 }
 
+// Subscription entities pointing at this dataset
 #[derive(Component, Debug, Clone)]
 #[relationship_target(relationship = UsesDataset)]
 struct DatasetSubscribers(Vec<Entity>);
 
+#[derive(Component, Debug, Clone)]
+#[relationship(relationship_target = PaneSubscriptions)]
+struct SubscriptionOf {
+    #[relationship]
+    pane: Entity,
+}
+
+// Subscription entities owned by this pane; despawning the pane despawns them too
+#[derive(Component, Debug, Clone)]
+#[relationship_target(relationship = SubscriptionOf, linked_spawn)]
+struct PaneSubscriptions(Vec<Entity>);
+
 // Command system resources - global state
 #[derive(Resource, Debug, Clone)]
 struct CommandQueue {
@@ -73,19 +64,26 @@ struct CreatedPanes {
     panes: Vec<(Vec<DatasetId>, PaneHandle)>,
 }
 
-// Command types
-#[derive(Debug, Clone)]
-pub enum Command {
-    CreatePaneWithDatasets { dataset_ids: Vec<DatasetId> },
-    DeletePane { pane: PaneHandle },
+// What the change detection system saw on the latest schedule run
+#[derive(Resource, Debug, Clone, Default)]
+struct PaneChanges {
+    added: Vec<PaneHandle>,
+    resized: Vec<PaneHandle>,
 }
 
+// Command types
+pub type Command = model::Command<PaneHandle, DatasetHandle>;
+
 // System-compatible pane creation
+// `spawned_datasets` holds datasets created earlier in the same system run, which
+// `datasets_query` can't see until the commands are applied
 fn create_pane_with_datasets_system(
     commands: &mut Commands,
     dataset_ids: Vec<DatasetId>,
     datasets_query: &Query<(Entity, &DatasetId)>,
+    spawned_datasets: &mut HashMap<DatasetId, DatasetHandle>,
 ) -> PaneHandle {
+    // region: spawn
     // Create the pane entity
     let pane = commands
         .spawn(Pane {
@@ -94,10 +92,12 @@ fn create_pane_with_datasets_system(
         })
         .id();
     let pane_handle = PaneHandle::new(pane);
+    // endregion: spawn
 
     for dataset_id in dataset_ids {
+        // region: spawn
         // Find existing dataset by querying all datasets
-        let mut existing_dataset = None;
+        let mut existing_dataset = spawned_datasets.get(&dataset_id).copied();
         for (entity, id) in datasets_query.iter() {
             if *id == dataset_id {
                 existing_dataset = Some(DatasetHandle::new(entity));
@@ -110,20 +110,31 @@ fn create_pane_with_datasets_system(
         } else {
             // Create new dataset entity
             let dataset_entity = commands.spawn(dataset_id).id();
-            DatasetHandle::new(dataset_entity)
+            let dataset_handle = DatasetHandle::new(dataset_entity);
+            spawned_datasets.insert(dataset_id, dataset_handle);
+            dataset_handle
         };
+        // endregion: spawn
 
+        // region: relate
         // Create the relationships using Bevy's relationship system
-        commands.entity(pane).insert(UsesDataset {
-            dataset: dataset_handle.entity(),
-        });
+        commands.spawn((
+            SubscriptionOf { pane },
+            UsesDataset {
+                dataset: dataset_handle.entity(),
+            },
+        ));
+        // endregion: relate
     }
 
     pane_handle
 }
 
 // Legacy function for non-system usage
-fn create_pane_with_datasets(world: &mut World, dataset_ids: Vec<DatasetId>) -> PaneHandle {
+pub(crate) fn create_pane_with_datasets(
+    world: &mut World,
+    dataset_ids: Vec<DatasetId>,
+) -> PaneHandle {
     // Create the pane entity
     let pane = world
         .spawn(Pane {
@@ -152,25 +163,87 @@ fn create_pane_with_datasets(world: &mut World, dataset_ids: Vec<DatasetId>) ->
         };
 
         // Create the relationships using Bevy's relationship system
-        world.entity_mut(pane).insert(UsesDataset {
-            dataset: dataset_handle.entity(),
-        });
+        world.spawn((
+            SubscriptionOf { pane },
+            UsesDataset {
+                dataset: dataset_handle.entity(),
+            },
+        ));
     }
 
     pane_handle
 }
 
-fn get_panes_for_dataset(world: &World, dataset: DatasetHandle) -> Vec<PaneHandle> {
+pub(crate) fn get_panes_for_dataset(world: &World, dataset: DatasetHandle) -> Vec<PaneHandle> {
+    // region: query-reverse
     let mut subscribing_panes = Vec::new();
 
-    // Query the relationship target component for this dataset
+    // Query the relationship target component for this dataset, then follow each
+    // subscription entity back to its pane
     if let Ok(entity_ref) = world.get_entity(dataset.entity()) {
         if let Some(subscribers) = entity_ref.get::<DatasetSubscribers>() {
-            subscribing_panes.extend(subscribers.0.iter().map(|&e| PaneHandle::new(e)));
+            subscribing_panes.extend(
+                subscribers
+                    .0
+                    .iter()
+                    .filter_map(|&subscription| world.get::<SubscriptionOf>(subscription))
+                    .map(|subscription_of| PaneHandle::new(subscription_of.pane)),
+            );
         }
     }
 
     subscribing_panes
+    // endregion: query-reverse
+}
+
+// Forward scan over every subscription's UsesDataset, kept to cross-check the
+// DatasetSubscribers target that Bevy maintains
+fn scan_panes_for_dataset(world: &mut World, dataset: DatasetHandle) -> Vec<PaneHandle> {
+    world
+        .query::<(&SubscriptionOf, &UsesDataset)>()
+        .iter(world)
+        .filter(|(_, uses)| uses.dataset == dataset.entity())
+        .map(|(subscription_of, _)| PaneHandle::new(subscription_of.pane))
+        .collect()
+}
+
+// Panes split by whether they subscribe to any dataset. Subscriptions are separate
+// entities here, so the pane-side marker is the PaneSubscriptions target, which
+// Bevy removes once the last subscription is gone
+fn panes_by_subscription(world: &mut World) -> (Vec<PaneHandle>, Vec<PaneHandle>) {
+    let subscribed = world
+        .query_filtered::<Entity, (With<Pane>, With<PaneSubscriptions>)>()
+        .iter(world)
+        .map(PaneHandle::new)
+        .collect();
+    let orphaned = world
+        .query_filtered::<Entity, (With<Pane>, Without<PaneSubscriptions>)>()
+        .iter(world)
+        .map(PaneHandle::new)
+        .collect();
+    (subscribed, orphaned)
+}
+
+// Datasets no subscription points at any more
+fn orphan_datasets(world: &mut World) -> Vec<DatasetId> {
+    world
+        .query_filtered::<&DatasetId, Without<DatasetSubscribers>>()
+        .iter(world)
+        .copied()
+        .collect()
+}
+
+// Datasets a pane is subscribed to, via its subscription entities
+fn get_datasets_for_pane(world: &World, pane: PaneHandle) -> Vec<DatasetHandle> {
+    let Some(subscriptions) = world.get::<PaneSubscriptions>(pane.entity()) else {
+        return Vec::new();
+    };
+    subscriptions
+        .0
+        .iter()
+        .filter_map(|&subscription| world.get::<UsesDataset>(subscription))
+        .map(|uses| DatasetHandle::new(uses.dataset))
+        .collect()
 }
 
 // Command processing system - proper Bevy system function
@@ -179,6 +252,8 @@ fn process_commands_system(
     mut command_queue: ResMut<CommandQueue>,
     mut created_panes: ResMut<CreatedPanes>,
     datasets_query: Query<(Entity, &DatasetId)>,
+    subscriptions_query: Query<(Entity, &SubscriptionOf, &UsesDataset)>,
+    mut panes_query: Query<&mut Pane>,
 ) {
     // Get and process all pending commands
     let pending_commands: Vec<Command> = command_queue.commands.drain(..).collect();
@@ -186,10 +261,12 @@ fn process_commands_system(
     // Process commands and collect results
     let mut new_panes = Vec::new();
     let mut deleted_panes = Vec::new();
+    let mut spawned_datasets = HashMap::new();
 
     for cmd in pending_commands {
         match cmd {
-            Command::CreatePaneWithDatasets { dataset_ids } => {
+            Command::CreatePaneWithDatasets { datasets, .. } => {
+                let dataset_ids = model::dataset_ids(&datasets);
                 println!(
                     "[System] Processing CreatePaneWithDatasets command with {} datasets",
                     dataset_ids.len()
@@ -198,15 +275,45 @@ fn process_commands_system(
                     &mut commands,
                     dataset_ids.clone(),
                     &datasets_query,
+                    &mut spawned_datasets,
                 );
                 new_panes.push((dataset_ids, pane_handle));
                 println!("[System] Created pane: {:?}", pane_handle);
             }
             Command::DeletePane { pane } => {
                 println!("[System] Processing DeletePane command for {:?}", pane);
+                // region: despawn
                 commands.entity(pane.entity()).despawn();
+                // endregion: despawn
                 deleted_panes.push(pane);
             }
+            Command::UnsubscribeDataset { pane, dataset } => {
+                println!(
+                    "[System] Processing UnsubscribeDataset command for {:?} from {:?}",
+                    pane, dataset
+                );
+                // Despawning the subscription entity updates both relationship targets
+                for (subscription, subscription_of, uses) in &subscriptions_query {
+                    if subscription_of.pane == pane.entity() && uses.dataset == dataset.entity() {
+                        commands.entity(subscription).despawn();
+                    }
+                }
+            }
+            Command::ResizePane {
+                pane,
+                width,
+                height,
+            } => {
+                println!(
+                    "[System] Processing ResizePane command for {:?} to {}x{}",
+                    pane, width, height
+                );
+                // Writing through `Mut<Pane>` marks it changed for `Changed<Pane>`
+                if let Ok(mut target) = panes_query.get_mut(pane.entity()) {
+                    target.width = width;
+                    target.height = height;
+                }
+            }
         }
     }
 
@@ -219,6 +326,39 @@ fn process_commands_system(
     }
 }
 
+// Reports panes added or resized since this system last ran. `Changed` also matches
+// newly added components, so panes in the `Added` query are skipped there
+fn pane_changes_system(
+    mut pane_changes: ResMut<PaneChanges>,
+    added_panes: Query<(Entity, &Pane), Added<Pane>>,
+    changed_panes: Query<(Entity, &Pane), Changed<Pane>>,
+) {
+    pane_changes.added.clear();
+    pane_changes.resized.clear();
+
+    for (entity, pane) in &added_panes {
+        println!(
+            "[Changes] Added {:?} at {}x{}",
+            PaneHandle::new(entity),
+            pane.width,
+            pane.height
+        );
+        pane_changes.added.push(PaneHandle::new(entity));
+    }
+    for (entity, pane) in &changed_panes {
+        if added_panes.contains(entity) {
+            continue;
+        }
+        println!(
+            "[Changes] Resized {:?} to {}x{}",
+            PaneHandle::new(entity),
+            pane.width,
+            pane.height
+        );
+        pane_changes.resized.push(PaneHandle::new(entity));
+    }
+}
+
 // Helper to enqueue commands using resources
 fn enqueue_command(world: &mut World, cmd: Command) {
     let mut queue = world.resource_mut::<CommandQueue>();
@@ -229,12 +369,18 @@ fn dump_subscriptions_by_dataset(world: &mut World) {
     // Print all datasets and their subscriptions
     println!("\n=== Dataset Subscriptions ===");
 
-    for (entity, dataset_id) in world.query::<(Entity, &DatasetId)>().iter(world) {
+    let datasets: Vec<(Entity, DatasetId)> = world
+        .query::<(Entity, &DatasetId)>()
+        .iter(world)
+        .map(|(entity, dataset_id)| (entity, *dataset_id))
+        .collect();
+    for (entity, dataset_id) in datasets {
+        let dataset_handle = DatasetHandle::new(entity);
         println!("Dataset: {:#?}", dataset_id);
-        println!("  Handle: {:?}", DatasetHandle::new(entity));
+        println!("  Handle: {:?}", dataset_handle);
 
-        // Use the dedicated function to get panes for this dataset
-        let subscribing_panes = get_panes_for_dataset(&world, DatasetHandle::new(entity));
+        // DatasetSubscribers is the authoritative source for subscribing panes
+        let subscribing_panes = get_panes_for_dataset(world, dataset_handle);
 
         if !subscribing_panes.is_empty() {
             println!(
@@ -248,7 +394,29 @@ fn dump_subscriptions_by_dataset(world: &mut World) {
     }
 }
 
+// Normalized subscriptions: dataset id -> sorted indices (into `panes`) of subscribing panes
+fn subscription_map(world: &mut World, panes: &[PaneHandle]) -> SubscriptionReport {
+    let mut subscriptions = BTreeMap::new();
+
+    for (entity, dataset_id) in world.query::<(Entity, &DatasetId)>().iter(world) {
+        let mut indices: Vec<usize> = get_panes_for_dataset(world, DatasetHandle::new(entity))
+            .iter()
+            .filter_map(|pane| panes.iter().position(|p| p == pane))
+            .collect();
+        indices.sort();
+        subscriptions.insert(dataset_id.0, indices);
+    }
+
+    subscriptions
+}
+
 pub fn main() {
+    let report = run();
+    report::print_report(&report);
+}
+
+// World with the command resources and the schedule that drains them
+fn command_world() -> (World, Schedule) {
     // Create a new bevy_ecs world
     let mut world = World::new();
 
@@ -257,10 +425,37 @@ pub fn main() {
         commands: VecDeque::new(),
     });
     world.insert_resource(CreatedPanes { panes: Vec::new() });
+    world.init_resource::<PaneChanges>();
 
-    // Create a schedule with our system
+    // Commands first, then change detection; `chain` applies the deferred spawns
+    // in between, so panes created this run already count as added
     let mut schedule = Schedule::default();
-    schedule.add_systems(process_commands_system);
+    schedule.add_systems((process_commands_system, pane_changes_system).chain());
+
+    (world, schedule)
+}
+
+fn dataset_count(world: &mut World) -> usize {
+    world.query::<&DatasetId>().iter(world).count()
+}
+
+// Dataset entities left after creating the overlapping panes in a single schedule run,
+// where datasets spawned by earlier commands are still deferred
+pub fn overlapping_dataset_count() -> usize {
+    let (mut world, mut schedule) = command_world();
+    for dataset_ids in OVERLAPPING_PANES {
+        enqueue_command(
+            &mut world,
+            Command::create_pane(dataset_ids.iter().map(|&id| DatasetId(id)).collect()),
+        );
+    }
+    schedule.run(&mut world);
+    dataset_count(&mut world)
+}
+
+// Runs the demo and returns the normalized subscriptions left after deletion
+pub fn run() -> SubscriptionReport {
+    let (mut world, mut schedule) = command_world();
 
     println!("=== Command-Based Pane Creation Demo ===\n");
 
@@ -268,29 +463,23 @@ pub fn main() {
     println!("Enqueueing commands...");
     enqueue_command(
         &mut world,
-        Command::CreatePaneWithDatasets {
-            dataset_ids: vec![
-                DatasetId("temperature_sensor_1"),
-                DatasetId("humidity_sensor_1"),
-            ],
-        },
+        Command::create_pane(vec![
+            DatasetId("temperature_sensor_1"),
+            DatasetId("humidity_sensor_1"),
+        ]),
     );
 
     enqueue_command(
         &mut world,
-        Command::CreatePaneWithDatasets {
-            dataset_ids: vec![DatasetId("humidity_sensor_1")],
-        },
+        Command::create_pane(vec![DatasetId("humidity_sensor_1")]),
     );
 
     enqueue_command(
         &mut world,
-        Command::CreatePaneWithDatasets {
-            dataset_ids: vec![
-                DatasetId("temperature_sensor_1"),
-                DatasetId("pressure_sensor_1"),
-            ],
-        },
+        Command::create_pane(vec![
+            DatasetId("temperature_sensor_1"),
+            DatasetId("pressure_sensor_1"),
+        ]),
     );
 
     // Process commands through the system
@@ -313,12 +502,7 @@ pub fn main() {
         println!("  Width: {}, Height: {}", pane.width, pane.height);
 
         // Query relationships: what datasets does this pane use?
-        let mut used_datasets = Vec::new();
-        if let Ok(entity_ref) = world.get_entity(entity) {
-            if let Some(uses_dataset) = entity_ref.get::<UsesDataset>() {
-                used_datasets.push(DatasetHandle::new(uses_dataset.dataset));
-            }
-        }
+        let used_datasets = get_datasets_for_pane(&world, pane_handle);
 
         if !used_datasets.is_empty() {
             println!(
@@ -329,10 +513,38 @@ pub fn main() {
         } else {
             println!("  Uses no datasets");
         }
+
+        // Reverse view: the datasets whose DatasetSubscribers target lists this pane
+        let listed_by: Vec<DatasetHandle> = used_datasets
+            .iter()
+            .copied()
+            .filter(|&dataset| get_panes_for_dataset(&world, dataset).contains(&pane_handle))
+            .collect();
+        println!("  Listed in DatasetSubscribers of: {:?}", listed_by);
     }
 
     dump_subscriptions_by_dataset(&mut world);
 
+    // Resize pane 2; only it should show up as changed on the next run
+    println!("\n=== Demonstrating Change Detection ===");
+    println!("Enqueueing resize command for pane 2...");
+    enqueue_command(
+        &mut world,
+        Command::ResizePane {
+            pane: pane2,
+            width: 320,
+            height: 240,
+        },
+    );
+    schedule.run(&mut world);
+
+    let changes = world.resource::<PaneChanges>().clone();
+    let resized = changes.added.is_empty() && changes.resized == vec![pane2];
+    println!(
+        "[Check] only pane 2 reported as resized: {}",
+        if resized { "ok" } else { "FAILED" }
+    );
+
     // Use command to delete pane 3
     println!("\n=== Demonstrating Command-Based Deletion ===");
     println!("Enqueueing delete command for pane 3...");
@@ -343,6 +555,67 @@ pub fn main() {
     schedule.run(&mut world);
 
     dump_subscriptions_by_dataset(&mut world);
+    let subscriptions = subscription_map(&mut world, &pane_handles);
+
+    // Unsubscribe pane 1 from its second dataset, keeping the first
+    println!("\n=== Demonstrating Command-Based Unsubscribe ===");
+    let (kept, dropped) = (
+        DatasetId("temperature_sensor_1"),
+        DatasetId("humidity_sensor_1"),
+    );
+    let find_dataset = |world: &mut World, id: DatasetId| {
+        world
+            .query::<(Entity, &DatasetId)>()
+            .iter(world)
+            .find(|(_, dataset_id)| **dataset_id == id)
+            .map(|(entity, _)| DatasetHandle::new(entity))
+    };
+    if let Some(dataset) = find_dataset(&mut world, dropped) {
+        println!("Unsubscribing pane 1 from {}...", dropped.0);
+        enqueue_command(
+            &mut world,
+            Command::UnsubscribeDataset {
+                pane: pane1,
+                dataset,
+            },
+        );
+        schedule.run(&mut world);
+    }
+
+    dump_subscriptions_by_dataset(&mut world);
+
+    let subscribed_to = |world: &mut World, id: DatasetId| {
+        find_dataset(world, id)
+            .is_some_and(|dataset| get_panes_for_dataset(world, dataset).contains(&pane1))
+    };
+    let unsubscribed = !subscribed_to(&mut world, dropped) && subscribed_to(&mut world, kept);
+    println!(
+        "[Check] pane 1 left {} and kept {}: {}",
+        dropped.0,
+        kept.0,
+        if unsubscribed { "ok" } else { "FAILED" }
+    );
+
+    // With/Without filters, like the Flax REPL's with_relation/without_relation
+    println!("\n=== Orphan Panes ===");
+    println!("Creating a pane with no datasets...");
+    enqueue_command(&mut world, Command::create_pane(Vec::new()));
+    schedule.run(&mut world);
+    let empty_pane = world
+        .resource::<CreatedPanes>()
+        .panes
+        .last()
+        .map(|(_, handle)| *handle)
+        .unwrap();
+
+    let (subscribed, orphaned) = panes_by_subscription(&mut world);
+    println!("Panes with subscriptions: {:?}", subscribed);
+    println!("Panes without subscriptions: {:?}", orphaned);
+    let datasets_without_subscribers = orphan_datasets(&mut world);
+    println!(
+        "Datasets without subscribers: {:?}",
+        datasets_without_subscribers
+    );
 
     // Print world statistics
     println!("\n=== World Statistics ===");
@@ -382,6 +655,12 @@ pub fn main() {
         if entity.get::<DatasetSubscribers>().is_some() {
             components.push("DatasetSubscribers");
         }
+        if entity.get::<SubscriptionOf>().is_some() {
+            components.push("SubscriptionOf");
+        }
+        if entity.get::<PaneSubscriptions>().is_some() {
+            components.push("PaneSubscriptions");
+        }
         // CommandQueue and CreatedPanes are now Resources, not Components
 
         println!("Components: {:?}", components);
@@ -424,6 +703,8 @@ pub fn main() {
         "- BEVY SYSTEMS: Proper system functions with Commands, Res, ResMut, Query parameters"
     );
     println!("- SCHEDULE INTEGRATION: System execution via Schedule.run() like real Bevy apps");
+    println!("- CHANGE DETECTION: Added<Pane>/Changed<Pane> filters report new and resized panes");
+    println!("- QUERY FILTERS: With/Without find panes and datasets with no subscriptions");
     println!(
         "- BUILT-IN RELATIONSHIPS: #[relationship] and #[relationship_target] for semantic connections"
     );
@@ -434,4 +715,176 @@ pub fn main() {
     println!("- World introspection and archetype analysis");
     println!("- Automatic bidirectional relationship management");
     println!("- Modern Rust API with comprehensive derive macros");
+
+    subscriptions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Same script as `run`
+    const DEMO_PANES: [&[&str]; 3] = [
+        &["temperature_sensor_1", "humidity_sensor_1"],
+        &["humidity_sensor_1"],
+        &["temperature_sensor_1", "pressure_sensor_1"],
+    ];
+
+    // The demo panes created in one schedule run, in creation order
+    fn demo_world() -> (World, Schedule, Vec<PaneHandle>) {
+        let (mut world, mut schedule) = command_world();
+        for dataset_ids in DEMO_PANES {
+            enqueue_command(
+                &mut world,
+                Command::create_pane(dataset_ids.iter().map(|&id| DatasetId(id)).collect()),
+            );
+        }
+        schedule.run(&mut world);
+
+        let panes = world
+            .resource::<CreatedPanes>()
+            .panes
+            .iter()
+            .map(|(_, pane)| *pane)
+            .collect();
+        (world, schedule, panes)
+    }
+
+    fn find_dataset(world: &mut World, id: &str) -> DatasetHandle {
+        world
+            .query::<(Entity, &DatasetId)>()
+            .iter(world)
+            .find(|(_, dataset_id)| dataset_id.0 == id)
+            .map(|(entity, _)| DatasetHandle::new(entity))
+            .unwrap()
+    }
+
+    #[test]
+    fn unsubscribing_keeps_the_panes_other_dataset() {
+        let (mut world, mut schedule, panes) = demo_world();
+        let temperature = find_dataset(&mut world, "temperature_sensor_1");
+        let humidity = find_dataset(&mut world, "humidity_sensor_1");
+
+        enqueue_command(
+            &mut world,
+            Command::UnsubscribeDataset {
+                pane: panes[0],
+                dataset: humidity,
+            },
+        );
+        schedule.run(&mut world);
+
+        assert_eq!(get_panes_for_dataset(&world, humidity), [panes[1]]);
+        assert_eq!(get_datasets_for_pane(&world, panes[0]), [temperature]);
+    }
+
+    #[test]
+    fn a_pane_keeps_both_of_its_datasets() {
+        let (mut world, _, panes) = demo_world();
+        let temperature = find_dataset(&mut world, "temperature_sensor_1");
+        let humidity = find_dataset(&mut world, "humidity_sensor_1");
+
+        let mut datasets = get_datasets_for_pane(&world, panes[0]);
+        datasets.sort_by_key(|dataset| dataset.entity());
+        let mut expected = vec![temperature, humidity];
+        expected.sort_by_key(|dataset| dataset.entity());
+        assert_eq!(datasets, expected);
+    }
+
+    #[test]
+    fn change_detection_reports_added_then_resized_panes() {
+        let (mut world, mut schedule, panes) = demo_world();
+
+        // Every pane is new on the first run, and none has been resized yet
+        let changes = world.resource::<PaneChanges>().clone();
+        assert_eq!(changes.added.len(), panes.len());
+        assert!(panes.iter().all(|pane| changes.added.contains(pane)));
+        assert!(changes.resized.is_empty());
+
+        enqueue_command(
+            &mut world,
+            Command::ResizePane {
+                pane: panes[1],
+                width: 320,
+                height: 240,
+            },
+        );
+        schedule.run(&mut world);
+
+        let changes = world.resource::<PaneChanges>().clone();
+        assert!(changes.added.is_empty());
+        assert_eq!(changes.resized, [panes[1]]);
+    }
+
+    #[test]
+    fn with_without_filters_find_orphans() {
+        let (mut world, mut schedule, panes) = demo_world();
+        enqueue_command(&mut world, Command::DeletePane { pane: panes[2] });
+        enqueue_command(&mut world, Command::create_pane(Vec::new()));
+        schedule.run(&mut world);
+        let empty_pane = world
+            .resource::<CreatedPanes>()
+            .panes
+            .last()
+            .map(|(_, handle)| *handle)
+            .unwrap();
+
+        let (subscribed, orphaned) = panes_by_subscription(&mut world);
+        assert_eq!(orphaned, [empty_pane]);
+        assert!(subscribed.contains(&panes[0]) && subscribed.contains(&panes[1]));
+        // The deleted pane was the pressure sensor's only subscriber
+        assert_eq!(
+            orphan_datasets(&mut world),
+            [DatasetId("pressure_sensor_1")]
+        );
+    }
+
+    // Bevy keeps the target in sync with the forward UsesDataset components,
+    // including after panes and their subscriptions are despawned
+    fn assert_subscribers_match_uses(world: &mut World) {
+        let datasets: Vec<(Entity, DatasetId)> = world
+            .query::<(Entity, &DatasetId)>()
+            .iter(world)
+            .map(|(entity, dataset_id)| (entity, *dataset_id))
+            .collect();
+        for (entity, dataset_id) in datasets {
+            let dataset = DatasetHandle::new(entity);
+            let mut from_target: Vec<Entity> = get_panes_for_dataset(world, dataset)
+                .iter()
+                .map(|p| p.entity())
+                .collect();
+            let mut from_forward: Vec<Entity> = scan_panes_for_dataset(world, dataset)
+                .iter()
+                .map(|p| p.entity())
+                .collect();
+            from_target.sort();
+            from_forward.sort();
+            assert_eq!(
+                from_target, from_forward,
+                "DatasetSubscribers out of sync with UsesDataset for {:?}",
+                dataset_id
+            );
+        }
+    }
+
+    #[test]
+    fn subscribers_stay_in_sync_with_uses_dataset() {
+        let (mut world, mut schedule, panes) = demo_world();
+        assert_subscribers_match_uses(&mut world);
+
+        enqueue_command(&mut world, Command::DeletePane { pane: panes[2] });
+        schedule.run(&mut world);
+        assert_subscribers_match_uses(&mut world);
+
+        let humidity = find_dataset(&mut world, "humidity_sensor_1");
+        enqueue_command(
+            &mut world,
+            Command::UnsubscribeDataset {
+                pane: panes[0],
+                dataset: humidity,
+            },
+        );
+        schedule.run(&mut world);
+        assert_subscribers_match_uses(&mut world);
+    }
 }
@@ -1,63 +1,40 @@
 #![allow(unused)]
-use evenio::prelude::*;
-use std::collections::VecDeque;
-
-// Macro to create type-safe entity handles
-macro_rules! entity_handles {
-    ($($handle_name:ident),* $(,)?) => {
-        $(
-            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-            pub struct $handle_name(EntityId);
-
-            impl $handle_name {
-                pub fn new(entity: EntityId) -> Self {
-                    Self(entity)
-                }
-
-                pub fn entity(&self) -> EntityId {
-                    self.0
-                }
-            }
+#[macro_use]
+mod handles;
+mod model;
+mod report;
 
-            impl From<EntityId> for $handle_name {
-                fn from(entity: EntityId) -> Self {
-                    Self(entity)
-                }
-            }
-
-            impl From<$handle_name> for EntityId {
-                fn from(handle: $handle_name) -> Self {
-                    handle.0
-                }
-            }
-        )*
-    };
-}
+use evenio::mutability::Immutable;
+use evenio::prelude::*;
+pub(crate) use model::DatasetId;
+use report::{OVERLAPPING_PANES, SubscriptionReport};
+use std::collections::{BTreeMap, VecDeque};
 
 // Create type-safe handles
-entity_handles! {
-    PaneHandle,
-    DatasetHandle,
-}
+entity_handles!(EntityId; PaneHandle, DatasetHandle);
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Component)]
-#[component(immutable)]
-struct DatasetId(&'static str);
+// What `#[derive(Component)] #[component(immutable)]` would generate; the shared
+// type can't carry the derive
+impl Component for DatasetId {
+    type Mutability = Immutable;
+}
 
 #[derive(Component, Default)]
-struct AllPanes {
-    panes: Vec<PaneHandle>,
+pub(crate) struct AllPanes {
+    pub(crate) panes: Vec<PaneHandle>,
 }
 
+// Mutable on purpose: ResizePane updates it in place
 #[derive(Component)]
-struct Pane {
-    width: u32,
-    height: u32,
+pub(crate) struct Pane {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
 }
 
 #[derive(Component)]
-struct PaneDatasets {
-    datasets: Vec<DatasetHandle>,
+#[component(immutable)]
+pub(crate) struct PaneDatasets {
+    pub(crate) datasets: Vec<DatasetHandle>,
 }
 
 #[derive(Component)]
@@ -66,8 +43,8 @@ struct DatasetSubscription {
 }
 
 #[derive(Component, Default)]
-struct DatasetIdToDatasetEntityLookup {
-    lookup: std::collections::HashMap<DatasetId, DatasetHandle>,
+pub(crate) struct DatasetIdToDatasetEntityLookup {
+    pub(crate) lookup: std::collections::HashMap<DatasetId, DatasetHandle>,
 }
 
 // Command system components
@@ -82,37 +59,123 @@ struct CreatedPanes {
 }
 
 // Command types
-#[derive(Debug, Clone)]
-pub enum Command {
-    CreatePaneWithDatasets { dataset_ids: Vec<DatasetId> },
-    DeletePane { pane: PaneHandle },
-}
+pub type Command = model::Command<PaneHandle, DatasetHandle>;
+
+// Sent to drain the command queue; the handler re-emits each command as its own event
+#[derive(GlobalEvent)]
+struct ProcessCommands;
 
-// Events can carry data, but for this example we only need a unit struct.
 #[derive(GlobalEvent)]
 struct CreatePaneWithDataset {
     datasets: Vec<DatasetId>,
 }
 
 #[derive(GlobalEvent)]
-struct ProcessCommands;
+struct DeletePane {
+    pane: PaneHandle,
+}
 
-struct AppRegistry {
-    pane_lookup: EntityId,
-    dataset_lookup: EntityId,
+// Sent by create_pane_handler for each new dataset so the lookup is maintained by
+// register_dataset_handler instead of inline
+#[derive(GlobalEvent)]
+struct DatasetSpawned {
+    id: DatasetId,
+    dataset: DatasetHandle,
+}
+
+#[derive(GlobalEvent)]
+struct ResizePane {
+    pane: PaneHandle,
+    width: u32,
+    height: u32,
+}
+
+pub(crate) struct AppRegistry {
+    pub(crate) pane_lookup: EntityId,
+    pub(crate) dataset_lookup: EntityId,
     command_queue: EntityId,
-    world: World,
+    pub(crate) world: World,
 }
 
-fn create_pane_with_datasets(
-    world: &mut World,
-    dataset_ids: Vec<DatasetId>,
-    pane_lookup: EntityId,
-    dataset_lookup: EntityId,
-) -> PaneHandle {
+impl AppRegistry {
+    // World with the lookup and command queue entities and every command handler
+    fn new() -> Self {
+        // Create a new `World` to store all our data.
+        let mut world = World::new();
+
+        let dataset_lookup = world.spawn();
+        world.insert(dataset_lookup, DatasetIdToDatasetEntityLookup::default());
+        let pane_lookup = world.spawn();
+        world.insert(pane_lookup, AllPanes::default());
+
+        // Create command queue entity
+        let command_entity = world.spawn();
+        world.insert(
+            command_entity,
+            CommandQueue {
+                commands: VecDeque::new(),
+            },
+        );
+        world.insert(command_entity, CreatedPanes { panes: Vec::new() });
+
+        // Commands are applied by event handlers instead of a manually called system
+        world.add_handler(process_commands_handler);
+        world.add_handler(create_pane_handler);
+        world.add_handler(delete_pane_handler);
+        world.add_handler(resize_pane_handler);
+        // Registries follow spawns and despawns instead of being edited by the command handlers
+        world.add_handler(register_pane_handler);
+        world.add_handler(unregister_pane_handler);
+        world.add_handler(register_dataset_handler);
+        world.add_handler(unregister_dataset_handler);
+
+        Self {
+            pane_lookup,
+            dataset_lookup,
+            command_queue: command_entity,
+            world,
+        }
+    }
+
+    // Counts entities carrying a DatasetId rather than trusting the lookup, which
+    // would hide a duplicate spawn behind a single map entry
+    fn dataset_count(&self) -> usize {
+        self.world
+            .archetypes()
+            .iter()
+            .flat_map(|archetype| archetype.entity_ids())
+            .filter(|&&entity| self.world.get::<DatasetId>(entity).is_some())
+            .count()
+    }
+}
+
+// Handlers can't borrow the world mutably, so entities and components are created
+// through the sender. Events are handled depth-first, which means the spawns and
+// inserts from one pane are applied before the next CreatePaneWithDataset runs.
+fn create_pane_handler(
+    r: Receiver<CreatePaneWithDataset>,
+    Single(lookup): Single<&DatasetIdToDatasetEntityLookup>,
+    Single(created): Single<&mut CreatedPanes>,
+    mut subscriptions: Fetcher<&mut DatasetSubscription>,
+    mut sender: Sender<(
+        Spawn,
+        Insert<Pane>,
+        Insert<PaneDatasets>,
+        Insert<DatasetId>,
+        Insert<DatasetSubscription>,
+        DatasetSpawned,
+    )>,
+) {
+    let dataset_ids = r.event.datasets.clone();
+    println!(
+        "[Handler] Handling CreatePaneWithDataset with {} datasets",
+        dataset_ids.len()
+    );
+
+    // region: spawn
     // Create the pane entity
-    let pane_entity = world.spawn();
-    world.insert(
+    let pane_entity = sender.spawn();
+    sender.insert(
         pane_entity,
         Pane {
             width: 100,
@@ -120,52 +183,151 @@ fn create_pane_with_datasets(
         },
     );
     let pane_handle = PaneHandle::new(pane_entity);
+    // endregion: spawn
 
     let mut dataset_handles = Vec::new();
 
-    for dataset_id in dataset_ids {
-        // Check if dataset already exists
-        let existing_dataset = {
-            let lookup = world
-                .get::<DatasetIdToDatasetEntityLookup>(dataset_lookup)
-                .unwrap();
-            lookup.lookup.get(&dataset_id).cloned()
-        };
+    for (i, &dataset_id) in dataset_ids.iter().enumerate() {
+        // A repeated id would otherwise look up its own not-yet-inserted subscription
+        if dataset_ids[..i].contains(&dataset_id) {
+            continue;
+        }
 
-        let dataset_handle = if let Some(existing) = existing_dataset {
+        // region: spawn
+        let dataset_handle = if let Some(&existing) = lookup.lookup.get(&dataset_id) {
+            // Maintain the reverse index: dataset -> subscribing panes
+            subscriptions
+                .get_mut(existing.entity())
+                .unwrap()
+                .panes
+                .push(pane_handle);
             existing
         } else {
-            // Create a new dataset entity
-            let dataset_entity = world.spawn();
-            world.insert(dataset_entity, dataset_id.clone());
+            // Create a new dataset entity whose reverse index starts with this pane
+            let dataset_entity = sender.spawn();
+            sender.insert(dataset_entity, dataset_id);
+            sender.insert(
+                dataset_entity,
+                DatasetSubscription {
+                    panes: vec![pane_handle],
+                },
+            );
             let dataset_handle = DatasetHandle::new(dataset_entity);
-
-            // Update lookup
-            let mut lookup = world
-                .get_mut::<DatasetIdToDatasetEntityLookup>(dataset_lookup)
-                .unwrap();
-            lookup.lookup.insert(dataset_id, dataset_handle);
+            sender.send(DatasetSpawned {
+                id: dataset_id,
+                dataset: dataset_handle,
+            });
             dataset_handle
         };
+        // endregion: spawn
 
         dataset_handles.push(dataset_handle);
     }
 
-    world.insert(
+    // region: relate
+    sender.insert(
         pane_entity,
         PaneDatasets {
             datasets: dataset_handles,
         },
     );
+    // endregion: relate
+
+    // Record the pane in creation order; AllPanes picks it up from the Insert<Pane> event
+    created.panes.push((dataset_ids, pane_handle));
+    println!("[Handler] Created pane: {:?}", pane_handle);
+}
+
+fn delete_pane_handler(
+    r: Receiver<DeletePane>,
+    Single(created): Single<&mut CreatedPanes>,
+    pane_datasets: Fetcher<&PaneDatasets>,
+    mut subscriptions: Fetcher<&mut DatasetSubscription>,
+    mut sender: Sender<Despawn>,
+) {
+    let pane = r.event.pane;
+    println!("[Handler] Handling DeletePane for {:?}", pane);
+
+    // region: despawn
+    // Drop the pane from each subscribed dataset's reverse index before despawning
+    if let Ok(pane_datasets) = pane_datasets.get(pane.entity()) {
+        for dataset in &pane_datasets.datasets {
+            if let Ok(subscription) = subscriptions.get_mut(dataset.entity()) {
+                subscription.panes.retain(|&h| h != pane);
+            }
+        }
+    }
 
-    // Add pane to the all_panes registry
-    let mut all_panes = world.get_mut::<AllPanes>(pane_lookup).unwrap();
-    all_panes.panes.push(pane_handle);
+    sender.despawn(pane.entity());
+    // endregion: despawn
+    created.panes.retain(|(_, h)| *h != pane);
+}
+
+// Every entity that gets a Pane joins AllPanes, whichever handler spawned it
+fn register_pane_handler(
+    r: Receiver<Insert<Pane>, EntityId>,
+    Single(all_panes): Single<&mut AllPanes>,
+) {
+    all_panes.panes.push(PaneHandle::new(r.query));
+}
+
+// Runs before the despawn is applied, while the entity still has its Pane
+fn unregister_pane_handler(
+    r: Receiver<Despawn, (EntityId, With<&Pane>)>,
+    Single(all_panes): Single<&mut AllPanes>,
+) {
+    let (entity, _) = r.query;
+    all_panes.panes.retain(|&h| h.entity() != entity);
+}
+
+fn register_dataset_handler(
+    r: Receiver<DatasetSpawned>,
+    Single(lookup): Single<&mut DatasetIdToDatasetEntityLookup>,
+) {
+    lookup.lookup.insert(r.event.id, r.event.dataset);
+}
 
-    pane_handle
+fn unregister_dataset_handler(
+    r: Receiver<Despawn, &DatasetId>,
+    Single(lookup): Single<&mut DatasetIdToDatasetEntityLookup>,
+) {
+    lookup.lookup.remove(r.query);
+}
+
+// Handlers can't take `&mut World`, so the mutable access goes through a
+// `Fetcher<&mut Pane>`, the handler-side counterpart of `world.get_mut::<Pane>`
+fn resize_pane_handler(r: Receiver<ResizePane>, mut panes: Fetcher<&mut Pane>) {
+    let &ResizePane {
+        pane,
+        width,
+        height,
+    } = r.event;
+    println!(
+        "[Handler] Handling ResizePane for {:?} to {}x{}",
+        pane, width, height
+    );
+
+    match panes.get_mut(pane.entity()) {
+        Ok(pane_component) => {
+            pane_component.width = width;
+            pane_component.height = height;
+        }
+        Err(_) => println!("[Handler] No pane {:?} to resize", pane),
+    }
 }
 
-fn get_panes_for_dataset(
+fn get_panes_for_dataset(world: &World, dataset: DatasetHandle) -> Vec<PaneHandle> {
+    // region: query-reverse
+    // Read the reverse index maintained on the dataset entity
+    world
+        .get::<DatasetSubscription>(dataset.entity())
+        .map(|subscription| subscription.panes.clone())
+        .unwrap_or_default()
+    // endregion: query-reverse
+}
+
+// Brute-force lookup over every pane, kept to cross-check the reverse index
+fn scan_panes_for_dataset(
     world: &World,
     dataset: DatasetHandle,
     pane_lookup: EntityId,
@@ -184,63 +346,39 @@ fn get_panes_for_dataset(
     subscribing_panes
 }
 
-// Command processing system
-fn process_commands_system(
-    world: &mut World,
-    command_entity: EntityId,
-    pane_lookup: EntityId,
-    dataset_lookup: EntityId,
+// Command processing handler: drains the queue and sends one event per command
+fn process_commands_handler(
+    _: Receiver<ProcessCommands>,
+    Single(queue): Single<&mut CommandQueue>,
+    mut sender: Sender<(CreatePaneWithDataset, DeletePane, ResizePane)>,
 ) {
-    // Get and process all pending commands
-    let commands: Vec<Command> = {
-        let mut queue = world.get_mut::<CommandQueue>(command_entity).unwrap();
-        queue.commands.drain(..).collect()
-    };
-
-    // Process commands and collect results
-    let mut new_panes = Vec::new();
-    let mut deleted_panes = Vec::new();
-
-    for cmd in commands {
+    println!("[Handler] Processing {} commands", queue.commands.len());
+    for cmd in queue.commands.drain(..) {
         match cmd {
-            Command::CreatePaneWithDatasets { dataset_ids } => {
-                println!(
-                    "[System] Processing CreatePaneWithDatasets command with {} datasets",
-                    dataset_ids.len()
-                );
-                let pane_handle = create_pane_with_datasets(
-                    world,
-                    dataset_ids.clone(),
-                    pane_lookup,
-                    dataset_lookup,
-                );
-                new_panes.push((dataset_ids, pane_handle));
-                println!("[System] Created pane: {:?}", pane_handle);
+            Command::CreatePaneWithDatasets { datasets, .. } => {
+                sender.send(CreatePaneWithDataset {
+                    datasets: model::dataset_ids(&datasets),
+                });
             }
             Command::DeletePane { pane } => {
-                println!("[System] Processing DeletePane command for {:?}", pane);
-                world.despawn(pane.entity());
-                deleted_panes.push(pane);
+                sender.send(DeletePane { pane });
+            }
+            Command::ResizePane {
+                pane,
+                width,
+                height,
+            } => {
+                sender.send(ResizePane {
+                    pane,
+                    width,
+                    height,
+                });
+            }
+            Command::UnsubscribeDataset { .. } => {
+                println!("[Handler] {:?} is not supported by this example", cmd);
             }
         }
     }
-
-    // Update created_panes tracking after processing
-    {
-        let mut created = world.get_mut::<CreatedPanes>(command_entity).unwrap();
-        for new_pane in new_panes {
-            created.panes.push(new_pane);
-        }
-        for deleted_pane in &deleted_panes {
-            created.panes.retain(|(_, h)| *h != *deleted_pane);
-        }
-    }
-
-    // Remove deleted panes from all_panes registry
-    for deleted_pane in deleted_panes {
-        let mut all_panes = world.get_mut::<AllPanes>(pane_lookup).unwrap();
-        all_panes.panes.retain(|&h| h != deleted_pane);
-    }
 }
 
 // Helper to enqueue commands
@@ -249,7 +387,7 @@ fn enqueue_command(world: &mut World, command_entity: EntityId, cmd: Command) {
     queue.commands.push_back(cmd);
 }
 
-fn dump_subscriptions_by_dataset(world: &World, dataset_lookup: EntityId, pane_lookup: EntityId) {
+fn dump_subscriptions_by_dataset(world: &World, dataset_lookup: EntityId) {
     // Print all datasets and their subscriptions
     println!("\n=== Dataset Subscriptions ===");
 
@@ -261,7 +399,7 @@ fn dump_subscriptions_by_dataset(world: &World, dataset_lookup: EntityId, pane_l
         println!("  Handle: {:?}", dataset_handle);
 
         // Use the dedicated function to get panes for this dataset
-        let subscribing_panes = get_panes_for_dataset(&world, dataset_handle, pane_lookup);
+        let subscribing_panes = get_panes_for_dataset(&world, dataset_handle);
 
         if !subscribing_panes.is_empty() {
             println!(
@@ -275,31 +413,93 @@ fn dump_subscriptions_by_dataset(world: &World, dataset_lookup: EntityId, pane_l
     }
 }
 
+// Normalized subscriptions: dataset id -> sorted indices (into `panes`) of subscribing panes
+pub(crate) fn subscription_map(
+    world: &World,
+    dataset_lookup: EntityId,
+    pane_lookup: EntityId,
+    panes: &[PaneHandle],
+) -> SubscriptionReport {
+    let mut subscriptions = BTreeMap::new();
+
+    let lookup = world
+        .get::<DatasetIdToDatasetEntityLookup>(dataset_lookup)
+        .unwrap();
+    for (&dataset_id, &dataset_handle) in &lookup.lookup {
+        let mut indices: Vec<usize> = get_panes_for_dataset(world, dataset_handle)
+            .iter()
+            .filter_map(|pane| panes.iter().position(|p| p == pane))
+            .collect();
+        indices.sort();
+        subscriptions.insert(dataset_id.0, indices);
+    }
+
+    subscriptions
+}
+
 pub fn main() {
-    // Create a new `World` to store all our data.
-    let mut world = World::new();
+    let report = run();
+    report::print_report(&report);
+}
 
-    let dataset_lookup = world.spawn();
-    world.insert(dataset_lookup, DatasetIdToDatasetEntityLookup::default());
-    let pane_lookup = world.spawn();
-    world.insert(pane_lookup, AllPanes::default());
+// Dataset entities left after creating the overlapping panes with one ProcessCommands
+pub fn overlapping_dataset_count() -> usize {
+    let mut registry = AppRegistry::new();
+    for dataset_ids in OVERLAPPING_PANES {
+        enqueue_command(
+            &mut registry.world,
+            registry.command_queue,
+            Command::create_pane(dataset_ids.iter().map(|&id| DatasetId(id)).collect()),
+        );
+    }
+    registry.world.send(ProcessCommands);
+    registry.dataset_count()
+}
 
-    // Create command queue entity
-    let command_entity = world.spawn();
-    world.insert(
-        command_entity,
-        CommandQueue {
-            commands: VecDeque::new(),
+// The demo's create-three-panes-then-delete-the-third state, without the dumps.
+// Returns every pane that was created, in creation order
+pub(crate) fn demo_registry() -> (AppRegistry, Vec<PaneHandle>) {
+    let mut registry = AppRegistry::new();
+    let demo_panes: [&[&'static str]; 3] = [
+        &["temperature_sensor_1", "humidity_sensor_1"],
+        &["humidity_sensor_1"],
+        &["temperature_sensor_1", "pressure_sensor_1"],
+    ];
+    for dataset_ids in demo_panes {
+        enqueue_command(
+            &mut registry.world,
+            registry.command_queue,
+            Command::create_pane(dataset_ids.iter().map(|&id| DatasetId(id)).collect()),
+        );
+    }
+    registry.world.send(ProcessCommands);
+
+    let pane_handles: Vec<PaneHandle> = registry
+        .world
+        .get::<CreatedPanes>(registry.command_queue)
+        .unwrap()
+        .panes
+        .iter()
+        .map(|(_, handle)| *handle)
+        .collect();
+    enqueue_command(
+        &mut registry.world,
+        registry.command_queue,
+        Command::DeletePane {
+            pane: pane_handles[2],
         },
     );
-    world.insert(command_entity, CreatedPanes { panes: Vec::new() });
+    registry.world.send(ProcessCommands);
 
-    let mut registry = AppRegistry {
-        pane_lookup,
-        dataset_lookup,
-        command_queue: command_entity,
-        world,
-    };
+    (registry, pane_handles)
+}
+
+// Runs the demo and returns the normalized subscriptions left after deletion
+pub fn run() -> SubscriptionReport {
+    let mut registry = AppRegistry::new();
+    let pane_lookup = registry.pane_lookup;
+    let dataset_lookup = registry.dataset_lookup;
+    let command_entity = registry.command_queue;
 
     println!("=== Command-Based Pane Creation Demo ===\n");
 
@@ -308,41 +508,30 @@ pub fn main() {
     enqueue_command(
         &mut registry.world,
         command_entity,
-        Command::CreatePaneWithDatasets {
-            dataset_ids: vec![
-                DatasetId("temperature_sensor_1"),
-                DatasetId("humidity_sensor_1"),
-            ],
-        },
+        Command::create_pane(vec![
+            DatasetId("temperature_sensor_1"),
+            DatasetId("humidity_sensor_1"),
+        ]),
     );
 
     enqueue_command(
         &mut registry.world,
         command_entity,
-        Command::CreatePaneWithDatasets {
-            dataset_ids: vec![DatasetId("humidity_sensor_1")],
-        },
+        Command::create_pane(vec![DatasetId("humidity_sensor_1")]),
     );
 
     enqueue_command(
         &mut registry.world,
         command_entity,
-        Command::CreatePaneWithDatasets {
-            dataset_ids: vec![
-                DatasetId("temperature_sensor_1"),
-                DatasetId("pressure_sensor_1"),
-            ],
-        },
+        Command::create_pane(vec![
+            DatasetId("temperature_sensor_1"),
+            DatasetId("pressure_sensor_1"),
+        ]),
     );
 
-    // Process commands through the system
-    println!("\nExecuting command processing system...\n");
-    process_commands_system(
-        &mut registry.world,
-        command_entity,
-        pane_lookup,
-        dataset_lookup,
-    );
+    // Process commands through the event handlers
+    println!("\nSending ProcessCommands...\n");
+    registry.world.send(ProcessCommands);
 
     // Get created panes from the command system
     let created = registry
@@ -379,7 +568,7 @@ pub fn main() {
         );
     }
 
-    dump_subscriptions_by_dataset(&registry.world, dataset_lookup, pane_lookup);
+    dump_subscriptions_by_dataset(&registry.world, dataset_lookup);
 
     // Use command to delete pane 3
     println!("\n=== Demonstrating Command-Based Deletion ===");
@@ -391,15 +580,30 @@ pub fn main() {
     );
 
     // Process the delete command
-    println!("Executing command processing system...\n");
-    process_commands_system(
+    println!("Sending ProcessCommands...\n");
+    registry.world.send(ProcessCommands);
+
+    dump_subscriptions_by_dataset(&registry.world, dataset_lookup);
+    let subscriptions =
+        subscription_map(&registry.world, dataset_lookup, pane_lookup, &pane_handles);
+
+    // Resize pane 1 through the queue; later reads must see the new dimensions
+    println!("\n=== Demonstrating Command-Based Resize ===");
+    println!("Enqueueing resize command for pane 1...");
+    enqueue_command(
         &mut registry.world,
         command_entity,
-        pane_lookup,
-        dataset_lookup,
+        Command::ResizePane {
+            pane: pane1,
+            width: 320,
+            height: 240,
+        },
     );
+    println!("Sending ProcessCommands...\n");
+    registry.world.send(ProcessCommands);
 
-    dump_subscriptions_by_dataset(&registry.world, dataset_lookup, pane_lookup);
+    let resized = registry.world.get::<Pane>(pane1.entity()).unwrap();
+    println!("Pane 1 is now {}x{}", resized.width, resized.height);
 
     // Print world statistics
     println!("\n=== World Statistics ===");
@@ -459,7 +663,7 @@ pub fn main() {
     }
 
     // Demonstrate type safety - these would be compile errors:
-    // let wrong_panes = get_panes_for_dataset(&registry.world, pane1, pane_lookup); // Error: expected DatasetHandle, found PaneHandle
+    // let wrong_panes = get_panes_for_dataset(&registry.world, pane1); // Error: expected DatasetHandle, found PaneHandle
     // let mixed_handles: Vec<EntityId> = vec![pane1.entity(), dataset1.entity()]; // Error: can't mix handle types
 
     println!("\n=== Evenio Example Complete ===");
@@ -474,4 +678,74 @@ pub fn main() {
     println!("- Registry pattern for entity management");
     println!("- World introspection and archetype analysis");
     println!("- Manual relationship management with Vec<Handle>");
+
+    subscriptions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dataset(registry: &AppRegistry, id: &'static str) -> DatasetHandle {
+        registry
+            .world
+            .get::<DatasetIdToDatasetEntityLookup>(registry.dataset_lookup)
+            .unwrap()
+            .lookup[&DatasetId(id)]
+    }
+
+    #[test]
+    fn reverse_index_matches_pane_scan() {
+        let (registry, panes) = demo_registry();
+
+        let lookup = registry
+            .world
+            .get::<DatasetIdToDatasetEntityLookup>(registry.dataset_lookup)
+            .unwrap();
+        for (id, &dataset) in &lookup.lookup {
+            assert_eq!(
+                get_panes_for_dataset(&registry.world, dataset),
+                scan_panes_for_dataset(&registry.world, dataset, registry.pane_lookup),
+                "DatasetSubscription index out of sync for {:?}",
+                id
+            );
+        }
+
+        // The deleted third pane no longer subscribes to temperature or pressure
+        let humidity = dataset(&registry, "humidity_sensor_1");
+        let temperature = dataset(&registry, "temperature_sensor_1");
+        let pressure = dataset(&registry, "pressure_sensor_1");
+        assert_eq!(
+            get_panes_for_dataset(&registry.world, humidity),
+            [panes[0], panes[1]]
+        );
+        assert_eq!(
+            get_panes_for_dataset(&registry.world, temperature),
+            [panes[0]]
+        );
+        assert!(get_panes_for_dataset(&registry.world, pressure).is_empty());
+    }
+
+    #[test]
+    fn resize_command_updates_pane() {
+        let (mut registry, panes) = demo_registry();
+        let other = registry.world.get::<Pane>(panes[1].entity()).unwrap();
+        let other_before = (other.width, other.height);
+
+        enqueue_command(
+            &mut registry.world,
+            registry.command_queue,
+            Command::ResizePane {
+                pane: panes[0],
+                width: 320,
+                height: 240,
+            },
+        );
+        registry.world.send(ProcessCommands);
+
+        let resized = registry.world.get::<Pane>(panes[0].entity()).unwrap();
+        assert_eq!((resized.width, resized.height), (320, 240));
+        let other = registry.world.get::<Pane>(panes[1].entity()).unwrap();
+        assert_eq!((other.width, other.height), other_before);
+    }
 }
@@ -1,47 +1,20 @@
 #![allow(unused)]
-use flax::*;
-use std::collections::{HashMap, VecDeque};
-
-// Macro to create type-safe entity handles
-macro_rules! entity_handles {
-    ($($handle_name:ident),* $(,)?) => {
-        $(
-            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-            pub struct $handle_name(Entity);
-
-            impl $handle_name {
-                pub fn new(entity: Entity) -> Self {
-                    Self(entity)
-                }
-
-                pub fn entity(&self) -> Entity {
-                    self.0
-                }
-            }
-
-            impl From<Entity> for $handle_name {
-                fn from(entity: Entity) -> Self {
-                    Self(entity)
-                }
-            }
+#[macro_use]
+mod handles;
+mod backend;
+mod model;
+mod report;
 
-            impl From<$handle_name> for Entity {
-                fn from(handle: $handle_name) -> Self {
-                    handle.0
-                }
-            }
-        )*
-    };
-}
+use backend::EcsBackend;
+use flax::*;
+pub(crate) use model::DEFAULT_DATASET_WEIGHT;
+pub use model::DatasetId;
+use report::SubscriptionReport;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::time::{Duration, Instant};
 
 // Create type-safe handles
-entity_handles! {
-    PaneHandle,
-    DatasetHandle,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct DatasetId(&'static str);
+entity_handles!(Entity; PaneHandle, DatasetHandle);
 
 pub mod pane {
     use flax::component;
@@ -50,12 +23,13 @@ pub mod pane {
         // Pane components
         pub width: u32,
         pub height: u32,
-        pub(crate) uses_dataset(dataset): (),
+        // Subscription weight, e.g. the sampling rate the pane reads the dataset at
+        pub(crate) uses_dataset(dataset): f32,
     }
 }
 
 pub mod dataset {
-    use crate::DatasetId;
+    use super::DatasetId;
     use flax::component;
 
     component! {
@@ -66,35 +40,80 @@ pub mod dataset {
     }
 }
 
+// Components for the schedule comparison. Each unit system writes a different
+// component, so none of them conflict and `execute_par` can run them together.
+pub mod unit {
+    use flax::component;
+
+    component! {
+        pub health: f32,
+        pub damage_per_tick: f32,
+        pub mana: f32,
+        pub mana_regen: f32,
+        pub level: u32,
+    }
+}
+
 // Command system components
 component! {
     // Command queue - singleton entity holds all commands
     pane_command_queue: VecDeque<Command>,
+    // Subscription events - drained by subscription_events_system
+    subscription_events: VecDeque<PaneSubscribed>,
+    // Panes created by commands, in creation order, with the datasets they were asked for
+    created_panes: Vec<(Vec<DatasetId>, PaneHandle)>,
+    // Most subscribers a dataset may have before it is reported as over-subscribed
+    subscriber_limit: usize,
+    // Datasets that hit the limit, with their subscriber count at the time
+    subscriber_alerts: Vec<(DatasetId, usize)>,
+    // Per-tick summaries written by the unit logging system
+    unit_log: Vec<String>,
     // Static entity, which is always alive
     resources,
 }
 
-// Command types
+// Event emitted whenever a pane starts using a dataset
 #[derive(Debug, Clone)]
-pub enum Command {
-    CreatePaneWithDatasets { dataset_ids: Vec<DatasetId> },
-    DeletePane { pane: PaneHandle },
+pub struct PaneSubscribed {
+    pub pane: PaneHandle,
+    pub dataset: DatasetHandle,
 }
 
-fn create_pane_with_datasets(
+// Default for the `subscriber_limit` resource
+const DEFAULT_SUBSCRIBER_LIMIT: usize = 3;
+
+// Size of the schedule comparison: units in the world and ticks run per executor
+const UNIT_COUNT: usize = 100_000;
+const UNIT_TICKS: usize = 50;
+const MAX_MANA: f32 = 100.0;
+
+// Command types. Omitted pane dimensions fall back to `default_pane_width` of the
+// command's queue index by 200
+pub type Command = model::Command<PaneHandle, DatasetHandle>;
+
+// Width of the `index`-th pane when its command gives none, so later panes are wider
+fn default_pane_width(index: usize) -> u32 {
+    100 * (index as u32 + 1)
+}
+
+// Each dataset id is paired with the weight stored on its `uses_dataset` relation
+pub(crate) fn create_pane_with_datasets(
     world: &mut World,
-    dataset_ids: Vec<DatasetId>,
+    datasets: Vec<(DatasetId, f32)>,
     width: u32,
     height: u32,
 ) -> PaneHandle {
+    // region: spawn
     // Create the pane entity
     let pane_entity = Entity::builder()
         .set(pane::width(), width)
         .set(pane::height(), height)
         .spawn(world);
     let pane = PaneHandle::new(pane_entity);
+    // endregion: spawn
 
-    for ds in dataset_ids {
+    for (ds, weight) in datasets {
+        // region: spawn
         // Find existing dataset by querying all datasets
         let mut existing_dataset = None;
         {
@@ -115,30 +134,152 @@ fn create_pane_with_datasets(
             let dataset_entity = Entity::builder().set(dataset::id(), ds).spawn(world);
             DatasetHandle::new(dataset_entity)
         };
+        // endregion: spawn
 
+        // region: relate
         // Create the relation: pane uses dataset
         world
-            .set(pane.entity(), pane::uses_dataset(dataset.entity()), ())
+            .set(pane.entity(), pane::uses_dataset(dataset.entity()), weight)
             .unwrap();
 
         // Create the reverse relation: dataset is subscribed by pane
         world
             .set(dataset.entity(), dataset::subscribed_by(pane.entity()), ())
             .unwrap();
+        // endregion: relate
+
+        // Let downstream systems react to the new subscription
+        if let Ok(mut events) = world.get_mut(resources(), subscription_events()) {
+            events.push_back(PaneSubscribed { pane, dataset });
+        }
     }
 
     pane
 }
 
-fn get_panes_for_dataset(world: &World, dataset: DatasetHandle) -> Vec<PaneHandle> {
+// Subscribing panes with the weight each one gave the subscription
+pub(crate) fn get_panes_for_dataset(
+    world: &World,
+    dataset: DatasetHandle,
+) -> Vec<(PaneHandle, f32)> {
+    // region: query-reverse
     let mut subscribing_panes = Vec::new();
     let mut relation_query = Query::new(relations_like(dataset::subscribed_by));
     if let Ok(relations) = relation_query.borrow(world).get(dataset.entity()) {
         for (target, _) in relations {
-            subscribing_panes.push(PaneHandle::new(target));
+            // The weight lives on the pane's side of the relation pair
+            let weight = world
+                .get(target, pane::uses_dataset(dataset.entity()))
+                .map(|weight| *weight)
+                .unwrap_or(DEFAULT_DATASET_WEIGHT);
+            subscribing_panes.push((PaneHandle::new(target), weight));
         }
     }
     subscribing_panes
+    // endregion: query-reverse
+}
+
+pub(crate) fn delete_pane(world: &mut World, pane: PaneHandle) {
+    // region: despawn
+    // Find the datasets this pane uses so their reverse relations can be removed eagerly
+    let used_datasets: Vec<DatasetHandle> = {
+        let mut relation_query = Query::new(relations_like(pane::uses_dataset));
+        match relation_query.borrow(world).get(pane.entity()) {
+            Ok(relations) => relations
+                .map(|(target, _)| DatasetHandle::new(target))
+                .collect(),
+            Err(_) => Vec::new(),
+        }
+    };
+
+    // A reverse relation that is already gone needs no cleanup
+    for dataset in &used_datasets {
+        world
+            .remove(dataset.entity(), dataset::subscribed_by(pane.entity()))
+            .ok();
+    }
+
+    if world.despawn(pane.entity()).is_err() {
+        println!("[System] Pane {:?} was already deleted", pane);
+    }
+    // endregion: despawn
+}
+
+fn unsubscribe_dataset(world: &mut World, pane: PaneHandle, dataset: DatasetHandle) {
+    world
+        .remove(pane.entity(), pane::uses_dataset(dataset.entity()))
+        .ok();
+    world
+        .remove(dataset.entity(), dataset::subscribed_by(pane.entity()))
+        .ok();
+}
+
+// Post-hook for pane creation: warns about every dataset of `pane` whose subscriber
+// count exceeds the `subscriber_limit` resource and records it in `subscriber_alerts`
+fn check_subscriber_limit(world: &mut World, pane: PaneHandle) {
+    let Ok(limit) = world
+        .get(resources(), subscriber_limit())
+        .map(|limit| *limit)
+    else {
+        return;
+    };
+
+    let touched_datasets: Vec<DatasetHandle> = {
+        let mut relation_query = Query::new(relations_like(pane::uses_dataset));
+        match relation_query.borrow(world).get(pane.entity()) {
+            Ok(relations) => relations
+                .map(|(target, _)| DatasetHandle::new(target))
+                .collect(),
+            Err(_) => Vec::new(),
+        }
+    };
+
+    for dataset in touched_datasets {
+        let subscribers = get_panes_for_dataset(world, dataset).len();
+        if subscribers <= limit {
+            continue;
+        }
+
+        let id = *world.get(dataset.entity(), dataset::id()).unwrap();
+        println!(
+            "[Warning] Dataset {:?} has {} subscribing panes (limit {})",
+            id.0, subscribers, limit
+        );
+        if let Ok(mut alerts) = world.get_mut(resources(), subscriber_alerts()) {
+            alerts.push((id, subscribers));
+        }
+    }
+}
+
+// Panes without a single uses_dataset relation. `without_relation` matches no
+// target at all, so there is no need to walk each pane's relations
+pub(crate) fn panes_without_datasets(world: &World) -> Vec<PaneHandle> {
+    Query::new(entity_ids())
+        .with(pane::width())
+        .without_relation(pane::uses_dataset)
+        .borrow(world)
+        .iter()
+        .map(PaneHandle::new)
+        .collect()
+}
+
+// Despawn datasets that no pane subscribes to anymore
+pub(crate) fn cleanup_orphan_datasets(world: &mut World) {
+    let mut orphaned_datasets = Vec::new();
+    {
+        let mut query =
+            Query::new((entity_ids(), relations_like(dataset::subscribed_by))).with(dataset::id());
+        for (entity, mut subscribers) in query.borrow(world).iter() {
+            if subscribers.next().is_none() {
+                orphaned_datasets.push(DatasetHandle::new(entity));
+            }
+        }
+    }
+
+    for dataset in orphaned_datasets {
+        println!("[System] Despawning orphaned dataset: {:?}", dataset);
+        world.despawn(dataset.entity()).unwrap();
+    }
 }
 
 // Command processing system
@@ -153,26 +294,65 @@ fn process_commands_system() -> BoxedSystem {
             let queue = resources.get().unwrap();
 
             println!("[System] Processing {} commands", queue.len());
-            // Note: In a real system, we'd need a way to access world here
-            // This is a limitation we'd need to work around
+            // Ordering guarantee: the queue is drained front to back and each command
+            // defers exactly one closure. The command buffer applies deferred closures
+            // in the order they were recorded, and the schedule runs sequentially, so
+            // panes are spawned in enqueue order and `index` maps to a stable width.
             for (index, cmd) in queue.drain(..).enumerate() {
                 match cmd {
-                    Command::CreatePaneWithDatasets { dataset_ids } => {
+                    Command::CreatePaneWithDatasets { datasets, width, height } => {
                         println!(
                             "[System] Processing CreatePaneWithDatasets command with {} datasets",
-                            dataset_ids.len()
+                            datasets.len()
                         );
 
+                        let width = width.unwrap_or_else(|| default_pane_width(index));
+                        let height = height.unwrap_or(200);
+
                         cmdbuf.defer(move |world| {
 
-                        let pane_handle = create_pane_with_datasets(world, dataset_ids, 100 * (index as u32 + 1), 200);
+                        let dataset_ids = model::dataset_ids(&datasets);
+                        let pane_handle = create_pane_with_datasets(world, datasets, width, height);
                         println!("[System] Created pane: {:?}", pane_handle);
+                        check_subscriber_limit(world, pane_handle);
+                        // Record the handle so callers can read it back in creation order
+                        if let Ok(mut created) = world.get_mut(resources(), created_panes()) {
+                            created.push((dataset_ids, pane_handle));
+                        }
                         Ok(())
                         });
                     }
                     Command::DeletePane { pane } => {
                         println!("[System] Processing DeletePane command for {:?}", pane);
-                        cmdbuf.despawn(pane.entity());
+                        cmdbuf.defer(move |world| {
+                            delete_pane(world, pane);
+                            if let Ok(mut created) = world.get_mut(resources(), created_panes()) {
+                                created.retain(|(_, handle)| *handle != pane);
+                            }
+                            Ok(())
+                        });
+                    }
+                    Command::UnsubscribeDataset { pane, dataset } => {
+                        println!(
+                            "[System] Processing UnsubscribeDataset command for {:?} from {:?}",
+                            pane, dataset
+                        );
+                        cmdbuf.defer(move |world| {
+                            unsubscribe_dataset(world, pane, dataset);
+                            Ok(())
+                        });
+                    }
+                    Command::ResizePane { pane, width, height } => {
+                        println!(
+                            "[System] Processing ResizePane command for {:?} to {}x{}",
+                            pane, width, height
+                        );
+                        // `set` on components the pane already has replaces them in place
+                        cmdbuf.defer(move |world| {
+                            world.set(pane.entity(), pane::width(), width)?;
+                            world.set(pane.entity(), pane::height(), height)?;
+                            Ok(())
+                        });
                     }
                 }
             }
@@ -180,7 +360,61 @@ fn process_commands_system() -> BoxedSystem {
         .boxed()
 }
 
-fn dump_subscriptions_by_dataset(world: &World) {
+// Event system - drains and reports subscription events
+fn subscription_events_system() -> BoxedSystem {
+    System::builder()
+        .with_name("subscription_events")
+        .with_query(Query::new(subscription_events().as_mut()).entity(resources()))
+        .build(
+            |mut resources: EntityBorrow<'_, ComponentMut<VecDeque<PaneSubscribed>>>| {
+                let events = resources.get().unwrap();
+                for event in events.drain(..) {
+                    println!(
+                        "[Event] Pane {:?} subscribed to dataset {:?}",
+                        event.pane, event.dataset
+                    );
+                }
+            },
+        )
+        .boxed()
+}
+
+// Print all panes with their sizes and weighted datasets
+pub(crate) fn dump_panes(world: &World) {
+    println!("\n=== Panes ===");
+    let mut query = Query::new((entity_ids(), pane::width(), pane::height()));
+    let mut binding = query.borrow(world);
+    let pane_entities: Vec<_> = binding.iter().collect();
+
+    for (pane_entity, width, height) in pane_entities {
+        let pane_handle = PaneHandle::new(pane_entity);
+        println!("Pane Handle: {:?}", pane_handle);
+        println!("  Width: {}, Height: {}", *width, *height);
+
+        // Query relations: what datasets does this pane use?
+        // Use relations_like to efficiently get all uses_dataset relations for this pane
+        // relations_like works the same with the f32 weight as it did with ()
+        let mut this_pane_datasets = Vec::new();
+        let mut relation_query = Query::new((pane::width(), relations_like(pane::uses_dataset)));
+        if let Ok((width, relations)) = relation_query.borrow(world).get(pane_entity) {
+            println!("  Width: {}", *width);
+            for (target, &weight) in relations {
+                this_pane_datasets.push((DatasetHandle::new(target), weight));
+            }
+        }
+
+        if !this_pane_datasets.is_empty() {
+            println!("  Uses {} datasets:", this_pane_datasets.len());
+            for (dataset, weight) in &this_pane_datasets {
+                println!("    {:?} (weight {})", dataset, weight);
+            }
+        } else {
+            println!("  Uses no datasets");
+        }
+    }
+}
+
+pub(crate) fn dump_subscriptions_by_dataset(world: &World) {
     // Print all datasets and their subscriptions
     println!("\n=== Dataset Subscriptions ===");
 
@@ -207,124 +441,415 @@ fn dump_subscriptions_by_dataset(world: &World) {
     }
 }
 
-pub fn main() {
-    // Create a new flax world
-    let mut world = World::new();
+// Bipartite Graphviz graph of the subscriptions: panes in one cluster, datasets in
+// the other, and an edge labelled with the weight for every `uses_dataset` relation.
+// Nodes and edges are sorted by entity index so the output is stable across runs
+pub(crate) fn export_subscriptions_dot(world: &World) -> String {
+    let node = |entity: Entity| format!("e{}", entity.index());
+    let escape = |label: String| label.replace('"', "\\\"");
+
+    let mut panes: Vec<(Entity, u32, u32)> =
+        Query::new((entity_ids(), pane::width(), pane::height()))
+            .borrow(world)
+            .iter()
+            .map(|(entity, &width, &height)| (entity, width, height))
+            .collect();
+    panes.sort_by_key(|(entity, _, _)| entity.index());
+
+    let mut datasets: Vec<(Entity, DatasetId)> = Query::new((entity_ids(), dataset::id()))
+        .borrow(world)
+        .iter()
+        .map(|(entity, &id)| (entity, id))
+        .collect();
+    datasets.sort_by_key(|(entity, _)| entity.index());
+
+    let mut edges: Vec<(Entity, Entity, f32)> = Vec::new();
+    Query::new((entity_ids(), relations_like(pane::uses_dataset)))
+        .borrow(world)
+        .for_each(|(pane, relations)| {
+            for (dataset, &weight) in relations {
+                edges.push((pane, dataset, weight));
+            }
+        });
+    edges.sort_by_key(|(pane, dataset, _)| (pane.index(), dataset.index()));
+
+    let mut dot = String::from("digraph subscriptions {\n    rankdir=LR;\n");
+
+    dot.push_str("    subgraph cluster_panes {\n        label=\"Panes\";\n");
+    for (entity, width, height) in panes {
+        let label = escape(format!(
+            "{:?}\\n{}x{}",
+            PaneHandle::new(entity),
+            width,
+            height
+        ));
+        dot.push_str(&format!(
+            "        {} [shape=box, label=\"{}\"];\n",
+            node(entity),
+            label
+        ));
+    }
+    dot.push_str("    }\n");
+
+    dot.push_str("    subgraph cluster_datasets {\n        label=\"Datasets\";\n");
+    for (entity, id) in datasets {
+        let label = escape(format!("{:?}\\n{}", DatasetHandle::new(entity), id.0));
+        dot.push_str(&format!(
+            "        {} [shape=ellipse, label=\"{}\"];\n",
+            node(entity),
+            label
+        ));
+    }
+    dot.push_str("    }\n");
+
+    for (pane, dataset, weight) in edges {
+        dot.push_str(&format!(
+            "    {} -> {} [label=\"{}\"];\n",
+            node(pane),
+            node(dataset),
+            weight
+        ));
+    }
+    dot.push_str("}\n");
+    dot
+}
 
-    // Create command queue entity
-    Entity::builder()
-        .set(pane_command_queue(), VecDeque::new())
-        .append_to(&mut world, resources())
-        .unwrap();
+// Helper to enqueue commands
+fn enqueue_command(world: &mut World, cmd: Command) {
+    let mut queue = world.get_mut(resources(), pane_command_queue()).unwrap();
+    queue.push_back(cmd);
+}
 
-    println!("=== Command-Based Pane Creation Demo ===\n");
+// Drives the demo through the command queue and schedule
+struct FlaxBackend {
+    world: World,
+    command_exec_schedules: Schedule,
+    // Panes requested so far. Each request runs its own schedule pass, where the
+    // queue index is always 0, so the backend numbers the panes itself
+    panes_requested: usize,
+}
 
-    // Enqueue commands instead of direct creation
-    println!("Enqueueing commands...");
+impl FlaxBackend {
+    fn new() -> Self {
+        let mut world = World::new();
+
+        // Create command queue entity
+        Entity::builder()
+            .set(pane_command_queue(), VecDeque::new())
+            .set(subscription_events(), VecDeque::new())
+            .set(created_panes(), Vec::new())
+            .set(subscriber_limit(), DEFAULT_SUBSCRIBER_LIMIT)
+            .set(subscriber_alerts(), Vec::new())
+            .append_to(&mut world, resources())
+            .unwrap();
 
-    // Helper to enqueue commands
-    fn enqueue_command(world: &mut World, cmd: Command) {
-        let mut queue = world.get_mut(resources(), pane_command_queue()).unwrap();
-        queue.push_back(cmd);
+        // Flush in between so events from deferred pane creation are visible to the event system
+        let command_exec_schedules = Schedule::builder()
+            .with_system(process_commands_system())
+            .flush()
+            .with_system(subscription_events_system())
+            .build();
+
+        Self {
+            world,
+            command_exec_schedules,
+            panes_requested: 0,
+        }
     }
 
-    let query = Query::new(pane_command_queue()).entity(resources());
+    fn process_commands(&mut self) {
+        // Sequential execution keeps command application in enqueue order
+        self.command_exec_schedules.execute_seq(&mut self.world);
+        cleanup_orphan_datasets(&mut self.world);
+    }
 
-    enqueue_command(
-        &mut world,
-        Command::CreatePaneWithDatasets {
-            dataset_ids: vec![
-                DatasetId("temperature_sensor_1"),
-                DatasetId("humidity_sensor_1"),
-            ],
-        },
-    );
+    fn resize_pane(&mut self, pane: PaneHandle, width: u32, height: u32) {
+        enqueue_command(
+            &mut self.world,
+            Command::ResizePane {
+                pane,
+                width,
+                height,
+            },
+        );
+        self.process_commands();
+    }
 
-    enqueue_command(
-        &mut world,
-        Command::CreatePaneWithDatasets {
-            dataset_ids: vec![DatasetId("humidity_sensor_1")],
-        },
-    );
+    fn find_dataset(&self, dataset_id: &str) -> Option<DatasetHandle> {
+        Query::new((entity_ids(), dataset::id()))
+            .borrow(&self.world)
+            .iter()
+            .find(|(_, id)| id.0 == dataset_id)
+            .map(|(entity, _)| DatasetHandle::new(entity))
+    }
+}
 
-    enqueue_command(
-        &mut world,
-        Command::CreatePaneWithDatasets {
-            dataset_ids: vec![
-                DatasetId("temperature_sensor_1"),
-                DatasetId("pressure_sensor_1"),
-            ],
-        },
-    );
+impl EcsBackend for FlaxBackend {
+    type PaneHandle = PaneHandle;
+
+    fn create_pane_with_datasets(&mut self, dataset_ids: &[&'static str]) -> PaneHandle {
+        let created_before = self.world.get(resources(), created_panes()).unwrap().len();
+
+        enqueue_command(
+            &mut self.world,
+            Command::CreatePaneWithDatasets {
+                datasets: dataset_ids
+                    .iter()
+                    .map(|&id| (DatasetId(id), DEFAULT_DATASET_WEIGHT))
+                    .collect(),
+                width: Some(default_pane_width(self.panes_requested)),
+                height: None,
+            },
+        );
+        self.panes_requested += 1;
+        self.process_commands();
+
+        // The deferred closure appends each new pane, so ours follows the earlier entries
+        let created = self.world.get(resources(), created_panes()).unwrap();
+        let (_, pane_handle) = created
+            .get(created_before)
+            .expect("CreatePaneWithDatasets command did not spawn a pane");
+        *pane_handle
+    }
 
-    // Process commands through the system
-    println!("\nExecuting command processing system...\n");
-    let mut command_exec_schedules = Schedule::builder()
-        .with_system(process_commands_system())
-        .build();
+    fn delete_pane(&mut self, pane: PaneHandle) {
+        enqueue_command(&mut self.world, Command::DeletePane { pane });
+        self.process_commands();
+    }
 
-    command_exec_schedules.execute_par(&mut world);
+    fn unsubscribe_dataset(&mut self, pane: PaneHandle, dataset_id: &'static str) {
+        let Some(dataset) = self.find_dataset(dataset_id) else {
+            return;
+        };
+        enqueue_command(
+            &mut self.world,
+            Command::UnsubscribeDataset { pane, dataset },
+        );
+        self.process_commands();
+    }
 
-    // Get created panes from the command system
-    let mut pane_query = Query::new(entity_ids()).with(pane::width());
-    let pane_handles: Vec<PaneHandle> = pane_query
+    fn panes_for_dataset(&self, dataset_id: &str) -> Vec<PaneHandle> {
+        match self.find_dataset(dataset_id) {
+            Some(dataset) => get_panes_for_dataset(&self.world, dataset)
+                .into_iter()
+                .map(|(pane, _)| pane)
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    fn dump_panes(&self) {
+        dump_panes(&self.world);
+    }
+
+    fn dump_subscriptions(&self) {
+        dump_subscriptions_by_dataset(&self.world);
+    }
+
+    fn dataset_count(&self) -> usize {
+        Query::new(dataset::id()).borrow(&self.world).iter().count()
+    }
+}
+
+// Spawns `count` units with varied damage and level, plus the `unit_log` resource
+pub(crate) fn spawn_units(world: &mut World, count: usize) {
+    for i in 0..count {
+        Entity::builder()
+            .set(unit::health(), 100.0)
+            .set(unit::damage_per_tick(), (i % 10) as f32 * 0.01)
+            .set(unit::mana(), 0.0)
+            .set(unit::mana_regen(), 0.5)
+            .set(unit::level(), (i % 50) as u32 + 1)
+            .spawn(world);
+    }
+    world.set(resources(), unit_log(), Vec::new()).unwrap();
+}
+
+// Regen, damage and logging: three systems over disjoint components, so the
+// parallel executor may run all of them at once
+pub(crate) fn unit_schedule() -> Schedule {
+    use flax::query::QueryBorrow;
+
+    let regen_system = System::builder()
+        .with_name("regen")
+        .with_query(Query::new((unit::mana().as_mut(), unit::mana_regen())))
+        .build(
+            |mut query: QueryBorrow<(ComponentMut<f32>, Component<f32>)>| {
+                for (mana, &regen) in query.iter() {
+                    *mana = (*mana + regen).min(MAX_MANA);
+                }
+            },
+        )
+        .boxed();
+
+    let damage_system = System::builder()
+        .with_name("damage")
+        .with_query(Query::new((
+            unit::health().as_mut(),
+            unit::damage_per_tick(),
+        )))
+        .build(
+            |mut query: QueryBorrow<(ComponentMut<f32>, Component<f32>)>| {
+                for (health, &damage) in query.iter() {
+                    *health = (*health - damage).max(0.0);
+                }
+            },
+        )
+        .boxed();
+
+    // Reads only `level` and the log resource, so it never waits on the other two
+    let logging_system = System::builder()
+        .with_name("logging")
+        .with_query(Query::new(unit::level()))
+        .with_query(Query::new(unit_log().as_mut()).entity(resources()))
+        .build(
+            |mut levels: QueryBorrow<Component<u32>>,
+             mut resources: EntityBorrow<'_, ComponentMut<Vec<String>>>| {
+                let (count, total) = levels.iter().fold((0u64, 0u64), |(count, total), &level| {
+                    (count + 1, total + level as u64)
+                });
+                let log = resources.get().unwrap();
+                log.push(format!(
+                    "{} units, average level {:.1}",
+                    count,
+                    total as f64 / count.max(1) as f64
+                ));
+            },
+        )
+        .boxed();
+
+    Schedule::builder()
+        .with_system(regen_system)
+        .with_system(damage_system)
+        .with_system(logging_system)
+        .build()
+}
+
+// Runs the unit schedule for `ticks` on a fresh world of `units` units with one
+// executor. Returns the time taken, the total health left and the last log line
+fn run_unit_schedule(units: usize, ticks: usize, parallel: bool) -> (Duration, f64, String) {
+    let mut world = World::new();
+    spawn_units(&mut world, units);
+    let mut schedule = unit_schedule();
+
+    let start = Instant::now();
+    for _ in 0..ticks {
+        if parallel {
+            schedule.execute_par(&mut world).unwrap();
+        } else {
+            schedule.execute_seq(&mut world).unwrap();
+        }
+    }
+    let elapsed = start.elapsed();
+
+    let total_health: f64 = Query::new(unit::health())
         .borrow(&world)
         .iter()
-        .map(|entity| PaneHandle::new(entity))
-        .collect();
+        .map(|&health| health as f64)
+        .sum();
+    let last_log = world
+        .get(resources(), unit_log())
+        .unwrap()
+        .last()
+        .cloned()
+        .unwrap_or_else(|| "no log".to_string());
+    (elapsed, total_health, last_log)
+}
 
-    let pane1 = pane_handles[0];
-    let pane2 = pane_handles[1];
-    let pane3 = pane_handles[2];
+// Both executors apply the same per-entity updates, but the totals are summed
+// floats, so they only have to agree up to rounding
+fn totals_agree(a: f64, b: f64) -> bool {
+    (a - b).abs() <= 1e-9 * a.abs().max(b.abs()).max(1.0)
+}
 
-    // Print all panes
-    println!("\n=== Panes ===");
-    {
-        let mut query = Query::new((entity_ids(), pane::width(), pane::height()));
-        let mut binding = query.borrow(&world);
-        let pane_entities: Vec<_> = binding.iter().collect();
-
-        for (pane_entity, width, height) in pane_entities {
-            let pane_handle = PaneHandle::new(pane_entity);
-            println!("Pane Handle: {:?}", pane_handle);
-            println!("  Width: {}, Height: {}", *width, *height);
-
-            // Query relations: what datasets does this pane use?
-            // Use relations_like to efficiently get all uses_dataset relations for this pane
-            let mut this_pane_datasets = Vec::new();
-            let mut relation_query =
-                Query::new((pane::width(), relations_like(pane::uses_dataset)));
-            if let Ok((width, relations)) = relation_query.borrow(&world).get(pane_entity) {
-                println!("  Width: {}", *width);
-                for (target, _) in relations {
-                    this_pane_datasets.push(DatasetHandle::new(target));
-                }
-            }
+// Runs the unit schedule on identical worlds with `execute_seq` and `execute_par`
+// and reports the time for each and the speedup
+pub fn compare_schedules() {
+    println!(
+        "\n=== Sequential vs Parallel Schedule ({} units x {} ticks) ===",
+        UNIT_COUNT, UNIT_TICKS
+    );
 
-            if !this_pane_datasets.is_empty() {
-                println!(
-                    "  Uses {} datasets: {:?}",
-                    this_pane_datasets.len(),
-                    this_pane_datasets
-                );
-            } else {
-                println!("  Uses no datasets");
-            }
+    let mut results = Vec::new();
+    for parallel in [false, true] {
+        let (elapsed, total_health, last_log) = run_unit_schedule(UNIT_COUNT, UNIT_TICKS, parallel);
+        let label = if parallel {
+            "execute_par"
+        } else {
+            "execute_seq"
+        };
+        println!(
+            "{:<12} {:>10.3} ms  ({})",
+            label,
+            elapsed.as_secs_f64() * 1000.0,
+            last_log
+        );
+        results.push((elapsed, total_health));
+    }
+
+    let [(seq_time, seq_health), (par_time, par_health)] = results[..] else {
+        unreachable!("one run per executor");
+    };
+    println!(
+        "[Check] executors agree on total health ({:.3} vs {:.3}): {}",
+        seq_health,
+        par_health,
+        if totals_agree(seq_health, par_health) {
+            "ok"
+        } else {
+            "FAILED"
         }
+    );
+    println!(
+        "Speedup: {:.2}x",
+        seq_time.as_secs_f64() / par_time.as_secs_f64()
+    );
+}
+
+// The schedule comparison spawns UNIT_COUNT units and takes a while, so it only
+// runs when asked for: `cargo run --release --bin flax_example -- --schedules`
+pub fn main() {
+    let report = run();
+    report::print_report(&report);
+    if std::env::args().any(|arg| arg == "--schedules") {
+        compare_schedules();
     }
+}
 
-    dump_subscriptions_by_dataset(&world);
+// Dataset entities left after creating the overlapping panes through the command queue
+pub fn overlapping_dataset_count() -> usize {
+    backend::overlapping_dataset_count(&mut FlaxBackend::new())
+}
 
-    // Use command to delete pane 3
-    println!("\n=== Demonstrating Command-Based Deletion ===");
-    println!("Enqueueing delete command for pane 3...");
-    enqueue_command(&mut world, Command::DeletePane { pane: pane3 });
+// Runs the demo and returns the normalized subscriptions left after deletion
+pub fn run() -> SubscriptionReport {
+    // Create a new flax world behind the shared backend interface
+    let mut backend = FlaxBackend::new();
 
-    // Process the delete command
-    println!("Executing command processing system...\n");
-    command_exec_schedules.execute_par(&mut world);
+    println!("=== Command-Based Pane Creation Demo ===\n");
+
+    // Run the create/dump/delete/dump sequence shared by every backend
+    let pane_handles = backend::run_pane_demo(&mut backend);
+    let subscriptions = backend::subscription_map(&backend, &pane_handles);
+
+    // Flax relations are many-to-many, so dropping one subscription keeps the other
+    backend::run_unsubscribe_demo(&mut backend, &pane_handles);
+
+    println!("\n=== Demonstrating Command-Based Resize ===");
+    println!("Resizing pane 2 to 300x150...");
+    backend.resize_pane(pane_handles[1], 300, 150);
 
-    dump_subscriptions_by_dataset(&world);
+    // Every demo pane has a dataset, so add one that deliberately has none
+    println!("\n=== Orphan Panes ===");
+    println!("Creating a pane with no datasets...");
+    let empty_pane = backend.create_pane_with_datasets(&[]);
+    let orphan_panes = panes_without_datasets(&backend.world);
+    println!("Panes without datasets: {:?}", orphan_panes);
+
+    let world = backend.world;
+
+    // Final sizes, after the resize and the extra empty pane
+    dump_panes(&world);
 
     // Print world statistics
     println!("\n=== World Statistics ===");
@@ -419,22 +944,28 @@ pub fn main() {
         println!("Components: {:?}", components);
     }
 
-    // Show archetype information using queries
+    // Show archetype information straight from the world; relations such as
+    // `uses_dataset(target)` are part of the component set, so each distinct
+    // combination of subscribed datasets lands in its own archetype
     println!("\n=== Archetype Analysis ===");
-
-    // Query panes (entities with both width and height)
-    let pane_count = Query::new((pane::width(), pane::height()))
-        .borrow(&world)
-        .iter()
-        .count();
-    println!("Pane archetype: {} entities", pane_count);
-
-    // Query datasets (entities with dataset_id)
-    let dataset_count = Query::new(dataset::id()).borrow(&world).iter().count();
-    println!("Dataset archetype: {} entities", dataset_count);
+    let archetypes = world.archetype_info();
+    println!("Total archetypes: {}", archetypes.len());
+    for (id, info) in &archetypes {
+        if info.entities() == 0 {
+            continue;
+        }
+        let components: Vec<_> = info.components().iter().map(|desc| desc.name()).collect();
+        println!("Archetype {:?}:", id);
+        println!("  Entity count: {}", info.entities());
+        println!("  Components: {:?}", components);
+    }
 
     // No more registry entities
 
+    // Same structure as the dataset dump above, as a graph; pipe into `dot -Tsvg`
+    println!("\n=== Subscription Graph (DOT) ===");
+    print!("{}", export_subscriptions_dot(&world));
+
     // Demonstrate advanced queries
     println!("\n=== Query Examples ===");
 
@@ -470,4 +1001,231 @@ pub fn main() {
         "- TYPE-SAFE ENTITY HANDLES: PaneHandle and DatasetHandle prevent mixing entity types"
     );
     println!("- COMMAND SYSTEM: Queue-based command processing with systems");
+    println!("- EVENT QUEUE: PaneSubscribed events drained by a dedicated system");
+    println!("- NEGATIVE RELATION FILTERS: without_relation finds panes with no datasets");
+
+    subscriptions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn create_command_keeps_subscription_weights() {
+        let mut backend = FlaxBackend::new();
+        enqueue_command(
+            &mut backend.world,
+            Command::CreatePaneWithDatasets {
+                datasets: vec![
+                    (DatasetId("temperature_sensor_1"), 0.5),
+                    (DatasetId("humidity_sensor_1"), 2.0),
+                ],
+                width: None,
+                height: None,
+            },
+        );
+        backend.process_commands();
+
+        let pane = backend.world.get(resources(), created_panes()).unwrap()[0].1;
+        let temperature = backend.find_dataset("temperature_sensor_1").unwrap();
+        let humidity = backend.find_dataset("humidity_sensor_1").unwrap();
+        assert_eq!(
+            get_panes_for_dataset(&backend.world, temperature),
+            [(pane, 0.5)]
+        );
+        assert_eq!(
+            get_panes_for_dataset(&backend.world, humidity),
+            [(pane, 2.0)]
+        );
+    }
+
+    #[test]
+    fn backend_panes_get_growing_default_widths() {
+        let mut backend = FlaxBackend::new();
+        let panes: Vec<PaneHandle> = backend::DEMO_PANES
+            .iter()
+            .map(|dataset_ids| backend.create_pane_with_datasets(dataset_ids))
+            .collect();
+
+        let widths: Vec<u32> = panes
+            .iter()
+            .map(|pane| *backend.world.get(pane.entity(), pane::width()).unwrap())
+            .collect();
+        assert_eq!(widths, [100, 200, 300]);
+    }
+
+    #[test]
+    fn one_pass_creates_panes_in_enqueue_order() {
+        let mut backend = FlaxBackend::new();
+        for dataset_ids in backend::DEMO_PANES {
+            enqueue_command(
+                &mut backend.world,
+                Command::create_pane(dataset_ids.iter().map(|&id| DatasetId(id)).collect()),
+            );
+        }
+        backend.process_commands();
+
+        let created = backend.world.get(resources(), created_panes()).unwrap();
+        let requested: Vec<Vec<DatasetId>> = created.iter().map(|(ids, _)| ids.clone()).collect();
+        let expected: Vec<Vec<DatasetId>> = backend::DEMO_PANES
+            .iter()
+            .map(|ids| ids.iter().map(|&id| DatasetId(id)).collect())
+            .collect();
+        assert_eq!(requested, expected);
+
+        // The queue index sets the default width, so the first pane is the narrowest
+        let widths: Vec<u32> = created
+            .iter()
+            .map(|(_, pane)| *backend.world.get(pane.entity(), pane::width()).unwrap())
+            .collect();
+        assert_eq!(widths, [100, 200, 300]);
+    }
+
+    #[test]
+    fn resize_command_replaces_pane_dimensions() {
+        let mut backend = FlaxBackend::new();
+        let pane = backend.create_pane_with_datasets(&["humidity_sensor_1"]);
+        let other = backend.create_pane_with_datasets(&["humidity_sensor_1"]);
+
+        backend.resize_pane(pane, 300, 150);
+
+        let size = |handle: PaneHandle| {
+            (
+                *backend.world.get(handle.entity(), pane::width()).unwrap(),
+                *backend.world.get(handle.entity(), pane::height()).unwrap(),
+            )
+        };
+        assert_eq!(size(pane), (300, 150));
+        assert_eq!(size(other), (200, 200));
+    }
+
+    #[test]
+    fn deleted_pane_leaves_no_subscribed_by_relations() {
+        let mut backend = FlaxBackend::new();
+        let panes = backend::run_pane_demo(&mut backend);
+        let deleted = panes[2];
+
+        let datasets: Vec<Entity> = Query::new(entity_ids())
+            .with(dataset::id())
+            .borrow(&backend.world)
+            .iter()
+            .collect();
+        assert!(!datasets.is_empty());
+        for dataset in datasets {
+            assert!(
+                !backend
+                    .world
+                    .has(dataset, dataset::subscribed_by(deleted.entity())),
+                "dataset {:?} still lists deleted pane {:?}",
+                dataset,
+                deleted
+            );
+        }
+
+        // Deleting it again is a no-op rather than a panic
+        backend.delete_pane(deleted);
+        assert!(!backend.world.is_alive(deleted.entity()));
+    }
+
+    #[test]
+    fn subscriber_limit_alerts_once_exceeded() {
+        let mut backend = FlaxBackend::new();
+        for _ in 0..DEFAULT_SUBSCRIBER_LIMIT {
+            backend.create_pane_with_datasets(&["humidity_sensor_1"]);
+        }
+        assert!(
+            backend
+                .world
+                .get(resources(), subscriber_alerts())
+                .unwrap()
+                .is_empty(),
+            "reaching the limit is still fine"
+        );
+
+        backend.create_pane_with_datasets(&["humidity_sensor_1"]);
+        assert_eq!(
+            *backend.world.get(resources(), subscriber_alerts()).unwrap(),
+            [(DatasetId("humidity_sensor_1"), DEFAULT_SUBSCRIBER_LIMIT + 1)]
+        );
+    }
+
+    #[test]
+    fn subscriber_limit_applies_to_the_demo_script() {
+        let mut backend = FlaxBackend::new();
+        backend
+            .world
+            .set(resources(), subscriber_limit(), 1)
+            .unwrap();
+
+        backend::run_pane_demo(&mut backend);
+
+        // Pane 2 is humidity_sensor_1's second subscriber, pane 3 temperature_sensor_1's
+        assert_eq!(
+            *backend.world.get(resources(), subscriber_alerts()).unwrap(),
+            [
+                (DatasetId("humidity_sensor_1"), 2),
+                (DatasetId("temperature_sensor_1"), 2)
+            ]
+        );
+    }
+
+    #[test]
+    fn executors_agree_on_a_small_unit_world() {
+        let (_, seq_health, seq_log) = run_unit_schedule(1_000, 5, false);
+        let (_, par_health, par_log) = run_unit_schedule(1_000, 5, true);
+
+        assert!(
+            totals_agree(seq_health, par_health),
+            "{} vs {}",
+            seq_health,
+            par_health
+        );
+        assert_eq!(seq_log, par_log);
+        assert!(!totals_agree(seq_health, seq_health + 1.0));
+    }
+
+    #[test]
+    fn unsubscribing_keeps_the_panes_other_dataset() {
+        let mut backend = FlaxBackend::new();
+        let panes = backend::run_pane_demo(&mut backend);
+
+        assert!(backend::run_unsubscribe_demo(&mut backend, &panes));
+        assert_eq!(backend.panes_for_dataset("humidity_sensor_1"), [panes[1]]);
+    }
+
+    #[test]
+    fn deleting_the_only_subscriber_despawns_the_dataset() {
+        let mut backend = FlaxBackend::new();
+        for dataset_ids in backend::DEMO_PANES {
+            backend.create_pane_with_datasets(dataset_ids);
+        }
+        let pressure = backend.find_dataset("pressure_sensor_1").unwrap();
+        assert_eq!(backend.dataset_count(), 3);
+
+        // Pane 3 is pressure_sensor_1's only subscriber
+        let pane3 = backend.world.get(resources(), created_panes()).unwrap()[2].1;
+        backend.delete_pane(pane3);
+
+        assert!(!backend.world.is_alive(pressure.entity()));
+        assert!(backend.find_dataset("pressure_sensor_1").is_none());
+        assert_eq!(backend.dataset_count(), 2);
+    }
+
+    #[test]
+    fn without_relation_finds_panes_with_no_datasets() {
+        let mut backend = FlaxBackend::new();
+        let panes: Vec<PaneHandle> = backend::DEMO_PANES
+            .iter()
+            .map(|dataset_ids| backend.create_pane_with_datasets(dataset_ids))
+            .collect();
+        let empty_pane = backend.create_pane_with_datasets(&[]);
+        assert_eq!(panes_without_datasets(&backend.world), [empty_pane]);
+
+        // Dropping a pane's only subscription makes it an orphan too
+        backend.unsubscribe_dataset(panes[1], "humidity_sensor_1");
+        let orphans = panes_without_datasets(&backend.world);
+        assert_eq!(orphans.len(), 2);
+        assert!(orphans.contains(&panes[1]) && orphans.contains(&empty_pane));
+    }
 }
@@ -1,47 +1,16 @@
 #![allow(unused)]
-use flecs::*;
-use std::collections::{HashMap, VecDeque};
-
-// Macro to create type-safe entity handles
-macro_rules! entity_handles {
-    ($($handle_name:ident),* $(,)?) => {
-        $(
-            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-            pub struct $handle_name(Entity);
-
-            impl $handle_name {
-                pub fn new(entity: Entity) -> Self {
-                    Self(entity)
-                }
-
-                pub fn entity(&self) -> Entity {
-                    self.0
-                }
-            }
-
-            impl From<Entity> for $handle_name {
-                fn from(entity: Entity) -> Self {
-                    Self(entity)
-                }
-            }
+#[macro_use]
+mod handles;
+mod model;
+mod report;
 
-            impl From<$handle_name> for Entity {
-                fn from(handle: $handle_name) -> Self {
-                    handle.0
-                }
-            }
-        )*
-    };
-}
+use flecs::*;
+use model::DatasetId;
+use report::{OVERLAPPING_PANES, SubscriptionReport};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 
 // Create type-safe handles
-entity_handles! {
-    PaneHandle,
-    DatasetHandle,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct DatasetId(&'static str);
+entity_handles!(@no_hash Entity; PaneHandle, DatasetHandle);
 
 // Flecs Rust bindings are extremely limited - use basic types that work
 // Component trait is automatically implemented for 'static types
@@ -68,11 +37,7 @@ struct CreatedPanes {
 }
 
 // Command types
-#[derive(Debug, Clone)]
-pub enum Command {
-    CreatePaneWithDatasets { dataset_ids: Vec<DatasetId> },
-    DeletePane { pane: PaneHandle },
-}
+pub type Command = model::Command<PaneHandle, DatasetHandle>;
 
 // Create a very simple implementation due to extremely limited Flecs Rust API
 fn create_pane_with_datasets(
@@ -80,17 +45,20 @@ fn create_pane_with_datasets(
     dataset_ids: Vec<DatasetId>,
     created_datasets: &mut HashMap<DatasetId, DatasetHandle>,
 ) -> (PaneHandle, Vec<DatasetHandle>) {
+    // region: spawn
     // Create the pane entity
     let pane = world.entity().set(Pane {
         width: 100,
         height: 200,
     });
     let pane_handle = PaneHandle::new(pane);
+    // endregion: spawn
 
     // Create dataset entities (limited deduplication due to API limitations)
     let mut dataset_handles = Vec::new();
 
     for dataset_id in dataset_ids {
+        // region: spawn
         let dataset_handle = if let Some(&existing_handle) = created_datasets.get(&dataset_id) {
             existing_handle
         } else {
@@ -99,23 +67,30 @@ fn create_pane_with_datasets(
             created_datasets.insert(dataset_id, dataset_handle);
             dataset_handle
         };
+        // endregion: spawn
 
         dataset_handles.push(dataset_handle);
     }
 
+    // region: relate
     // Store the relationships in the pane
     pane.set(PaneDatasets {
         dataset_handles: dataset_handles.clone(),
     });
+    // endregion: relate
 
     (pane_handle, dataset_handles)
 }
 
+/// Returns the panes subscribed to `dataset`.
+///
+/// The flecs bindings have no query or relationship API, so pane -> dataset
+/// relations are tracked externally in `all_panes` rather than read from the world.
 fn get_panes_for_dataset(
-    world: &World,
     dataset: DatasetHandle,
     all_panes: &[(PaneHandle, Vec<DatasetHandle>)],
 ) -> Vec<PaneHandle> {
+    // region: query-reverse
     let mut subscribing_panes = Vec::new();
 
     for &(pane_handle, ref dataset_handles) in all_panes {
@@ -125,6 +100,22 @@ fn get_panes_for_dataset(
     }
 
     subscribing_panes
+    // endregion: query-reverse
+}
+
+// Entity deletion is only wired up when the `flecs-despawn` feature is enabled,
+// since the pinned bindings don't reliably expose it
+#[cfg(feature = "flecs-despawn")]
+fn despawn_pane(pane: PaneHandle) -> bool {
+    // region: despawn
+    pane.entity().destruct();
+    true
+    // endregion: despawn
+}
+
+#[cfg(not(feature = "flecs-despawn"))]
+fn despawn_pane(_pane: PaneHandle) -> bool {
+    false
 }
 
 // Command processing system (simplified due to API limitations)
@@ -141,7 +132,8 @@ fn process_commands_system(
 
     for cmd in commands.drain(..) {
         match cmd {
-            Command::CreatePaneWithDatasets { dataset_ids } => {
+            Command::CreatePaneWithDatasets { datasets, .. } => {
+                let dataset_ids = model::dataset_ids(&datasets);
                 println!(
                     "[System] Processing CreatePaneWithDatasets command with {} datasets",
                     dataset_ids.len()
@@ -149,17 +141,25 @@ fn process_commands_system(
                 let (pane_handle, dataset_handles) =
                     create_pane_with_datasets(world, dataset_ids.clone(), created_datasets);
                 new_panes.push((dataset_ids.clone(), pane_handle));
+                // region: relate
                 all_pane_dataset_relations.push((pane_handle, dataset_handles));
+                // endregion: relate
                 println!("[System] Created pane: {:?}", pane_handle);
             }
             Command::DeletePane { pane } => {
                 println!("[System] Processing DeletePane command for {:?}", pane);
-                // Note: Due to API limitations, we can't actually despawn entities
-                // In a real implementation with full Flecs API, you would call world.delete(pane.entity())
+                if despawn_pane(pane) {
+                    println!("[System] Despawned pane entity {:?}", pane.entity());
+                } else {
+                    // Without the delete binding we can only drop the tracking entries
+                    println!(
+                        "[System] Note: Entity despawn not supported in current Flecs Rust bindings"
+                    );
+                }
                 deleted_panes.push(pane);
-                println!(
-                    "[System] Note: Entity despawn not supported in current Flecs Rust bindings"
-                );
+            }
+            Command::UnsubscribeDataset { .. } | Command::ResizePane { .. } => {
+                println!("[System] {:?} is not supported by this example", cmd);
             }
         }
     }
@@ -170,7 +170,9 @@ fn process_commands_system(
     }
     for deleted_pane in deleted_panes {
         created_panes.retain(|(_, h)| *h != deleted_pane);
+        // region: despawn
         all_pane_dataset_relations.retain(|(h, _)| *h != deleted_pane);
+        // endregion: despawn
     }
 }
 
@@ -191,8 +193,7 @@ fn dump_subscriptions_by_dataset(
         println!("  Handle: {:?}", dataset_handle);
 
         // Use the dedicated function to get panes for this dataset
-        let subscribing_panes =
-            get_panes_for_dataset(&World::new(), dataset_handle, all_pane_dataset_relations);
+        let subscribing_panes = get_panes_for_dataset(dataset_handle, all_pane_dataset_relations);
 
         if !subscribing_panes.is_empty() {
             println!(
@@ -206,7 +207,74 @@ fn dump_subscriptions_by_dataset(
     }
 }
 
+// Distinct dataset entities referenced by any pane. The bindings can't query the
+// world for DatasetId, so this counts handles rather than entries in `created_datasets`
+fn dataset_count(all_pane_dataset_relations: &[(PaneHandle, Vec<DatasetHandle>)]) -> usize {
+    let mut datasets: Vec<DatasetHandle> = Vec::new();
+    for (_, dataset_handles) in all_pane_dataset_relations {
+        for &dataset in dataset_handles {
+            if !datasets.contains(&dataset) {
+                datasets.push(dataset);
+            }
+        }
+    }
+    datasets.len()
+}
+
+// Normalized subscriptions: dataset id -> sorted indices (into `panes`) of subscribing panes
+fn subscription_map(
+    created_datasets: &HashMap<DatasetId, DatasetHandle>,
+    all_pane_dataset_relations: &[(PaneHandle, Vec<DatasetHandle>)],
+    panes: &[PaneHandle],
+) -> SubscriptionReport {
+    let mut subscriptions = BTreeMap::new();
+
+    for (&dataset_id, &dataset_handle) in created_datasets {
+        let mut indices: Vec<usize> =
+            get_panes_for_dataset(dataset_handle, all_pane_dataset_relations)
+                .iter()
+                .filter_map(|pane| panes.iter().position(|p| p == pane))
+                .collect();
+        indices.sort();
+        subscriptions.insert(dataset_id.0, indices);
+    }
+
+    subscriptions
+}
+
 pub fn main() {
+    let report = run();
+    report::print_report(&report);
+}
+
+// Dataset entities left after creating the overlapping panes in one command batch
+pub fn overlapping_dataset_count() -> usize {
+    let world = World::new();
+    world.component::<Pane>();
+    world.component::<DatasetId>();
+    world.component::<PaneDatasets>();
+
+    let mut command_queue = VecDeque::new();
+    for dataset_ids in OVERLAPPING_PANES {
+        enqueue_command(
+            &mut command_queue,
+            Command::create_pane(dataset_ids.iter().map(|&id| DatasetId(id)).collect()),
+        );
+    }
+
+    let mut all_pane_dataset_relations = Vec::new();
+    process_commands_system(
+        &world,
+        &mut command_queue,
+        &mut HashMap::new(),
+        &mut Vec::new(),
+        &mut all_pane_dataset_relations,
+    );
+    dataset_count(&all_pane_dataset_relations)
+}
+
+// Runs the demo and returns the normalized subscriptions left after deletion
+pub fn run() -> SubscriptionReport {
     // Create a new flecs world
     let mut world = World::new();
 
@@ -231,29 +299,23 @@ pub fn main() {
     println!("Enqueueing commands...");
     enqueue_command(
         &mut command_queue,
-        Command::CreatePaneWithDatasets {
-            dataset_ids: vec![
-                DatasetId("temperature_sensor_1"),
-                DatasetId("humidity_sensor_1"),
-            ],
-        },
+        Command::create_pane(vec![
+            DatasetId("temperature_sensor_1"),
+            DatasetId("humidity_sensor_1"),
+        ]),
     );
 
     enqueue_command(
         &mut command_queue,
-        Command::CreatePaneWithDatasets {
-            dataset_ids: vec![DatasetId("humidity_sensor_1")],
-        },
+        Command::create_pane(vec![DatasetId("humidity_sensor_1")]),
     );
 
     enqueue_command(
         &mut command_queue,
-        Command::CreatePaneWithDatasets {
-            dataset_ids: vec![
-                DatasetId("temperature_sensor_1"),
-                DatasetId("pressure_sensor_1"),
-            ],
-        },
+        Command::create_pane(vec![
+            DatasetId("temperature_sensor_1"),
+            DatasetId("pressure_sensor_1"),
+        ]),
     );
 
     // Process commands through the system
@@ -301,6 +363,11 @@ pub fn main() {
     );
 
     dump_subscriptions_by_dataset(&created_datasets, &all_pane_dataset_relations);
+    let subscriptions = subscription_map(
+        &created_datasets,
+        &all_pane_dataset_relations,
+        &pane_handles,
+    );
 
     // Print world statistics
     println!("\n=== World Statistics ===");
@@ -354,7 +421,7 @@ pub fn main() {
     }
 
     // Demonstrate type safety - these would be compile errors:
-    // let wrong_panes = get_panes_for_dataset(&world, pane1, &all_pane_dataset_relations); // Error: expected DatasetHandle, found PaneHandle
+    // let wrong_panes = get_panes_for_dataset(pane1, &all_pane_dataset_relations); // Error: expected DatasetHandle, found PaneHandle
     // let mixed_handles: Vec<Entity> = vec![pane1.entity(), dataset1.entity()]; // Error: can't mix handle types
 
     println!("\n=== Flecs Example Complete ===");
@@ -373,7 +440,9 @@ pub fn main() {
     println!("- No .has() method for checking components");
     println!("- No query API (no .query(), .each(), .filter())");
     println!("- No relationship API");
-    println!("- No entity despawn in current bindings");
+    println!("- Entity despawn only with the `flecs-despawn` feature enabled");
     println!("- Current Flecs Rust bindings (0.1.x) are incomplete and not production-ready");
     println!("- For production use, consider the C API directly or wait for better Rust bindings");
+
+    subscriptions
 }
@@ -0,0 +1,43 @@
+// Macro to create type-safe entity handles over a library's entity id type.
+//
+//     entity_handles!(Entity; PaneHandle, DatasetHandle);
+//
+// Use the `@no_hash` form for id types that don't implement `Hash`:
+//
+//     entity_handles!(@no_hash Entity; PaneHandle, DatasetHandle);
+macro_rules! entity_handles {
+    ($entity:ty; $($handle_name:ident),* $(,)?) => {
+        entity_handles!(@impl [Debug, Clone, Copy, PartialEq, Eq, Hash] $entity; $($handle_name),*);
+    };
+    (@no_hash $entity:ty; $($handle_name:ident),* $(,)?) => {
+        entity_handles!(@impl [Debug, Clone, Copy, PartialEq, Eq] $entity; $($handle_name),*);
+    };
+    (@impl [$($derive:ident),*] $entity:ty; $($handle_name:ident),*) => {
+        $(
+            #[derive($($derive),*)]
+            pub struct $handle_name($entity);
+
+            impl $handle_name {
+                pub fn new(entity: $entity) -> Self {
+                    Self(entity)
+                }
+
+                pub fn entity(&self) -> $entity {
+                    self.0
+                }
+            }
+
+            impl From<$entity> for $handle_name {
+                fn from(entity: $entity) -> Self {
+                    Self(entity)
+                }
+            }
+
+            impl From<$handle_name> for $entity {
+                fn from(handle: $handle_name) -> Self {
+                    handle.0
+                }
+            }
+        )*
+    };
+}
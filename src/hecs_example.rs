@@ -1,48 +1,19 @@
 #![allow(unused)]
+#[macro_use]
+mod handles;
+mod backend;
+mod model;
+mod report;
+
+use backend::EcsBackend;
 use hecs::*;
 use hecs_hierarchy::*;
-use std::collections::VecDeque;
-
-// Macro to create type-safe entity handles
-macro_rules! entity_handles {
-    ($($handle_name:ident),* $(,)?) => {
-        $(
-            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-            pub struct $handle_name(Entity);
-
-            impl $handle_name {
-                pub fn new(entity: Entity) -> Self {
-                    Self(entity)
-                }
-
-                pub fn entity(&self) -> Entity {
-                    self.0
-                }
-            }
-
-            impl From<Entity> for $handle_name {
-                fn from(entity: Entity) -> Self {
-                    Self(entity)
-                }
-            }
-
-            impl From<$handle_name> for Entity {
-                fn from(handle: $handle_name) -> Self {
-                    handle.0
-                }
-            }
-        )*
-    };
-}
+use model::DatasetId;
+use report::SubscriptionReport;
+use std::collections::{BTreeMap, VecDeque};
 
 // Create type-safe handles
-entity_handles! {
-    PaneHandle,
-    DatasetHandle,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct DatasetId(&'static str);
+entity_handles!(Entity; PaneHandle, DatasetHandle);
 
 #[derive(Debug, Clone)]
 struct Pane {
@@ -72,18 +43,15 @@ struct CreatedPanes {
 struct Tree;
 
 // Command types
-#[derive(Debug, Clone)]
-pub enum Command {
-    CreatePaneWithDatasets { dataset_ids: Vec<DatasetId> },
-    DeletePane { pane: PaneHandle },
-}
+pub type Command = model::Command<PaneHandle, DatasetHandle>;
 
-fn create_pane_with_datasets(
+pub(crate) fn create_pane_with_datasets(
     world: &mut World,
     dataset_ids: Vec<DatasetId>,
     pane_root: Entity,
     dataset_root: Entity,
 ) -> PaneHandle {
+    // region: spawn
     // Create the pane entity and attach it as child of pane_root
     let pane = world
         .attach_new::<Tree, _>(
@@ -95,8 +63,10 @@ fn create_pane_with_datasets(
         )
         .unwrap();
     let pane_handle = PaneHandle::new(pane);
+    // endregion: spawn
 
     for dataset_id in dataset_ids {
+        // region: spawn
         // Find existing dataset by searching children of dataset_root
         let mut existing_dataset = None;
 
@@ -119,16 +89,20 @@ fn create_pane_with_datasets(
                 .unwrap();
             DatasetHandle::new(dataset_entity)
         };
+        // endregion: spawn
 
+        // region: relate
         // Create relationship: attach pane as child of dataset to show "uses" relationship
         // This creates a many-to-many relationship through the hierarchy
         world.attach::<Tree>(pane, dataset_handle.entity()).unwrap();
+        // endregion: relate
     }
 
     pane_handle
 }
 
-fn get_panes_for_dataset(world: &World, dataset: DatasetHandle) -> Vec<PaneHandle> {
+pub(crate) fn get_panes_for_dataset(world: &World, dataset: DatasetHandle) -> Vec<PaneHandle> {
+    // region: query-reverse
     let mut subscribing_panes = Vec::new();
     // Get all children of this dataset (which are panes that use it)
     for child in world.children::<Tree>(dataset.entity()) {
@@ -137,6 +111,49 @@ fn get_panes_for_dataset(world: &World, dataset: DatasetHandle) -> Vec<PaneHandl
         }
     }
     subscribing_panes
+    // endregion: query-reverse
+}
+
+// Datasets a pane uses, found by walking up from the pane. Panes are attached
+// under their datasets, so the datasets are the pane's ancestors, not descendants.
+fn datasets_for_pane(world: &World, pane: PaneHandle) -> Vec<DatasetHandle> {
+    world
+        .ancestors::<Tree>(pane.entity())
+        .filter(|&ancestor| world.get::<&DatasetId>(ancestor).is_ok())
+        .map(DatasetHandle::new)
+        .collect()
+}
+
+// Brute-force lookup over every dataset's children, kept to cross-check the ancestor walk
+fn scan_datasets_for_pane(
+    world: &World,
+    pane: PaneHandle,
+    dataset_root: Entity,
+) -> Vec<DatasetHandle> {
+    world
+        .children::<Tree>(dataset_root)
+        .filter(|&dataset| {
+            world
+                .children::<Tree>(dataset)
+                .any(|child| child == pane.entity())
+        })
+        .map(DatasetHandle::new)
+        .collect()
+}
+
+// Distance from `entity` to the root of its Tree, following parent links one at a time.
+// Roots are depth 0; the walk is capped at the entity count so a cycle can't hang it.
+fn hierarchy_depth(world: &World, entity: Entity) -> usize {
+    let mut depth = 0;
+    let mut current = entity;
+    while let Ok(parent) = world.parent::<Tree>(current) {
+        depth += 1;
+        current = parent;
+        if depth > world.len() as usize {
+            break;
+        }
+    }
+    depth
 }
 
 // Command processing system
@@ -158,7 +175,8 @@ fn process_commands_system(
 
     for cmd in commands {
         match cmd {
-            Command::CreatePaneWithDatasets { dataset_ids } => {
+            Command::CreatePaneWithDatasets { datasets, .. } => {
+                let dataset_ids = model::dataset_ids(&datasets);
                 println!(
                     "[System] Processing CreatePaneWithDatasets command with {} datasets",
                     dataset_ids.len()
@@ -170,9 +188,28 @@ fn process_commands_system(
             }
             Command::DeletePane { pane } => {
                 println!("[System] Processing DeletePane command for {:?}", pane);
+                // region: despawn
                 world.despawn(pane.entity()).ok();
+                // endregion: despawn
                 deleted_panes.push(pane);
             }
+            Command::UnsubscribeDataset { pane, dataset } => {
+                println!(
+                    "[System] Processing UnsubscribeDataset command for {:?} from {:?}",
+                    pane, dataset
+                );
+                // A hierarchy node has a single parent, so the pane can only be
+                // detached if that parent is this dataset
+                let is_child = world
+                    .children::<Tree>(dataset.entity())
+                    .any(|child| child == pane.entity());
+                if is_child {
+                    world.detach::<Tree>(pane.entity()).ok();
+                }
+            }
+            Command::ResizePane { .. } => {
+                println!("[System] {:?} is not supported by this example", cmd);
+            }
         }
     }
 
@@ -218,73 +255,8 @@ fn dump_subscriptions_by_dataset(world: &World, dataset_root: Entity) {
     }
 }
 
-pub fn main() {
-    // Create a new hecs world
-    let mut world = World::new();
-
-    // Create root entities for organization
-    let pane_root = world.spawn((PaneRoot,));
-    let dataset_root = world.spawn((DatasetRoot,));
-
-    // Create command queue entity
-    let command_entity = world.spawn((
-        CommandQueue {
-            commands: VecDeque::new(),
-        },
-        CreatedPanes { panes: Vec::new() },
-    ));
-
-    println!("=== Command-Based Pane Creation Demo ===\n");
-
-    // Enqueue commands instead of direct creation
-    println!("Enqueueing commands...");
-    enqueue_command(
-        &mut world,
-        command_entity,
-        Command::CreatePaneWithDatasets {
-            dataset_ids: vec![
-                DatasetId("temperature_sensor_1"),
-                DatasetId("humidity_sensor_1"),
-            ],
-        },
-    );
-
-    enqueue_command(
-        &mut world,
-        command_entity,
-        Command::CreatePaneWithDatasets {
-            dataset_ids: vec![DatasetId("humidity_sensor_1")],
-        },
-    );
-
-    enqueue_command(
-        &mut world,
-        command_entity,
-        Command::CreatePaneWithDatasets {
-            dataset_ids: vec![
-                DatasetId("temperature_sensor_1"),
-                DatasetId("pressure_sensor_1"),
-            ],
-        },
-    );
-
-    // Process commands through the system
-    println!("\nExecuting command processing system...\n");
-    process_commands_system(&mut world, command_entity, pane_root, dataset_root);
-
-    // Get created panes from the command system
-    let created = world
-        .get::<&CreatedPanes>(command_entity)
-        .unwrap()
-        .panes
-        .clone();
-    let pane_handles: Vec<PaneHandle> = created.iter().map(|(_, h)| *h).collect();
-
-    let pane1 = pane_handles[0];
-    let pane2 = pane_handles[1];
-    let pane3 = pane_handles[2];
-
-    // Print all panes using hierarchy
+// Print all panes using hierarchy
+fn dump_panes(world: &World, pane_root: Entity) {
     println!("\n=== Panes (via Hierarchy) ===");
     for pane_entity in world.children::<Tree>(pane_root) {
         if let Ok(pane) = world.get::<&Pane>(pane_entity) {
@@ -292,15 +264,8 @@ pub fn main() {
             println!("Pane Handle: {:?}", pane_handle);
             println!("  Width: {}, Height: {}", pane.width, pane.height);
 
-            // Find datasets this pane uses by looking at which datasets have this pane as child
-            let mut used_datasets = Vec::new();
-            for dataset_entity in world.children::<Tree>(dataset_root) {
-                // Check if this pane is a child of this dataset
-                let dataset_children: Vec<_> = world.children::<Tree>(dataset_entity).collect();
-                if dataset_children.contains(&pane_entity) {
-                    used_datasets.push(DatasetHandle::new(dataset_entity));
-                }
-            }
+            // Walk up from the pane instead of scanning every dataset's children
+            let used_datasets = datasets_for_pane(world, pane_handle);
 
             if !used_datasets.is_empty() {
                 println!(
@@ -313,23 +278,148 @@ pub fn main() {
             }
         }
     }
+}
 
-    dump_subscriptions_by_dataset(&world, dataset_root);
+// Drives the demo through the command queue entity
+struct HecsBackend {
+    world: World,
+    pane_root: Entity,
+    dataset_root: Entity,
+    command_entity: Entity,
+}
 
-    // Use command to delete pane 3
-    println!("\n=== Demonstrating Command-Based Deletion ===");
-    println!("Enqueueing delete command for pane 3...");
-    enqueue_command(
-        &mut world,
-        command_entity,
-        Command::DeletePane { pane: pane3 },
-    );
+impl HecsBackend {
+    fn new() -> Self {
+        let mut world = World::new();
+
+        // Create root entities for organization
+        let pane_root = world.spawn((PaneRoot,));
+        let dataset_root = world.spawn((DatasetRoot,));
+
+        // Create command queue entity
+        let command_entity = world.spawn((
+            CommandQueue {
+                commands: VecDeque::new(),
+            },
+            CreatedPanes { panes: Vec::new() },
+        ));
+
+        Self {
+            world,
+            pane_root,
+            dataset_root,
+            command_entity,
+        }
+    }
+
+    fn process_commands(&mut self) {
+        process_commands_system(
+            &mut self.world,
+            self.command_entity,
+            self.pane_root,
+            self.dataset_root,
+        );
+    }
 
-    // Process the delete command
-    println!("Executing command processing system...\n");
-    process_commands_system(&mut world, command_entity, pane_root, dataset_root);
+    fn find_dataset(&self, dataset_id: &str) -> Option<DatasetHandle> {
+        self.world
+            .children::<Tree>(self.dataset_root)
+            .find(|&dataset_entity| {
+                self.world
+                    .get::<&DatasetId>(dataset_entity)
+                    .is_ok_and(|id| id.0 == dataset_id)
+            })
+            .map(DatasetHandle::new)
+    }
+}
 
-    dump_subscriptions_by_dataset(&world, dataset_root);
+impl EcsBackend for HecsBackend {
+    type PaneHandle = PaneHandle;
+
+    fn create_pane_with_datasets(&mut self, dataset_ids: &[&'static str]) -> PaneHandle {
+        enqueue_command(
+            &mut self.world,
+            self.command_entity,
+            Command::create_pane(dataset_ids.iter().map(|&id| DatasetId(id)).collect()),
+        );
+        self.process_commands();
+
+        // The command system records every pane it creates, newest last
+        let created = self
+            .world
+            .get::<&CreatedPanes>(self.command_entity)
+            .unwrap();
+        created.panes.last().map(|(_, h)| *h).unwrap()
+    }
+
+    fn delete_pane(&mut self, pane: PaneHandle) {
+        enqueue_command(
+            &mut self.world,
+            self.command_entity,
+            Command::DeletePane { pane },
+        );
+        self.process_commands();
+    }
+
+    fn unsubscribe_dataset(&mut self, pane: PaneHandle, dataset_id: &'static str) {
+        let Some(dataset) = self.find_dataset(dataset_id) else {
+            return;
+        };
+        enqueue_command(
+            &mut self.world,
+            self.command_entity,
+            Command::UnsubscribeDataset { pane, dataset },
+        );
+        self.process_commands();
+    }
+
+    fn panes_for_dataset(&self, dataset_id: &str) -> Vec<PaneHandle> {
+        match self.find_dataset(dataset_id) {
+            Some(dataset) => get_panes_for_dataset(&self.world, dataset),
+            None => Vec::new(),
+        }
+    }
+
+    fn dump_panes(&self) {
+        dump_panes(&self.world, self.pane_root);
+    }
+
+    fn dump_subscriptions(&self) {
+        dump_subscriptions_by_dataset(&self.world, self.dataset_root);
+    }
+
+    fn dataset_count(&self) -> usize {
+        self.world.query::<&DatasetId>().iter().count()
+    }
+}
+
+pub fn main() {
+    let report = run();
+    report::print_report(&report);
+}
+
+// Dataset entities left after creating the overlapping panes through the command queue
+pub fn overlapping_dataset_count() -> usize {
+    backend::overlapping_dataset_count(&mut HecsBackend::new())
+}
+
+// Runs the demo and returns the normalized subscriptions left after deletion
+pub fn run() -> SubscriptionReport {
+    // Create a new hecs world behind the shared backend interface
+    let mut backend = HecsBackend::new();
+
+    println!("=== Command-Based Pane Creation Demo ===\n");
+
+    // Run the create/dump/delete/dump sequence shared by every backend
+    let pane_handles = backend::run_pane_demo(&mut backend);
+    let subscriptions = backend::subscription_map(&backend, &pane_handles);
+    backend::run_unsubscribe_demo(&mut backend, &pane_handles);
+    let HecsBackend {
+        world,
+        pane_root,
+        dataset_root,
+        command_entity,
+    } = backend;
 
     // Print world statistics
     println!("\n=== World Statistics ===");
@@ -395,7 +485,13 @@ pub fn main() {
             components.push("HasChildren");
         }
 
-        println!("Components: {:?}", components);
+        // Panes and datasets sit at depth 1 under their roots; a pane attached under a
+        // dataset is one level deeper
+        println!(
+            "Components: {:?}, Depth: {}",
+            components,
+            hierarchy_depth(&world, entity_id)
+        );
     }
 
     // Demonstrate type safety - these would be compile errors:
@@ -417,4 +513,38 @@ pub fn main() {
     println!("- Efficient relationship queries through .children() and .parent()");
     println!("- No manual Vec<Entity> bookkeeping required");
     println!("- Built-in depth-first and breadth-first traversal");
+
+    subscriptions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unsubscribing_keeps_the_panes_other_dataset() {
+        let mut backend = HecsBackend::new();
+        let panes = backend::run_pane_demo(&mut backend);
+
+        assert!(backend::run_unsubscribe_demo(&mut backend, &panes));
+        assert_eq!(backend.panes_for_dataset("humidity_sensor_1"), [panes[1]]);
+    }
+
+    #[test]
+    fn ancestor_walk_matches_dataset_scan() {
+        let mut backend = HecsBackend::new();
+        let panes = backend::run_pane_demo(&mut backend);
+
+        let world = &backend.world;
+        // The third pane was deleted by the demo
+        for &pane in &panes[..2] {
+            assert_eq!(
+                datasets_for_pane(world, pane),
+                scan_datasets_for_pane(world, pane, backend.dataset_root),
+                "ancestor walk and dataset scan disagree for {:?}",
+                pane
+            );
+        }
+        assert_eq!(datasets_for_pane(world, panes[1]).len(), 1);
+    }
 }
@@ -0,0 +1,123 @@
+// Rebuilds the evenio example's pane/dataset state as a Flax world, to show the
+// logical model doesn't depend on the library holding it. Lives next to the
+// examples in parity_check, which includes both as sibling modules.
+use super::evenio_example::{
+    self, AllPanes, AppRegistry, DatasetIdToDatasetEntityLookup, Pane, PaneDatasets,
+    PaneHandle as EvenioPane,
+};
+use super::flax_example::{
+    self, DEFAULT_DATASET_WEIGHT, DatasetHandle as FlaxDataset, DatasetId as FlaxDatasetId,
+    PaneHandle as FlaxPane, dataset,
+};
+use super::report::SubscriptionReport;
+use flax::{Entity, Query, World, entity_ids};
+use std::collections::BTreeMap;
+
+// Copies every dataset in the lookup, then every live pane in AllPanes order with
+// its dimensions and subscriptions. Datasets go first so the ones no pane uses any
+// more survive the conversion. Returns the world and each pane's Flax counterpart.
+pub fn evenio_to_flax(registry: &AppRegistry) -> (World, Vec<(EvenioPane, FlaxPane)>) {
+    let source = &registry.world;
+    let mut world = World::new();
+
+    let lookup = source
+        .get::<DatasetIdToDatasetEntityLookup>(registry.dataset_lookup)
+        .unwrap();
+    for id in lookup.lookup.keys() {
+        Entity::builder()
+            .set(dataset::id(), FlaxDatasetId(id.0))
+            .spawn(&mut world);
+    }
+
+    let all_panes = source.get::<AllPanes>(registry.pane_lookup).unwrap();
+    let panes = all_panes
+        .panes
+        .iter()
+        .map(|&pane_handle| {
+            let pane = source.get::<Pane>(pane_handle.entity()).unwrap();
+            // evenio subscriptions carry no weight, so each gets the Flax default
+            let datasets: Vec<(FlaxDatasetId, f32)> = source
+                .get::<PaneDatasets>(pane_handle.entity())
+                .map(|pane_datasets| {
+                    pane_datasets
+                        .datasets
+                        .iter()
+                        .filter_map(|dataset| {
+                            source.get::<evenio_example::DatasetId>(dataset.entity())
+                        })
+                        .map(|id| (FlaxDatasetId(id.0), DEFAULT_DATASET_WEIGHT))
+                        .collect()
+                })
+                .unwrap_or_default();
+            // Finds the datasets spawned above by id, so none are duplicated
+            let converted = flax_example::create_pane_with_datasets(
+                &mut world,
+                datasets,
+                pane.width,
+                pane.height,
+            );
+            (pane_handle, converted)
+        })
+        .collect();
+
+    (world, panes)
+}
+
+// The converted world's subscriptions as indices into `panes`, the evenio demo's
+// pane list, so the result compares directly with evenio's own report
+pub fn flax_subscription_map(
+    world: &World,
+    converted: &[(EvenioPane, FlaxPane)],
+    panes: &[EvenioPane],
+) -> SubscriptionReport {
+    let mut subscriptions = BTreeMap::new();
+    for (entity, id) in Query::new((entity_ids(), dataset::id()))
+        .borrow(world)
+        .iter()
+    {
+        let mut indices: Vec<usize> =
+            flax_example::get_panes_for_dataset(world, FlaxDataset::new(entity))
+                .iter()
+                .filter_map(|(flax_pane, _)| {
+                    converted
+                        .iter()
+                        .find(|(_, converted_pane)| converted_pane == flax_pane)
+                })
+                .filter_map(|(evenio_pane, _)| panes.iter().position(|p| p == evenio_pane))
+                .collect();
+        indices.sort();
+        subscriptions.insert(id.0, indices);
+    }
+    subscriptions
+}
+
+// Converts the evenio demo state and checks the Flax world reports the same
+// subscriptions. Entity ids differ between the two, so the dumps are compared
+// through the normalized report rather than as text. Returns whether they match.
+pub fn check_evenio_to_flax() -> bool {
+    println!("\n=== evenio -> Flax Conversion ===");
+    let (registry, pane_handles) = evenio_example::demo_registry();
+    let expected = evenio_example::subscription_map(
+        &registry.world,
+        registry.dataset_lookup,
+        registry.pane_lookup,
+        &pane_handles,
+    );
+
+    let (world, converted) = evenio_to_flax(&registry);
+    flax_example::dump_subscriptions_by_dataset(&world);
+    let actual = flax_subscription_map(&world, &converted, &pane_handles);
+
+    println!("{:<10} {:?}", "evenio", expected);
+    println!("{:<10} {:?}", "flax", actual);
+    if expected == actual {
+        println!(
+            "Converted {} panes with identical subscriptions",
+            converted.len()
+        );
+        true
+    } else {
+        println!("MISMATCH: the converted Flax world disagrees with evenio");
+        false
+    }
+}
@@ -0,0 +1,132 @@
+// Counts the code each example needs for the core pane/dataset operations.
+// Regions are marked in the examples with `// region: <op>` / `// endregion: <op>`;
+// blank and comment-only lines inside them are not counted, and repeated regions
+// for the same operation are summed.
+use std::collections::BTreeMap;
+use std::process::ExitCode;
+
+const OPERATIONS: [&str; 4] = ["spawn", "relate", "query-reverse", "despawn"];
+
+const EXAMPLES: [(&str, &str); 6] = [
+    ("flax", include_str!("flax_example.rs")),
+    ("bevy_ecs", include_str!("bevy_ecs_example.rs")),
+    ("hecs", include_str!("hecs_example.rs")),
+    ("evenio", include_str!("evenio_example.rs")),
+    ("sparsey", include_str!("sparsey_example.rs")),
+    ("flecs", include_str!("flecs_example.rs")),
+];
+
+// Lines of code per operation, or an error describing a malformed region
+fn count_regions(source: &str) -> Result<BTreeMap<&str, usize>, String> {
+    let mut counts = BTreeMap::new();
+    let mut open: Option<(&str, usize)> = None;
+
+    for (index, line) in source.lines().enumerate() {
+        let line_number = index + 1;
+        let trimmed = line.trim();
+
+        if let Some(name) = trimmed.strip_prefix("// region:") {
+            if let Some((outer, start)) = open {
+                return Err(format!(
+                    "line {}: region '{}' opened inside '{}' from line {}",
+                    line_number,
+                    name.trim(),
+                    outer,
+                    start
+                ));
+            }
+            open = Some((name.trim(), line_number));
+            counts.entry(name.trim()).or_insert(0);
+        } else if let Some(name) = trimmed.strip_prefix("// endregion:") {
+            match open.take() {
+                Some((current, _)) if current == name.trim() => {}
+                Some((current, start)) => {
+                    return Err(format!(
+                        "line {}: endregion '{}' closes '{}' from line {}",
+                        line_number,
+                        name.trim(),
+                        current,
+                        start
+                    ));
+                }
+                None => {
+                    return Err(format!(
+                        "line {}: endregion '{}' without a region",
+                        line_number,
+                        name.trim()
+                    ));
+                }
+            }
+        } else if let Some((current, _)) = open {
+            if !trimmed.is_empty() && !trimmed.starts_with("//") {
+                *counts.entry(current).or_insert(0) += 1;
+            }
+        }
+    }
+
+    match open {
+        Some((current, start)) => Err(format!(
+            "region '{}' from line {} is never closed",
+            current, start
+        )),
+        None => Ok(counts),
+    }
+}
+
+fn main() -> ExitCode {
+    let mut rows = Vec::new();
+    for (name, source) in EXAMPLES {
+        match count_regions(source) {
+            Ok(counts) => rows.push((name, counts)),
+            Err(err) => {
+                println!("{}: {}", name, err);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    let totals: Vec<usize> = rows
+        .iter()
+        .map(|(_, counts)| OPERATIONS.iter().filter_map(|op| counts.get(op)).sum())
+        .collect();
+    // Friction is relative to the leanest backend, so 1.00 is the best score
+    let leanest = totals.iter().copied().filter(|&total| total > 0).min();
+
+    println!("=== Core Operation Lines of Code ===");
+    print!("{:<10}", "Library");
+    for op in OPERATIONS {
+        print!(" {:>14}", op);
+    }
+    println!(" {:>8} {:>10}", "Total", "Friction");
+
+    for ((name, counts), total) in rows.iter().zip(&totals) {
+        print!("{:<10}", name);
+        for op in OPERATIONS {
+            match counts.get(op) {
+                Some(lines) => print!(" {:>14}", lines),
+                None => print!(" {:>14}", "-"),
+            }
+        }
+        match leanest {
+            Some(leanest) => println!(" {:>8} {:>10.2}", total, *total as f64 / leanest as f64),
+            None => println!(" {:>8} {:>10}", total, "-"),
+        }
+    }
+
+    // An operation without a region would silently look free
+    let mut missing = 0;
+    for (name, counts) in &rows {
+        for op in OPERATIONS {
+            if !counts.contains_key(op) {
+                missing += 1;
+                println!("{} has no '{}' region", name, op);
+            }
+        }
+    }
+
+    if missing == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
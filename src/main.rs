@@ -1,17 +1,36 @@
+#[path = "flax_example.rs"]
+mod flax_example;
+mod pane_repl;
+mod tokenizer;
+mod totals;
+mod traversal;
+
 use colored::*;
+use flax::Topo;
 use flax::system::BoxedSystem;
 use flax::*;
-use flax::{Dfs, Topo};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use rustyline::Editor;
 use rustyline::completion::{Completer, Pair};
-use rustyline::config::{Config, EditMode};
+use rustyline::config::{Config, Configurer, EditMode};
 use rustyline::error::ReadlineError;
 use rustyline::highlight::{Highlighter, MatchingBracketHighlighter};
 use rustyline::hint::{Hinter, HistoryHinter};
+use rustyline::history::DefaultHistory;
 use rustyline::validate::{self, MatchingBracketValidator, Validator};
 use rustyline::{Cmd, KeyEvent};
 use rustyline::{Context, Helper};
-use std::collections::HashMap;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokenizer::{quote, tokenize};
+use totals::Totals;
+use traversal::{find_child_of_cycle, traverse_child_of, traverse_child_of_from};
 
 // Custom Mana struct with Drop implementation
 #[derive(Debug, Clone)]
@@ -52,20 +71,337 @@ impl Drop for Mana {
     }
 }
 
+// Metadata carried on each has_child edge
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct RelationInfo {
+    since: f64,
+    label: String,
+}
+
+impl RelationInfo {
+    fn describe(&self, now: f64) -> String {
+        format!("{}, {:.0}s ago", self.label, (now - self.since).max(0.0))
+    }
+}
+
+// Health above `good` is green, above `warn` yellow, anything else red
+#[derive(Debug, Clone, Copy)]
+struct HealthThresholds {
+    good: i32,
+    warn: i32,
+}
+
+impl Default for HealthThresholds {
+    fn default() -> Self {
+        Self { good: 75, warn: 30 }
+    }
+}
+
+fn health_color(value: i32, thresholds: &HealthThresholds) -> ColoredString {
+    if value > thresholds.good {
+        value.to_string().green()
+    } else if value > thresholds.warn {
+        value.to_string().yellow()
+    } else {
+        value.to_string().red()
+    }
+}
+
+const DEFAULT_MAX_HEALTH: i32 = 100;
+
+// How full a bar is, 0..=100; a zero or negative maximum counts as empty
+fn fill_percentage(current: i32, maximum: i32) -> i32 {
+    if maximum > 0 {
+        (current as f32 / maximum as f32 * 100.0) as i32
+    } else {
+        0
+    }
+    .clamp(0, 100)
+}
+
+// Filled and empty segments of a 10-cell bar
+fn bar_segments(percentage: i32) -> (String, String) {
+    let filled = (percentage / 10).max(0) as usize;
+    ("█".repeat(filled), "░".repeat(10 - filled))
+}
+
+// Cells in the longest `histogram` bar; shorter bars scale against it
+const HISTOGRAM_WIDTH: usize = 30;
+
+// Mana copied out of the component, so no `Mana` is cloned and dropped
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+struct ManaLevel {
+    current: i32,
+    maximum: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct ChildEntry {
+    name: String,
+    #[serde(flatten)]
+    relation: RelationInfo,
+}
+
+// Probed values of one entity, gathered once and then formatted by `get`, compared
+// by `diff` or serialized in JSON output mode
+#[derive(Debug, Default, PartialEq, Serialize)]
+struct EntitySummary {
+    health: Option<i32>,
+    max_health: Option<i32>,
+    mana: Option<ManaLevel>,
+    mana_regen: Option<i32>,
+    spells_cast: Option<u32>,
+    parents: Vec<String>,
+    children: Vec<ChildEntry>,
+    tags: Vec<String>,
+    floats: BTreeMap<String, f64>,
+}
+
+// What `get` reports: the summary plus the entity's identity
+#[derive(Debug, Serialize)]
+struct EntityReport {
+    name: String,
+    id: String,
+    slot: String,
+    #[serde(flatten)]
+    summary: EntitySummary,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct NamedHealth {
+    name: String,
+    health: i32,
+}
+
+// What `stats` reports; the health figures are absent when nothing has health
+#[derive(Debug, PartialEq, Serialize)]
+struct WorldStats {
+    entities: usize,
+    with_health: usize,
+    with_mana: usize,
+    child_of_relations: usize,
+    total_mana: i64,
+    average_health: Option<f64>,
+    lowest_health: Option<NamedHealth>,
+    highest_health: Option<NamedHealth>,
+}
+
+// Health and mana sums across the whole world, computed in parallel
+fn world_totals(world: &World) -> Totals {
+    totals::par_totals(world, health(), mana(), |mana_val| mana_val.current)
+}
+
+fn child_names(children: &[ChildEntry]) -> Vec<String> {
+    children.iter().map(|child| child.name.clone()).collect()
+}
+
+// One compact JSON document per line, so scripts can read output line by line
+fn print_json<T: Serialize>(value: &T) {
+    match serde_json::to_string(value) {
+        Ok(line) => println!("{}", line),
+        Err(e) => println!("{}", json!({ "error": e.to_string() })),
+    }
+}
+
+fn print_json_result<T: Serialize>(result: Result<T, String>) {
+    match result {
+        Ok(value) => print_json(&value),
+        Err(e) => print_json(&json!({ "error": e })),
+    }
+}
+
+// How `get`, `list`, `find` and `stats` print (`--json`, `set output json|human`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum OutputMode {
+    Human,
+    Json,
+}
+
+// One named entity as captured by `checkpoint`. Parents are recorded by name so
+// the relations can be rebuilt after restore hands out fresh entity ids
+#[derive(Debug, Clone)]
+struct EntityRecord {
+    name: String,
+    health: Option<i32>,
+    max_health: Option<i32>,
+    mana: Option<(i32, i32)>,
+    mana_regen: Option<i32>,
+    spells_cast: Option<u32>,
+    tags: Option<HashSet<String>>,
+    floats: Option<HashMap<String, f64>>,
+    parents: Vec<(String, RelationInfo)>,
+}
+
+#[derive(Debug, Clone)]
+struct Checkpoint {
+    entities: Vec<EntityRecord>,
+    spellbook: BTreeMap<String, i32>,
+}
+
 component! {
-    has_child(child): String,
+    has_child(child): RelationInfo,
     last_modified: f64,
+    // When health / mana last changed, so `dump modified` can tell them apart
+    health_modified_at: f64,
+    mana_modified_at: f64,
     health: i32,
+    // Full health for the health bar; entities without one use DEFAULT_MAX_HEALTH
+    max_health: i32,
     mana: Mana,
+    // Mana gained per `tick`; entities without it don't regenerate
+    mana_regen: i32,
+    // Successful casts so far; added on an entity's first cast
+    spells_cast: u32,
+    // Whether mana is at zero. Kept in step with `mana` so queries can filter on it;
+    // the `Mana` Drop message is only flavour
+    depleted: bool,
+    // Free-form labels such as "enemy" or "boss", for grouping without relations
+    tags: HashSet<String>,
+    // Named measurements such as "temperature", set with `set float`
+    floats: HashMap<String, f64>,
+    // Spell name -> mana cost, stored on the resources entity
+    spellbook: BTreeMap<String, i32>,
+    // Static entity, which is always alive
+    resources,
+}
+
+// Spells with a dedicated effect in `cast_spell`; any other name gets the generic effect
+const KNOWN_SPELLS: [&str; 5] = ["fireball", "heal", "lightning", "shield", "teleport"];
+
+// What a spell does to a target when cast with one; the mana spent sets the amount
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SpellEffect {
+    Damage,
+    Heal,
+    // Buffs such as `shield` only ever affect the caster
+    SelfOnly,
+}
+
+fn spell_effect(spell_name: &str) -> SpellEffect {
+    match spell_name.to_lowercase().as_str() {
+        "fireball" | "lightning" => SpellEffect::Damage,
+        "heal" => SpellEffect::Heal,
+        _ => SpellEffect::SelfOnly,
+    }
+}
+
+// Target health after the spell. Damage may go below zero (the death hook reports
+// it); healing stops at the target's max health
+fn health_after_spell(effect: SpellEffect, health: i32, amount: i32, max_health: i32) -> i32 {
+    match effect {
+        SpellEffect::Damage => health - amount,
+        SpellEffect::Heal => (health + amount).min(max_health.max(health)),
+        SpellEffect::SelfOnly => health,
+    }
+}
+
+// Default costs for KNOWN_SPELLS, in the same order
+const DEFAULT_SPELL_COSTS: [i32; 5] = [30, 20, 40, 15, 50];
+
+// Common values offered when completing `set health`/`set mana`
+const HEALTH_MANA_PRESETS: [&str; 4] = ["0", "25", "50", "100"];
+
+// Decimal places `get` shows for float values
+const FLOAT_PRECISION: usize = 3;
+
+// Accepts anything `f64::parse` does, including scientific notation like 1.5e-3,
+// but not NaN or infinities, which can't be compared or summed meaningfully
+fn parse_float(text: &str) -> Result<f64, String> {
+    match text.parse::<f64>() {
+        Ok(value) if value.is_finite() => Ok(value),
+        Ok(_) => Err(format!("Invalid value '{}', must be finite", text)),
+        Err(_) => Err(format!("Invalid value '{}', must be a number", text)),
+    }
+}
+
+// Commands offered by the `--panes` REPL
+const PANE_COMMANDS: [&str; 7] = ["pane", "delpane", "subs", "stats", "help", "quit", "exit"];
+
+// Names of the change-detection systems, in schedule order
+const ADDED_SYSTEM: &str = "added_components";
+const MODIFIED_SYSTEM: &str = "modified_components";
+const REMOVED_SYSTEM: &str = "removed_components";
+
+// How the next change schedule pass behaves: `system` limits it to one system
+// (None runs them all) and `quiet` drains the change queries without printing
+#[derive(Clone, Copy, Default)]
+struct ChangePass {
+    system: Option<&'static str>,
+    quiet: bool,
+}
+
+impl ChangePass {
+    fn selects(&self, system_name: &str) -> bool {
+        self.system.is_none_or(|selected| selected == system_name)
+    }
+}
+
+type ChangeSelection = Arc<Mutex<ChangePass>>;
+
+// Each change system opens its own group so `dump` output reads system by system
+fn print_change_group(system_name: &str) {
+    println!(
+        "  {}",
+        format!("-- {} --", system_name).bright_white().bold()
+    );
+}
+
+fn current_time() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs_f64()
+}
+
+fn format_age(now: f64, modified_at: f64) -> String {
+    format!("{:.1}s ago", (now - modified_at).max(0.0))
 }
 
+// What `top [field]` can rank by; `descendants` counts everything below an entity
+const LEADERBOARD_FIELDS: [&str; 4] = ["health", "mana", "spells_cast", "descendants"];
+const DEFAULT_LEADERBOARD_SIZE: usize = 5;
+
+// Prompt template used unless `--prompt` or `set prompt` replaces it
+const DEFAULT_PROMPT: &str = "►";
+
+// Entities shown by `recent` when no count is given
+const DEFAULT_RECENT_COUNT: usize = 10;
+
+// Layouts accepted by `tree [mode]`
+const TREE_MODES: [&str; 3] = ["dfs", "bfs", "topo"];
+
 struct ReplState {
     world: World,
     entity_names: HashMap<String, Entity>,
-    // Systems for change detection
-    added_system: BoxedSystem,
-    modified_system: BoxedSystem,
-    removed_system: BoxedSystem,
+    // Added, modified and removed change-detection systems, run in one pass by `dump`
+    change_schedule: Schedule,
+    // Which of the change systems the next pass reports, shared with each system
+    change_selection: ChangeSelection,
+    // Applies each entity's mana_regen once per `tick`
+    regen_system: BoxedSystem,
+    // Health values as of the last dump, shared with the modified system for old -> new diffs
+    health_snapshot: Arc<Mutex<HashMap<Entity, i32>>>,
+    // Print how long each command took (`time on|off`)
+    timing: bool,
+    // Despawn entities whose health drops to 0 or below (`set autodeath on|off`)
+    autodeath: bool,
+    // Health colour bands, shared with the added/modified systems (`set thresholds`)
+    health_thresholds: Arc<Mutex<HealthThresholds>>,
+    // Read-only command re-run after every command (`watch ...` / `unwatch`)
+    watch: Option<String>,
+    // In-memory world snapshots taken with `checkpoint [name]`. Kept as typed records
+    // rather than JSON strings: there is no file save to share a format with, and
+    // parsing would only add a failure path to `restore`. Sorted for `checkpoints`
+    checkpoints: BTreeMap<String, Checkpoint>,
+    // First-token rewrites applied before dispatch (`alias [name] [expansion]`)
+    aliases: HashMap<String, String>,
+    output: OutputMode,
+    // Prompt template, re-rendered before every readline (`set prompt` / `--prompt`)
+    prompt: String,
+    // Replayable record of mutating commands (`--log [path]`), appended to as they run
+    audit_log: Option<std::fs::File>,
+    // Nesting of `source` calls, so a script that sources itself stops
+    source_depth: usize,
 }
 
 struct MyHelper {
@@ -126,8 +462,8 @@ impl Highlighter for MyHelper {
     }
 
     fn highlight_hint<'h>(&self, hint: &'h str) -> std::borrow::Cow<'h, str> {
-        // Use dim/gray color (ANSI code 90) for completion hints
-        std::borrow::Cow::Owned(format!("\x1b[90m{}\x1b[0m", hint))
+        // Dim gray for completion hints; going through `colored` keeps NO_COLOR honoured
+        std::borrow::Cow::Owned(hint.bright_black().to_string())
     }
 
     fn highlight<'l>(&self, line: &'l str, pos: usize) -> std::borrow::Cow<'l, str> {
@@ -156,19 +492,145 @@ impl Helper for MyHelper {}
 
 struct MyCompleter {
     entity_names: Vec<String>,
+    // Float keys already set on each entity, for `set float [name] [key]`
+    float_keys: HashMap<String, Vec<String>>,
+    // Set for `--panes`, which completes the pane REPL's commands instead
+    pane_mode: bool,
+    dataset_ids: Vec<String>,
+    pane_numbers: Vec<String>,
 }
 
 impl MyCompleter {
     fn new() -> Self {
         Self {
             entity_names: Vec::new(),
+            float_keys: HashMap::new(),
+            pane_mode: false,
+            dataset_ids: Vec::new(),
+            pane_numbers: Vec::new(),
+        }
+    }
+
+    fn for_panes() -> Self {
+        Self {
+            pane_mode: true,
+            ..Self::new()
         }
     }
 
+    fn update_panes(&mut self, dataset_ids: Vec<String>, pane_numbers: Vec<String>) {
+        self.dataset_ids = dataset_ids;
+        self.pane_numbers = pane_numbers;
+    }
+
+    fn complete_pane_command(&self, line: &str, pos: usize) -> (usize, Vec<Pair>) {
+        let line_up_to_pos = &line[..pos];
+        let parts: Vec<&str> = line_up_to_pos.split_whitespace().collect();
+        let ends_with_space = line_up_to_pos.ends_with(' ');
+
+        let (partial, options): (&str, Vec<&str>) = match parts.as_slice() {
+            [] => ("", PANE_COMMANDS.to_vec()),
+            [partial] if !ends_with_space => (*partial, PANE_COMMANDS.to_vec()),
+            // Only the dataset after the last comma is being typed
+            ["pane"] if ends_with_space => ("", self.dataset_options()),
+            ["pane", list] if !ends_with_space => {
+                let partial = list.rsplit(',').next().unwrap_or("");
+                (partial, self.dataset_options())
+            }
+            ["delpane"] if ends_with_space => ("", self.pane_number_options()),
+            ["delpane", partial] if !ends_with_space => (*partial, self.pane_number_options()),
+            _ => return (pos, Vec::new()),
+        };
+
+        let candidates = options
+            .into_iter()
+            .filter(|option| option.starts_with(partial))
+            .map(|option| Pair {
+                display: option.to_string(),
+                replacement: option.to_string(),
+            })
+            .collect();
+        (pos - partial.len(), candidates)
+    }
+
+    fn dataset_options(&self) -> Vec<&str> {
+        self.dataset_ids.iter().map(String::as_str).collect()
+    }
+
+    fn pane_number_options(&self) -> Vec<&str> {
+        self.pane_numbers.iter().map(String::as_str).collect()
+    }
+
     fn update_entities(&mut self, entities: &HashMap<String, Entity>) {
         self.entity_names = entities.keys().cloned().collect();
         self.entity_names.sort();
     }
+
+    fn update_float_keys(&mut self, float_keys: HashMap<String, Vec<String>>) {
+        self.float_keys = float_keys;
+    }
+
+    // The entity's existing float keys starting with `partial`
+    fn float_key_candidates(&self, name: &str, partial: &str) -> Vec<Pair> {
+        self.float_keys
+            .get(name)
+            .into_iter()
+            .flatten()
+            .filter(|key| key.starts_with(partial))
+            .map(|key| Pair {
+                display: key.clone(),
+                replacement: key.clone(),
+            })
+            .collect()
+    }
+
+    // Entity names matching `partial` as a fuzzy subsequence, best match first
+    fn entity_candidates(&self, partial: &str) -> Vec<Pair> {
+        let mut scored: Vec<(i32, &String)> = self
+            .entity_names
+            .iter()
+            .filter_map(|entity| fuzzy_score(entity, partial).map(|score| (score, entity)))
+            .collect();
+        // Stable sort keeps the alphabetical order among equal scores
+        scored.sort_by(|a, b| b.0.cmp(&a.0));
+
+        scored
+            .into_iter()
+            .map(|(_, entity)| Pair {
+                display: entity.clone(),
+                // Names with spaces must be quoted to come back as one argument
+                replacement: quote(entity),
+            })
+            .collect()
+    }
+}
+
+// Scores `candidate` against `pattern` as a case-insensitive subsequence.
+// Prefixes, also compared without case, always outrank other matches so prefix
+// completion still wins; otherwise consecutive runs and word-start hits score higher.
+fn fuzzy_score(candidate: &str, pattern: &str) -> Option<i32> {
+    if candidate
+        .to_ascii_lowercase()
+        .starts_with(&pattern.to_ascii_lowercase())
+    {
+        return Some(1000 - candidate.len() as i32);
+    }
+
+    let mut score = 0;
+    let mut previous_match: Option<usize> = None;
+    let mut chars = candidate
+        .char_indices()
+        .map(|(i, c)| (i, c.to_ascii_lowercase()));
+    for wanted in pattern.chars().map(|c| c.to_ascii_lowercase()) {
+        let (index, _) = chars.find(|(_, c)| *c == wanted)?;
+        score += 1;
+        if index == 0 || previous_match.is_some_and(|prev| prev + 1 == index) {
+            score += 5;
+        }
+        previous_match = Some(index);
+    }
+
+    Some(score)
 }
 
 impl Completer for MyCompleter {
@@ -180,21 +642,68 @@ impl Completer for MyCompleter {
         pos: usize,
         _ctx: &Context<'_>,
     ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        if self.pane_mode {
+            return Ok(self.complete_pane_command(line, pos));
+        }
+
         let base_commands = vec![
             "add entity",
+            "spawn",
             "get",
+            "clone",
+            "diff",
+            "whereis",
             "set-relation child",
             "rm-relation child",
+            "reparent",
             "set health",
+            "set max-health",
+            "set regen",
+            "set float",
+            "tick",
             "set mana",
+            "set editmode vi",
+            "set editmode emacs",
+            "set thresholds",
+            "set output json",
+            "set output human",
+            "set prompt",
+            "set autodeath on",
+            "set autodeath off",
+            "randomize health",
             "cast",
+            "cast-aoe",
+            "set-spell-cost",
             "rm",
+            "tag",
+            "untag",
+            "find tag",
+            "checkpoint",
+            "checkpoints",
+            "source",
+            "restore",
+            "find health",
+            "find castcount",
+            "find depleted",
+            "find relation",
+            "watch",
+            "unwatch",
+            "mark-seen",
             "dump",
             "list",
+            "stats",
+            "top",
+            "recent",
+            "histogram",
+            "components",
             "tree",
             "tree dfs",
+            "tree bfs",
             "tree topo",
             "echo",
+            "alias",
+            "time on",
+            "time off",
             "help",
             "quit",
             "exit",
@@ -242,6 +751,33 @@ impl Completer for MyCompleter {
                         replacement: "child".to_string(),
                     });
                 }
+                "help" => {
+                    start = pos;
+                    for (command, _) in HELP_ENTRIES {
+                        candidates.push(Pair {
+                            display: command.to_string(),
+                            replacement: command.to_string(),
+                        });
+                    }
+                }
+                "top" => {
+                    start = pos;
+                    for field in &LEADERBOARD_FIELDS {
+                        candidates.push(Pair {
+                            display: field.to_string(),
+                            replacement: field.to_string(),
+                        });
+                    }
+                }
+                "find" => {
+                    start = pos;
+                    for filter in &["tag", "health", "castcount", "depleted", "relation"] {
+                        candidates.push(Pair {
+                            display: filter.to_string(),
+                            replacement: filter.to_string(),
+                        });
+                    }
+                }
                 "add" => {
                     start = pos;
                     candidates.push(Pair {
@@ -251,13 +787,22 @@ impl Completer for MyCompleter {
                 }
                 "tree" => {
                     start = pos;
-                    for mode in &["dfs", "topo"] {
+                    for mode in &TREE_MODES {
                         candidates.push(Pair {
                             display: mode.to_string(),
                             replacement: mode.to_string(),
                         });
                     }
                 }
+                "cast" | "cast-aoe" => {
+                    start = pos;
+                    for spell in &KNOWN_SPELLS {
+                        candidates.push(Pair {
+                            display: spell.to_string(),
+                            replacement: spell.to_string(),
+                        });
+                    }
+                }
                 _ => {}
             }
         } else if parts.len() == 2 && !line_up_to_pos.ends_with(' ') {
@@ -275,10 +820,22 @@ impl Completer for MyCompleter {
                         }
                     }
                 }
+                "help" => {
+                    let partial = parts[1];
+                    start = pos - partial.len();
+                    for (command, _) in HELP_ENTRIES {
+                        if command.starts_with(partial) {
+                            candidates.push(Pair {
+                                display: command.to_string(),
+                                replacement: command.to_string(),
+                            });
+                        }
+                    }
+                }
                 "tree" => {
                     let partial = parts[1];
                     start = pos - partial.len();
-                    for mode in &["dfs", "topo"] {
+                    for mode in &TREE_MODES {
                         if mode.starts_with(partial) {
                             candidates.push(Pair {
                                 display: mode.to_string(),
@@ -287,6 +844,19 @@ impl Completer for MyCompleter {
                         }
                     }
                 }
+                "cast" | "cast-aoe" => {
+                    // Unknown spell names are still castable, so only known ones are offered
+                    let partial = parts[1];
+                    start = pos - partial.len();
+                    for spell in &KNOWN_SPELLS {
+                        if spell.starts_with(partial) {
+                            candidates.push(Pair {
+                                display: spell.to_string(),
+                                replacement: spell.to_string(),
+                            });
+                        }
+                    }
+                }
                 _ => {
                     // Fall through to existing entity completion logic below
                 }
@@ -296,79 +866,179 @@ impl Completer for MyCompleter {
         // Handle entity name completions for commands that expect entity names
         if candidates.is_empty() {
             match parts.as_slice() {
-                ["get", partial] if !line_up_to_pos.ends_with(' ') => {
+                ["whereis", partial] if !line_up_to_pos.ends_with(' ') => {
                     start = pos - partial.len();
-                    for entity in &self.entity_names {
-                        if entity.starts_with(partial) {
-                            candidates.push(Pair {
-                                display: entity.clone(),
-                                replacement: entity.clone(),
-                            });
-                        }
-                    }
+                    candidates.extend(self.entity_candidates(partial));
+                }
+                // `get` takes any number of names, so every argument completes one
+                ["get", .., partial] if !line_up_to_pos.ends_with(' ') => {
+                    start = pos - partial.len();
+                    candidates.extend(self.entity_candidates(partial));
                 }
-                ["set", "health", partial] | ["set", "mana", partial]
+                ["get", ..] if line_up_to_pos.ends_with(' ') => {
+                    start = pos;
+                    candidates.extend(self.entity_candidates(""));
+                }
+                ["set", "health", partial]
+                | ["set", "max-health", partial]
+                | ["set", "regen", partial]
+                | ["set", "mana", partial]
+                | ["set", "float", partial]
                     if !line_up_to_pos.ends_with(' ') =>
                 {
                     start = pos - partial.len();
-                    for entity in &self.entity_names {
-                        if entity.starts_with(partial) {
+                    candidates.extend(self.entity_candidates(partial));
+                }
+                ["set", "health", name] | ["set", "mana", name]
+                    if line_up_to_pos.ends_with(' ')
+                        && self.entity_names.contains(&name.to_string()) =>
+                {
+                    start = pos;
+                    for preset in &HEALTH_MANA_PRESETS {
+                        candidates.push(Pair {
+                            display: preset.to_string(),
+                            replacement: preset.to_string(),
+                        });
+                    }
+                }
+                ["set", "health", name, partial] | ["set", "mana", name, partial]
+                    if !line_up_to_pos.ends_with(' ')
+                        && self.entity_names.contains(&name.to_string()) =>
+                {
+                    start = pos - partial.len();
+                    for preset in &HEALTH_MANA_PRESETS {
+                        if preset.starts_with(partial) {
                             candidates.push(Pair {
-                                display: entity.clone(),
-                                replacement: entity.clone(),
+                                display: preset.to_string(),
+                                replacement: preset.to_string(),
                             });
                         }
                     }
                 }
+                ["set", "float", name]
+                    if line_up_to_pos.ends_with(' ')
+                        && self.entity_names.contains(&name.to_string()) =>
+                {
+                    start = pos;
+                    candidates.extend(self.float_key_candidates(name, ""));
+                }
+                ["set", "float", name, partial]
+                    if !line_up_to_pos.ends_with(' ')
+                        && self.entity_names.contains(&name.to_string()) =>
+                {
+                    start = pos - partial.len();
+                    candidates.extend(self.float_key_candidates(name, partial));
+                }
+                ["clone", partial] if !line_up_to_pos.ends_with(' ') => {
+                    start = pos - partial.len();
+                    candidates.extend(self.entity_candidates(partial));
+                }
+                ["clone", _, _] if line_up_to_pos.ends_with(' ') => {
+                    start = pos;
+                    candidates.push(Pair {
+                        display: "with parents".to_string(),
+                        replacement: "with parents".to_string(),
+                    });
+                }
+                ["diff", partial] | ["diff", _, partial] if !line_up_to_pos.ends_with(' ') => {
+                    start = pos - partial.len();
+                    candidates.extend(self.entity_candidates(partial));
+                }
+                ["clone"] | ["diff"] | ["diff", _] | ["tag"] | ["untag"]
+                    if line_up_to_pos.ends_with(' ') =>
+                {
+                    start = pos;
+                    candidates.extend(self.entity_candidates(""));
+                }
+                ["cast-aoe", _, partial] if !line_up_to_pos.ends_with(' ') => {
+                    start = pos - partial.len();
+                    candidates.extend(self.entity_candidates(partial));
+                }
                 ["cast", _, partial] if !line_up_to_pos.ends_with(' ') => {
                     // Autocomplete entity names for caster
                     start = pos - partial.len();
-                    for entity in &self.entity_names {
-                        if entity.starts_with(partial) {
-                            candidates.push(Pair {
-                                display: entity.clone(),
-                                replacement: entity.clone(),
-                            });
-                        }
-                    }
+                    candidates.extend(self.entity_candidates(partial));
+                }
+                ["cast", _, caster] if line_up_to_pos.ends_with(' ') && *caster != "by" => {
+                    // Optional target
+                    start = pos;
+                    candidates.extend(self.entity_candidates(""));
+                }
+                ["cast", _, caster, partial]
+                    if !line_up_to_pos.ends_with(' ') && *caster != "by" =>
+                {
+                    start = pos - partial.len();
+                    candidates.extend(self.entity_candidates(partial));
                 }
-                ["rm", partial] if !line_up_to_pos.ends_with(' ') => {
+                ["tree", "dfs", partial] if !line_up_to_pos.ends_with(' ') => {
+                    // Optional subtree root
                     start = pos - partial.len();
+                    candidates.extend(self.entity_candidates(partial));
+                }
+                ["tree", "dfs"] if line_up_to_pos.ends_with(' ') => {
+                    start = pos;
                     for entity in &self.entity_names {
-                        if entity.starts_with(partial) {
-                            candidates.push(Pair {
-                                display: entity.clone(),
-                                replacement: entity.clone(),
-                            });
-                        }
+                        candidates.push(Pair {
+                            display: entity.clone(),
+                            replacement: entity.clone(),
+                        });
                     }
                 }
+                ["rm", partial]
+                | ["rm", "-f" | "--force", partial]
+                | ["tag", partial]
+                | ["untag", partial]
+                    if !line_up_to_pos.ends_with(' ') =>
+                {
+                    start = pos - partial.len();
+                    candidates.extend(self.entity_candidates(partial));
+                }
+                ["reparent", partial] | ["reparent", _, partial]
+                    if !line_up_to_pos.ends_with(' ') =>
+                {
+                    start = pos - partial.len();
+                    candidates.extend(self.entity_candidates(partial));
+                }
+                ["reparent"] | ["reparent", _] if line_up_to_pos.ends_with(' ') => {
+                    start = pos;
+                    candidates.extend(self.entity_candidates(""));
+                }
                 ["set-relation", "child", partial] | ["rm-relation", "child", partial]
                     if !line_up_to_pos.ends_with(' ') =>
                 {
                     start = pos - partial.len();
-                    for entity in &self.entity_names {
-                        if entity.starts_with(partial) {
-                            candidates.push(Pair {
-                                display: entity.clone(),
-                                replacement: entity.clone(),
-                            });
-                        }
-                    }
+                    candidates.extend(self.entity_candidates(partial));
+                }
+                ["set-relation", "child", _] | ["rm-relation", "child", _]
+                    if line_up_to_pos.ends_with(' ') =>
+                {
+                    start = pos;
+                    candidates.push(Pair {
+                        display: "parent".to_string(),
+                        replacement: "parent".to_string(),
+                    });
+                }
+                ["set-relation", "child", _, partial] | ["rm-relation", "child", _, partial]
+                    if !line_up_to_pos.ends_with(' ') && "parent".starts_with(partial) =>
+                {
+                    start = pos - partial.len();
+                    candidates.push(Pair {
+                        display: "parent".to_string(),
+                        replacement: "parent".to_string(),
+                    });
+                }
+                ["set-relation", "child", _, "parent"] | ["rm-relation", "child", _, "parent"]
+                    if line_up_to_pos.ends_with(' ') =>
+                {
+                    start = pos;
+                    candidates.extend(self.entity_candidates(""));
                 }
                 ["set-relation", "child", _, "parent", partial]
                 | ["rm-relation", "child", _, "parent", partial]
                     if !line_up_to_pos.ends_with(' ') =>
                 {
                     start = pos - partial.len();
-                    for entity in &self.entity_names {
-                        if entity.starts_with(partial) {
-                            candidates.push(Pair {
-                                display: entity.clone(),
-                                replacement: entity.clone(),
-                            });
-                        }
-                    }
+                    candidates.extend(self.entity_candidates(partial));
                 }
                 _ => {}
             }
@@ -380,135 +1050,228 @@ impl Completer for MyCompleter {
 
 impl ReplState {
     fn new() -> Self {
-        use flax::filter::ChangeFilter;
         use flax::query::QueryBorrow;
 
-        // Create systems for change detection using the proper Flax System API
-        let added_system = System::builder()
-            .with_name("added_components")
-            .with_query(Query::new((entity_ids(), components::name().added())))
-            .with_query(Query::new((
-                entity_ids(),
-                components::name(),
-                health().added(),
-            )))
-            .build(
-                |mut name_query: QueryBorrow<(EntityIds, ChangeFilter<String>)>,
-                 mut health_query: QueryBorrow<(
-                    EntityIds,
-                    flax::Component<String>,
-                    ChangeFilter<i32>,
-                )>| {
-                    let mut found_changes = false;
-
-                    // Query for newly added name components
-                    for (entity, name) in name_query.iter() {
-                        found_changes = true;
-                        println!(
-                            "  [{}] {} {} ({})",
-                            "ADDED".green().bold(),
-                            "Entity".white(),
-                            format!("{:?}", entity).bright_magenta(),
-                            name.bright_cyan()
-                        );
-                    }
+        let health_thresholds = Arc::new(Mutex::new(HealthThresholds::default()));
+        let added_thresholds = health_thresholds.clone();
+        let modified_thresholds = health_thresholds.clone();
 
-                    // Query for newly added health components
-                    for (entity, name, health_val) in health_query.iter() {
-                        found_changes = true;
-                        let health_color = if *health_val > 75 {
-                            format!("{}", *health_val).green()
-                        } else if *health_val > 30 {
-                            format!("{}", *health_val).yellow()
-                        } else {
-                            format!("{}", *health_val).red()
-                        };
-                        println!(
-                            "  [{}] {} {} ({}) - Health: {}",
-                            "ADDED HEALTH".green().bold(),
-                            "Entity".white(),
-                            format!("{:?}", entity).bright_magenta(),
-                            name.bright_cyan(),
-                            health_color
-                        );
-                    }
+        let change_selection: ChangeSelection = Arc::new(Mutex::new(ChangePass::default()));
+        let added_selection = change_selection.clone();
+        let modified_selection = change_selection.clone();
+        let removed_selection = change_selection.clone();
 
-                    if !found_changes {
-                        println!("    {}", "No added components to display".yellow());
-                    }
-                    () // Explicitly return ()
-                },
-            )
+        // The change systems own their queries and borrow them only when selected:
+        // borrowing advances a query's change tick, so a skipped system would
+        // otherwise swallow changes meant for its next `dump`
+        let mut added_name_query = Query::new((entity_ids(), components::name().added()));
+        let mut added_health_query =
+            Query::new((entity_ids(), components::name(), health().added()));
+        let added_system = System::builder()
+            .with_name(ADDED_SYSTEM)
+            .with_world()
+            .build(move |world: &World| {
+                let pass = *added_selection.lock().unwrap();
+                if !pass.selects(ADDED_SYSTEM) {
+                    return;
+                }
+                if pass.quiet {
+                    added_name_query.borrow(world).iter().count();
+                    added_health_query.borrow(world).iter().count();
+                    return;
+                }
+                print_change_group(ADDED_SYSTEM);
+                let mut found_changes = false;
+
+                // Query for newly added name components
+                for (entity, name) in added_name_query.borrow(world).iter() {
+                    found_changes = true;
+                    println!(
+                        "  [{}] {} {} ({})",
+                        "ADDED".green().bold(),
+                        "Entity".white(),
+                        format!("{:?}", entity).bright_magenta(),
+                        name.bright_cyan()
+                    );
+                }
+
+                // Query for newly added health components
+                let thresholds = *added_thresholds.lock().unwrap();
+                for (entity, name, health_val) in added_health_query.borrow(world).iter() {
+                    found_changes = true;
+                    let health_color = health_color(*health_val, &thresholds);
+                    println!(
+                        "  [{}] {} {} ({}) - Health: {}",
+                        "ADDED HEALTH".green().bold(),
+                        "Entity".white(),
+                        format!("{:?}", entity).bright_magenta(),
+                        name.bright_cyan(),
+                        health_color
+                    );
+                }
+
+                if !found_changes {
+                    println!("    {}", "No added components to display".yellow());
+                }
+                () // Explicitly return ()
+            })
             .boxed();
 
+        let health_snapshot: Arc<Mutex<HashMap<Entity, i32>>> =
+            Arc::new(Mutex::new(HashMap::new()));
+        let snapshot = health_snapshot.clone();
+
+        let mut modified_health_query = Query::new((
+            entity_ids(),
+            components::name(),
+            health().modified(),
+            health_modified_at().opt(),
+        ));
+        let mut modified_query = Query::new((
+            entity_ids(),
+            components::name(),
+            last_modified().modified(),
+            health_modified_at().opt(),
+            mana_modified_at().opt(),
+        ));
         let modified_system = System::builder()
-            .with_name("modified_components")
-            .with_query(Query::new((
-                entity_ids(),
-                components::name(),
-                health().modified(),
-            )))
-            .with_query(Query::new((
-                entity_ids(),
-                components::name(),
-                last_modified().modified(),
-            )))
-            .build(
-                |mut health_query: QueryBorrow<(
-                    EntityIds,
-                    flax::Component<String>,
-                    ChangeFilter<i32>,
-                )>,
-                 mut modified_query: QueryBorrow<(
-                    EntityIds,
-                    flax::Component<String>,
-                    ChangeFilter<f64>,
-                )>| {
-                    let mut found_changes = false;
-
-                    // Query for modified health components
-                    for (entity, name, health_val) in health_query.iter() {
-                        found_changes = true;
-                        let health_color = if *health_val > 75 {
-                            format!("{}", *health_val).green()
-                        } else if *health_val > 30 {
-                            format!("{}", *health_val).yellow()
-                        } else {
-                            format!("{}", *health_val).red()
-                        };
-                        println!(
-                            "  [{}] {} {} ({}) - Health: {}",
-                            "MODIFIED HEALTH".blue().bold(),
-                            "Entity".white(),
-                            format!("{:?}", entity).bright_magenta(),
-                            name.bright_cyan(),
-                            health_color
-                        );
-                    }
+            .with_name(MODIFIED_SYSTEM)
+            .with_world()
+            .build(move |world: &World| {
+                let pass = *modified_selection.lock().unwrap();
+                if !pass.selects(MODIFIED_SYSTEM) {
+                    return;
+                }
+                if pass.quiet {
+                    modified_health_query.borrow(world).iter().count();
+                    modified_query.borrow(world).iter().count();
+                    return;
+                }
+                print_change_group(MODIFIED_SYSTEM);
+                let mut found_changes = false;
+                let snapshot = snapshot.lock().unwrap();
+                let thresholds = *modified_thresholds.lock().unwrap();
+                let now = current_time();
 
-                    // Query for general modifications via last_modified
-                    for (entity, name, _timestamp) in modified_query.iter() {
-                        found_changes = true;
-                        println!(
-                            "  [{}] {} {} ({})",
-                            "MODIFIED".blue().bold(),
-                            "Entity".white(),
-                            format!("{:?}", entity).bright_magenta(),
-                            name.bright_cyan()
-                        );
+                // Query for modified health components, most recently changed first
+                let mut health_rows: Vec<(Entity, String, i32, Option<f64>)> =
+                    modified_health_query
+                        .borrow(world)
+                        .iter()
+                        .map(|(entity, name, health_val, at)| {
+                            (entity, name.clone(), *health_val, at.copied())
+                        })
+                        .collect();
+                health_rows.sort_by(|a, b| b.3.unwrap_or(0.0).total_cmp(&a.3.unwrap_or(0.0)));
+                for (entity, name, health_val, modified_at) in health_rows {
+                    found_changes = true;
+                    let health_color = health_color(health_val, &thresholds);
+
+                    // Show the previous value and delta when we have a snapshot from the last dump
+                    let health_str = match snapshot.get(&entity) {
+                        Some(&old_val) => {
+                            let delta = health_val - old_val;
+                            let delta_str = if delta > 0 {
+                                format!("+{}", delta).green()
+                            } else if delta < 0 {
+                                format!("{}", delta).red()
+                            } else {
+                                "±0".bright_black()
+                            };
+                            format!(
+                                "{} {} {} ({})",
+                                old_val.to_string().bright_black(),
+                                "->".white(),
+                                health_color,
+                                delta_str
+                            )
+                        }
+                        None => health_color.to_string(),
+                    };
+
+                    let age = modified_at
+                        .map(|at| {
+                            format!(" [{}]", format_age(now, at))
+                                .bright_black()
+                                .to_string()
+                        })
+                        .unwrap_or_default();
+                    println!(
+                        "  [{}] {} {} ({}) - Health: {}{}",
+                        "MODIFIED HEALTH".blue().bold(),
+                        "Entity".white(),
+                        format!("{:?}", entity).bright_magenta(),
+                        name.bright_cyan(),
+                        health_str,
+                        age
+                    );
+                }
+
+                // Query for general modifications via last_modified, most recent first,
+                // with the per-component ages so health and mana changes can be told apart
+                let mut modified_rows: Vec<(Entity, String, f64, Option<f64>, Option<f64>)> =
+                    modified_query
+                        .borrow(world)
+                        .iter()
+                        .map(|(entity, name, timestamp, health_at, mana_at)| {
+                            (
+                                entity,
+                                name.clone(),
+                                *timestamp,
+                                health_at.copied(),
+                                mana_at.copied(),
+                            )
+                        })
+                        .collect();
+                modified_rows.sort_by(|a, b| b.2.total_cmp(&a.2));
+                for (entity, name, timestamp, health_at, mana_at) in modified_rows {
+                    found_changes = true;
+                    let mut ages = vec![format!("any {}", format_age(now, timestamp))];
+                    if let Some(at) = health_at {
+                        ages.push(format!("health {}", format_age(now, at)));
+                    }
+                    if let Some(at) = mana_at {
+                        ages.push(format!("mana {}", format_age(now, at)));
                     }
+                    println!(
+                        "  [{}] {} {} ({}) {}",
+                        "MODIFIED".blue().bold(),
+                        "Entity".white(),
+                        format!("{:?}", entity).bright_magenta(),
+                        name.bright_cyan(),
+                        format!("[{}]", ages.join(", ")).bright_black()
+                    );
+                }
+
+                if !found_changes {
+                    println!("    {}", "No modified components to display".yellow());
+                }
+                () // Explicitly return ()
+            })
+            .boxed();
 
-                    if !found_changes {
-                        println!("    {}", "No modified components to display".yellow());
+        let regen_system = System::builder()
+            .with_name("mana_regen")
+            .with_query(Query::new((mana().as_mut(), mana_regen())))
+            .build(
+                |mut regen_query: QueryBorrow<(ComponentMut<Mana>, flax::Component<i32>)>| {
+                    for (mana_val, rate) in regen_query.iter() {
+                        // Negative rates drain; either way stay within [0, maximum]
+                        mana_val.current =
+                            (mana_val.current + *rate).clamp(0, mana_val.maximum.max(0));
                     }
-                    () // Explicitly return ()
                 },
             )
             .boxed();
 
         let removed_system = System::builder()
-            .with_name("removed_components")
-            .build(|| {
+            .with_name(REMOVED_SYSTEM)
+            .build(move || {
+                let pass = *removed_selection.lock().unwrap();
+                if !pass.selects(REMOVED_SYSTEM) || pass.quiet {
+                    return;
+                }
+                print_change_group(REMOVED_SYSTEM);
                 println!(
                     "    {}",
                     "Note: Removed component tracking not fully implemented yet".yellow()
@@ -517,16 +1280,61 @@ impl ReplState {
             })
             .boxed();
 
+        // Sequential order keeps the groups in added, modified, removed order
+        let change_schedule = Schedule::builder()
+            .with_system(added_system)
+            .with_system(modified_system)
+            .with_system(removed_system)
+            .build();
+
+        let mut world = World::new();
+        let default_spellbook: BTreeMap<String, i32> = KNOWN_SPELLS
+            .iter()
+            .zip(DEFAULT_SPELL_COSTS)
+            .map(|(spell, cost)| (spell.to_string(), cost))
+            .collect();
+        world
+            .set(resources(), spellbook(), default_spellbook)
+            .expect("resources entity is always alive");
+
         Self {
-            world: World::new(),
+            world,
             entity_names: HashMap::new(),
-            added_system,
-            modified_system,
-            removed_system,
+            change_schedule,
+            change_selection,
+            regen_system,
+            health_snapshot,
+            timing: false,
+            autodeath: false,
+            health_thresholds,
+            watch: None,
+            checkpoints: BTreeMap::new(),
+            aliases: DEFAULT_ALIASES
+                .iter()
+                .map(|(alias, expansion)| (alias.to_string(), expansion.to_string()))
+                .collect(),
+            output: OutputMode::Human,
+            prompt: DEFAULT_PROMPT.to_string(),
+            audit_log: None,
+            source_depth: 0,
         }
     }
 
+    // Fills in the prompt template's tokens: {count} (entities), {checkpoints} and
+    // {output} (human or json)
+    fn render_prompt(&self) -> String {
+        let output = match self.output {
+            OutputMode::Human => "human",
+            OutputMode::Json => "json",
+        };
+        self.prompt
+            .replace("{count}", &self.entity_names.len().to_string())
+            .replace("{checkpoints}", &self.checkpoints.len().to_string())
+            .replace("{output}", output)
+    }
+
     fn add_entity(&mut self, name: &str) -> Result<Entity, String> {
+        validate_entity_name(name)?;
         if self.entity_names.contains_key(name) {
             return Err(format!("Entity '{}' already exists", name));
         }
@@ -542,6 +1350,135 @@ impl ReplState {
         Ok(entity)
     }
 
+    // Copies every component, and with `with_parents` the child_of relations and
+    // their labels. Children are not copied, since a child can't gain a second parent silently
+    fn clone_entity(
+        &mut self,
+        source_name: &str,
+        new_name: &str,
+        with_parents: bool,
+    ) -> Result<Entity, String> {
+        let source = self.get_entity(source_name)?;
+        if self.entity_names.contains_key(new_name) {
+            return Err(format!("Entity '{}' already exists", new_name));
+        }
+
+        let health_val = self.world.get(source, health()).ok().map(|h| *h);
+        let max_health_val = self.world.get(source, max_health()).ok().map(|m| *m);
+        let regen_val = self.world.get(source, mana_regen()).ok().map(|r| *r);
+        let tags_val = self.world.get(source, tags()).ok().map(|t| t.clone());
+        let floats_val = self.world.get(source, floats()).ok().map(|f| f.clone());
+        let spells_cast_val = self.world.get(source, spells_cast()).ok().map(|c| *c);
+        // Copy the numbers rather than the `Mana` itself, so the clone's Drop names the clone
+        let mana_val = self
+            .world
+            .get(source, mana())
+            .ok()
+            .map(|m| (m.current, m.maximum));
+        let parents: Vec<(Entity, String)> = if with_parents {
+            Query::new(relations_like(components::child_of))
+                .borrow(&self.world)
+                .get(source)
+                .map(|relations| {
+                    relations
+                        .map(|(parent, _)| {
+                            let label = self
+                                .world
+                                .get(parent, has_child(source))
+                                .map(|info| info.label.clone())
+                                .unwrap_or_else(|_| "child".to_string());
+                            (parent, label)
+                        })
+                        .collect()
+                })
+                .unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+
+        let entity = self.add_entity(new_name)?;
+        let timestamp = self.get_current_time();
+
+        if let Some(health_val) = health_val {
+            self.world
+                .set(entity, health(), health_val)
+                .map_err(|e| format!("Failed to set health: {:?}", e))?;
+        }
+        if let Some(maximum) = max_health_val {
+            self.world
+                .set(entity, max_health(), maximum)
+                .map_err(|e| format!("Failed to set max health: {:?}", e))?;
+        }
+        if let Some(rate) = regen_val {
+            self.world
+                .set(entity, mana_regen(), rate)
+                .map_err(|e| format!("Failed to set mana regen: {:?}", e))?;
+        }
+        if let Some(labels) = tags_val {
+            self.world
+                .set(entity, tags(), labels)
+                .map_err(|e| format!("Failed to set tags: {:?}", e))?;
+        }
+        if let Some(values) = floats_val {
+            self.world
+                .set(entity, floats(), values)
+                .map_err(|e| format!("Failed to set floats: {:?}", e))?;
+        }
+        if let Some(count) = spells_cast_val {
+            self.world
+                .set(entity, spells_cast(), count)
+                .map_err(|e| format!("Failed to set spells cast: {:?}", e))?;
+        }
+        if let Some((current, maximum)) = mana_val {
+            let mana_component = Mana {
+                current,
+                maximum,
+                entity_name: new_name.to_string(),
+            };
+            self.world
+                .set(entity, mana(), mana_component)
+                .map_err(|e| format!("Failed to set mana: {:?}", e))?;
+            self.sync_depleted(entity);
+        }
+        for (parent, label) in parents {
+            self.world
+                .set(entity, components::child_of(parent), ())
+                .map_err(|e| format!("Failed to set child_of relation: {:?}", e))?;
+            self.world
+                .set(
+                    parent,
+                    has_child(entity),
+                    RelationInfo {
+                        since: timestamp,
+                        label,
+                    },
+                )
+                .map_err(|e| format!("Failed to set has_child relation: {:?}", e))?;
+            self.world.set(parent, last_modified(), timestamp).ok();
+        }
+        self.world.set(entity, last_modified(), timestamp).ok();
+
+        Ok(entity)
+    }
+
+    fn spawn_entities(&mut self, prefix: &str, count: usize) -> Result<Vec<Entity>, String> {
+        let mut created = Vec::with_capacity(count);
+        for i in 0..count {
+            match self.add_entity(&format!("{}{}", prefix, i)) {
+                Ok(entity) => created.push(entity),
+                Err(e) => {
+                    // Roll back everything spawned so far so the batch is all-or-nothing
+                    for j in 0..created.len() {
+                        self.remove_entity(&format!("{}{}", prefix, j)).ok();
+                    }
+                    return Err(e);
+                }
+            }
+        }
+
+        Ok(created)
+    }
+
     fn get_entity(&self, name: &str) -> Result<Entity, String> {
         self.entity_names
             .get(name)
@@ -549,7 +1486,8 @@ impl ReplState {
             .ok_or_else(|| format!("Entity '{}' not found", name))
     }
 
-    fn set_health(&mut self, name: &str, health_value: i32) -> Result<(), String> {
+    // Returns true if the entity died and `autodeath` despawned it
+    fn set_health(&mut self, name: &str, health_value: i32) -> Result<bool, String> {
         let entity = self.get_entity(name)?;
         let timestamp = self.get_current_time();
 
@@ -558,30 +1496,212 @@ impl ReplState {
             .map_err(|e| format!("Failed to set health: {:?}", e))?;
 
         self.world.set(entity, last_modified(), timestamp).ok();
+        self.world.set(entity, health_modified_at(), timestamp).ok();
+
+        if health_value <= 0 {
+            println!(
+                "💀 {} {}",
+                format!(
+                    "{} has fallen ({} health), their last breath lingers in the air...",
+                    name, health_value
+                )
+                .red()
+                .italic(),
+                "🪦".bright_black()
+            );
+
+            if self.autodeath {
+                // Same path as `rm`, so relations are cleaned up with the entity
+                self.remove_entity(name)?;
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    fn set_max_health(&mut self, name: &str, maximum: i32) -> Result<(), String> {
+        if maximum <= 0 {
+            return Err(format!("max health must be positive, got {}", maximum));
+        }
+        let entity = self.get_entity(name)?;
+        let timestamp = self.get_current_time();
+
+        self.world
+            .set(entity, max_health(), maximum)
+            .map_err(|e| format!("Failed to set max health: {:?}", e))?;
+
+        self.world.set(entity, last_modified(), timestamp).ok();
+
+        Ok(())
+    }
+
+    fn set_regen(&mut self, name: &str, rate: i32) -> Result<(), String> {
+        let entity = self.get_entity(name)?;
+        let timestamp = self.get_current_time();
+
+        self.world
+            .set(entity, mana_regen(), rate)
+            .map_err(|e| format!("Failed to set mana regen: {:?}", e))?;
+
+        self.world.set(entity, last_modified(), timestamp).ok();
+
+        Ok(())
+    }
+
+    fn set_float(&mut self, name: &str, key: &str, value: f64) -> Result<(), String> {
+        let entity = self.get_entity(name)?;
+        let timestamp = self.get_current_time();
+
+        match self.world.get_mut(entity, floats()) {
+            Ok(mut values) => {
+                values.insert(key.to_string(), value);
+            }
+            Err(_) => {
+                self.world
+                    .set(entity, floats(), HashMap::from([(key.to_string(), value)]))
+                    .map_err(|e| format!("Failed to set floats: {:?}", e))?;
+            }
+        }
 
+        self.world.set(entity, last_modified(), timestamp).ok();
         Ok(())
     }
 
+    // Float keys per named entity, sorted, for completion
+    fn float_keys(&self) -> HashMap<String, Vec<String>> {
+        Query::new((components::name(), floats()))
+            .borrow(&self.world)
+            .iter()
+            .map(|(name, values)| {
+                let mut keys: Vec<String> = values.keys().cloned().collect();
+                keys.sort();
+                (name.clone(), keys)
+            })
+            .collect()
+    }
+
+    // Runs the regen system `count` times; returns how many entities regenerate
+    fn tick(&mut self, count: usize) -> Result<usize, String> {
+        for _ in 0..count {
+            self.regen_system
+                .run(&mut self.world)
+                .map_err(|e| format!("Regen failed: {:?}", e))?;
+        }
+
+        // Regen can refill or drain to zero, so re-derive every caster's flag
+        let casters: Vec<Entity> = Query::new(entity_ids())
+            .with(mana())
+            .borrow(&self.world)
+            .iter()
+            .collect();
+        for entity in casters {
+            self.sync_depleted(entity);
+        }
+
+        Ok(Query::new((mana(), mana_regen()))
+            .borrow(&self.world)
+            .iter()
+            .count())
+    }
+
+    // Sets `depleted` from the entity's current mana; entities without mana are left alone
+    fn sync_depleted(&mut self, entity: Entity) {
+        let is_empty = match self.world.get(entity, mana()) {
+            Ok(mana_val) => mana_val.current <= 0,
+            Err(_) => return,
+        };
+        self.world.set(entity, depleted(), is_empty).ok();
+    }
+
+    fn randomize_health(&mut self, min: i32, max: i32, seed: u64) -> Result<usize, String> {
+        if min > max {
+            return Err(format!("min ({}) must not exceed max ({})", min, max));
+        }
+
+        // Sort by name so the same seed always assigns the same values
+        let mut names: Vec<String> = self.entity_names.keys().cloned().collect();
+        names.sort();
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        for name in &names {
+            self.set_health(name, rng.gen_range(min..=max))?;
+        }
+
+        Ok(names.len())
+    }
+
     fn set_mana(&mut self, name: &str, mana_value: i32) -> Result<(), String> {
+        self.store_mana(name, mana_value, mana_value)
+    }
+
+    // Sets current and maximum separately; current is clamped to [0, maximum].
+    // Returns the current value actually stored.
+    fn set_mana_with_maximum(
+        &mut self,
+        name: &str,
+        current: i32,
+        maximum: i32,
+    ) -> Result<i32, String> {
+        if maximum < 0 {
+            return Err(format!(
+                "Maximum mana must not be negative (got {})",
+                maximum
+            ));
+        }
+
+        let current = current.clamp(0, maximum);
+        self.store_mana(name, current, maximum)?;
+        Ok(current)
+    }
+
+    fn store_mana(&mut self, name: &str, current: i32, maximum: i32) -> Result<(), String> {
         let entity = self.get_entity(name)?;
         let timestamp = self.get_current_time();
 
         // Create a new Mana struct with the entity name
         let mana_component = Mana {
-            current: mana_value,
-            maximum: mana_value,
+            current,
+            maximum,
             entity_name: name.to_string(),
         };
 
         self.world
             .set(entity, mana(), mana_component)
             .map_err(|e| format!("Failed to set mana: {:?}", e))?;
+        self.sync_depleted(entity);
 
         self.world.set(entity, last_modified(), timestamp).ok();
+        self.world.set(entity, mana_modified_at(), timestamp).ok();
 
         Ok(())
     }
 
+    fn spell_cost(&self, spell_name: &str) -> Result<i32, String> {
+        self.world
+            .get(resources(), spellbook())
+            .ok()
+            .and_then(|book| book.get(&spell_name.to_lowercase()).copied())
+            .ok_or_else(|| {
+                format!(
+                    "Spell '{}' has no cost in the spellbook, pass one explicitly",
+                    spell_name
+                )
+            })
+    }
+
+    fn set_spell_cost(&mut self, spell_name: &str, cost: i32) -> Result<(), String> {
+        if cost < 0 {
+            return Err(format!("Spell cost must not be negative (got {})", cost));
+        }
+        let mut book = self
+            .world
+            .get_mut(resources(), spellbook())
+            .map_err(|e| format!("Failed to access spellbook: {:?}", e))?;
+        book.insert(spell_name.to_lowercase(), cost);
+        Ok(())
+    }
+
     fn cast_spell(
         &mut self,
         caster_name: &str,
@@ -612,8 +1732,20 @@ impl ReplState {
         self.world
             .set(entity, mana(), mana_component.clone())
             .map_err(|e| format!("Failed to update mana: {:?}", e))?;
+        self.world
+            .set(entity, depleted(), mana_component.current <= 0)
+            .ok();
 
         self.world.set(entity, last_modified(), timestamp).ok();
+        self.world.set(entity, mana_modified_at(), timestamp).ok();
+
+        // Read-modify-write in place; the first cast adds the counter
+        match self.world.get_mut(entity, spells_cast()) {
+            Ok(mut count) => *count += 1,
+            Err(_) => {
+                self.world.set(entity, spells_cast(), 1).ok();
+            }
+        }
 
         // Print spell casting message
         let spell_effect = match spell_name.to_lowercase().as_str() {
@@ -646,20 +1778,201 @@ impl ReplState {
         Ok(())
     }
 
-    fn add_relation(&mut self, child_name: &str, parent_name: &str) -> Result<(), String> {
+    // Casts at another entity: mana comes from the caster as usual, then the target
+    // takes damage or healing equal to the cost. Nothing changes if the cast fails
+    fn cast_spell_on(
+        &mut self,
+        caster_name: &str,
+        target_name: &str,
+        spell_name: &str,
+        mana_cost: i32,
+    ) -> Result<Option<i32>, String> {
+        let effect = spell_effect(spell_name);
+        if effect == SpellEffect::SelfOnly {
+            if target_name != caster_name {
+                return Err(format!(
+                    "{} can only be cast on the caster, use 'cast {} {}'",
+                    spell_name, spell_name, caster_name
+                ));
+            }
+            // Buffs don't touch health, so the caster needn't have any
+            self.cast_spell(caster_name, spell_name, mana_cost)?;
+            return Ok(None);
+        }
+
+        let target = self.get_entity(target_name)?;
+        if !self.world.has(target, health()) {
+            return Err(format!(
+                "{} has no health for {} to affect",
+                target_name, spell_name
+            ));
+        }
+
+        // Fails on missing or insufficient mana before anything is changed
+        self.cast_spell(caster_name, spell_name, mana_cost)?;
+
+        self.apply_spell(caster_name, spell_name, effect, target_name, mana_cost)
+            .map(Some)
+    }
+
+    // Applies an already-paid spell to one target and prints the combat line
+    fn apply_spell(
+        &mut self,
+        caster_name: &str,
+        spell_name: &str,
+        effect: SpellEffect,
+        target_name: &str,
+        amount: i32,
+    ) -> Result<i32, String> {
+        let target = self.get_entity(target_name)?;
+        let health_before =
+            self.world.get(target, health()).map(|h| *h).map_err(|_| {
+                format!("{} has no health for {} to affect", target_name, spell_name)
+            })?;
+        let max_health_val = self
+            .world
+            .get(target, max_health())
+            .map(|m| *m)
+            .unwrap_or(DEFAULT_MAX_HEALTH);
+
+        let health_after = health_after_spell(effect, health_before, amount, max_health_val);
+
+        match effect {
+            SpellEffect::Damage => println!(
+                "{} {}'s {} hits {} for {} damage ({} → {})",
+                "⚔".red().bold(),
+                caster_name.bright_cyan(),
+                spell_name.bright_yellow(),
+                target_name.bright_cyan(),
+                (health_before - health_after).to_string().red(),
+                health_before,
+                health_after
+            ),
+            SpellEffect::Heal => println!(
+                "{} {}'s {} restores {} health to {} ({} → {})",
+                "✚".green().bold(),
+                caster_name.bright_cyan(),
+                spell_name.bright_yellow(),
+                (health_after - health_before).to_string().green(),
+                target_name.bright_cyan(),
+                health_before,
+                health_after
+            ),
+            SpellEffect::SelfOnly => {}
+        }
+
+        self.set_health(target_name, health_after)?;
+        Ok(health_after)
+    }
+
+    // Every entity below `root` along has_child edges, in depth-first order
+    fn descendants(&self, root: Entity) -> Vec<Entity> {
+        let mut order = Vec::new();
+        let mut visited = HashSet::from([root]);
+        let mut stack = vec![root];
+        let mut query = Query::new(relations_like(has_child));
+        let mut borrow = query.borrow(&self.world);
+        while let Some(entity) = stack.pop() {
+            let Ok(relations) = borrow.get(entity) else {
+                continue;
+            };
+            let children: Vec<Entity> = relations.map(|(child, _)| child).collect();
+            // Reversed so siblings come out in relation order; visited guards against cycles
+            for child in children.into_iter().rev() {
+                if visited.insert(child) {
+                    order.push(child);
+                    stack.push(child);
+                }
+            }
+        }
+        order
+    }
+
+    // Hits every descendant of the caster that has health. The caster pays
+    // `cost_per_target` for each of them in one deduction before any effect lands
+    fn cast_aoe(
+        &mut self,
+        caster_name: &str,
+        spell_name: &str,
+        cost_per_target: i32,
+    ) -> Result<usize, String> {
+        let effect = spell_effect(spell_name);
+        if effect == SpellEffect::SelfOnly {
+            return Err(format!("{} has no area effect", spell_name));
+        }
+
+        let caster = self.get_entity(caster_name)?;
+        let targets: Vec<String> = self
+            .descendants(caster)
+            .into_iter()
+            .filter(|&entity| self.world.has(entity, health()))
+            .filter_map(|entity| {
+                self.world
+                    .get(entity, components::name())
+                    .ok()
+                    .map(|n| n.clone())
+            })
+            .collect();
+        if targets.is_empty() {
+            return Err(format!(
+                "{} has no descendants with health to hit",
+                caster_name
+            ));
+        }
+
+        let total_cost = cost_per_target
+            .checked_mul(targets.len() as i32)
+            .ok_or_else(|| format!("Mana cost overflows for {} targets", targets.len()))?;
+        self.cast_spell(caster_name, spell_name, total_cost)?;
+
+        for target_name in &targets {
+            self.apply_spell(
+                caster_name,
+                spell_name,
+                effect,
+                target_name,
+                cost_per_target,
+            )?;
+        }
+        Ok(targets.len())
+    }
+
+    fn add_relation(
+        &mut self,
+        child_name: &str,
+        parent_name: &str,
+        label: &str,
+    ) -> Result<(), String> {
         let child = self.get_entity(child_name)?;
         let parent = self.get_entity(parent_name)?;
         let timestamp = self.get_current_time();
 
+        // A loop would leave `tree topo` nothing to sort. This edge closes one exactly
+        // when the parent is the child or already somewhere below it
+        if parent == child {
+            return Err(format!("{} can't be a child of itself", child_name));
+        }
+        if traverse_child_of_from(&self.world, child)
+            .iter()
+            .any(|&(entity, _)| entity == parent)
+        {
+            return Err(format!(
+                "{} under {} would create a cycle: {} is already below {}",
+                child_name, parent_name, parent_name, child_name
+            ));
+        }
+
         self.world
             .set(child, components::child_of(parent), ())
             .map_err(|e| format!("Failed to set child_of relation: {:?}", e))?;
 
-        // Create a more interesting relation description
-        let relation_desc = format!("guardian of {}", child_name);
+        let relation_info = RelationInfo {
+            since: timestamp,
+            label: label.to_string(),
+        };
 
         self.world
-            .set(parent, has_child(child), relation_desc)
+            .set(parent, has_child(child), relation_info)
             .map_err(|e| format!("Failed to set has_child relation: {:?}", e))?;
 
         self.world.set(child, last_modified(), timestamp).ok();
@@ -668,6 +1981,38 @@ impl ReplState {
         Ok(())
     }
 
+    // Moves `child` under `new_parent`, dropping whatever child_of relations it had.
+    // The edge label carries over from the old parent. Only the child's own edge
+    // changes, so its descendants come along untouched. Returns the old parents
+    fn reparent(&mut self, child_name: &str, new_parent_name: &str) -> Result<Vec<String>, String> {
+        let child = self.get_entity(child_name)?;
+        let new_parent = self.get_entity(new_parent_name)?;
+        if child == new_parent {
+            return Err(format!("{} can't be its own parent", child_name));
+        }
+        // Checked before anything changes, so a rejected move leaves the tree as it was
+        if self.descendants(child).contains(&new_parent) {
+            return Err(format!(
+                "{} is a descendant of {}, reparenting would create a cycle",
+                new_parent_name, child_name
+            ));
+        }
+
+        let old_parents = self.parent_names(child);
+        let label = old_parents
+            .first()
+            .and_then(|name| self.entity_names.get(name))
+            .and_then(|&parent| self.world.get(parent, has_child(child)).ok())
+            .map(|info| info.label.clone())
+            .unwrap_or_else(|| "child".to_string());
+
+        for old_parent in &old_parents {
+            self.remove_relation(child_name, old_parent)?;
+        }
+        self.add_relation(child_name, new_parent_name, &label)?;
+        Ok(old_parents)
+    }
+
     fn remove_relation(&mut self, child_name: &str, parent_name: &str) -> Result<(), String> {
         let child = self.get_entity(child_name)?;
         let parent = self.get_entity(parent_name)?;
@@ -689,6 +2034,36 @@ impl ReplState {
         Ok(())
     }
 
+    // Entities with children need confirmation unless `force`d, since despawning
+    // the parent silently cuts them loose
+    fn rm_command(&mut self, name: &str, force: bool) {
+        let children = match self.get_entity(name) {
+            Ok(entity) => self.child_relations(entity).len(),
+            Err(e) => {
+                println!("{} {}", "✗".red().bold(), e.red());
+                return;
+            }
+        };
+        if children > 0
+            && !force
+            && !confirm(&format!("Remove {} and its {} children?", name, children))
+        {
+            println!("{}", "Cancelled".bright_black());
+            return;
+        }
+
+        match self.remove_entity(name) {
+            Ok(_) => {
+                println!(
+                    "{} Removed entity '{}'",
+                    "✓".green().bold(),
+                    name.bright_cyan()
+                );
+            }
+            Err(e) => println!("{} {}", "✗".red().bold(), e.red()),
+        }
+    }
+
     fn remove_entity(&mut self, name: &str) -> Result<(), String> {
         let entity = self.get_entity(name)?;
 
@@ -703,15 +2078,399 @@ impl ReplState {
         Ok(())
     }
 
-    fn get_current_time(&self) -> f64 {
-        std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs_f64()
-    }
+    // Returns false if the entity already carried the tag
+    fn tag_entity(&mut self, name: &str, label: &str) -> Result<bool, String> {
+        let entity = self.get_entity(name)?;
+        let timestamp = self.get_current_time();
 
-    fn dump_changes(&mut self, filter: Option<&str>) {
-        let title = match filter {
+        let added = match self.world.get_mut(entity, tags()) {
+            Ok(mut labels) => labels.insert(label.to_string()),
+            Err(_) => {
+                self.world
+                    .set(entity, tags(), HashSet::from([label.to_string()]))
+                    .map_err(|e| format!("Failed to set tags: {:?}", e))?;
+                true
+            }
+        };
+
+        if added {
+            self.world.set(entity, last_modified(), timestamp).ok();
+        }
+        Ok(added)
+    }
+
+    // Returns false if the entity didn't carry the tag
+    fn untag_entity(&mut self, name: &str, label: &str) -> Result<bool, String> {
+        let entity = self.get_entity(name)?;
+        let timestamp = self.get_current_time();
+
+        let (removed, now_empty) = match self.world.get_mut(entity, tags()) {
+            Ok(mut labels) => (labels.remove(label), labels.is_empty()),
+            Err(_) => (false, false),
+        };
+        // Drop the component with its last tag so untagged entities share an archetype again
+        if now_empty {
+            self.world.remove(entity, tags()).ok();
+        }
+
+        if removed {
+            self.world.set(entity, last_modified(), timestamp).ok();
+        }
+        Ok(removed)
+    }
+
+    fn find_by_tag(&self, label: &str) -> Vec<(String, Entity)> {
+        let mut found: Vec<(String, Entity)> =
+            Query::new((entity_ids(), components::name(), tags()))
+                .borrow(&self.world)
+                .iter()
+                .filter(|(_, _, labels)| labels.contains(label))
+                .map(|(entity, name, _)| (name.clone(), entity))
+                .collect();
+        found.sort();
+        found
+    }
+
+    // has_child edges whose label contains `substring`, as (parent, child, label),
+    // sorted by parent then child
+    fn find_by_relation_label(&self, substring: &str) -> Vec<(String, String, String)> {
+        let mut found = Vec::new();
+        Query::new((entity_ids(), relations_like(has_child)))
+            .borrow(&self.world)
+            .for_each(|(parent, relations)| {
+                for (child, info) in relations {
+                    let info: &RelationInfo = info;
+                    if info.label.contains(substring) {
+                        found.push((
+                            self.name_or_id(parent),
+                            self.name_or_id(child),
+                            info.label.clone(),
+                        ));
+                    }
+                }
+            });
+        found.sort();
+        found
+    }
+
+    // Inclusive range, lowest health first
+    fn find_by_health(&self, min: i32, max: i32) -> Vec<(i32, String)> {
+        let mut found: Vec<(i32, String)> = Query::new((components::name(), health()))
+            .borrow(&self.world)
+            .iter()
+            .filter(|(_, health_val)| (min..=max).contains(*health_val))
+            .map(|(name, health_val)| (*health_val, name.clone()))
+            .collect();
+        found.sort();
+        found
+    }
+
+    // Every named entity carrying the field, best first (or worst first with
+    // `ascending`); equal values are ordered by name either way
+    fn leaderboard(&self, field: &str, ascending: bool) -> Result<Vec<(i64, String)>, String> {
+        let mut ranked: Vec<(i64, String)> = match field {
+            "health" => Query::new((components::name(), health()))
+                .borrow(&self.world)
+                .iter()
+                .map(|(name, value)| (*value as i64, name.clone()))
+                .collect(),
+            "mana" => Query::new((components::name(), mana()))
+                .borrow(&self.world)
+                .iter()
+                .map(|(name, value)| (value.current as i64, name.clone()))
+                .collect(),
+            "spells_cast" => Query::new((components::name(), spells_cast()))
+                .borrow(&self.world)
+                .iter()
+                .map(|(name, count)| (*count as i64, name.clone()))
+                .collect(),
+            "descendants" => Query::new((entity_ids(), components::name()))
+                .borrow(&self.world)
+                .iter()
+                .map(|(entity, name)| (self.descendants(entity).len() as i64, name.clone()))
+                .collect(),
+            _ => {
+                return Err(format!(
+                    "Unknown field '{}', expected one of: {}",
+                    field,
+                    LEADERBOARD_FIELDS.join(", ")
+                ));
+            }
+        };
+
+        ranked.sort_by(|a, b| {
+            let by_value = if ascending {
+                a.0.cmp(&b.0)
+            } else {
+                b.0.cmp(&a.0)
+            };
+            by_value.then_with(|| a.1.cmp(&b.1))
+        });
+        Ok(ranked)
+    }
+
+    fn show_leaderboard(&self, field: &str, count: usize, ascending: bool) {
+        let ranked = match self.leaderboard(field, ascending) {
+            Ok(ranked) => ranked,
+            Err(e) => {
+                if self.output == OutputMode::Json {
+                    print_json(&json!({ "error": e }));
+                } else {
+                    println!("{} {}", "✗".red().bold(), e.red());
+                }
+                return;
+            }
+        };
+        let ranked = ranked.into_iter().take(count);
+
+        if self.output == OutputMode::Json {
+            let rows: Vec<_> = ranked
+                .enumerate()
+                .map(|(i, (value, name))| json!({ "rank": i + 1, "name": name, field: value }))
+                .collect();
+            print_json(&rows);
+            return;
+        }
+
+        let direction = if ascending { "Bottom" } else { "Top" };
+        println!(
+            "{}",
+            format!("🏆 {} {} by {}:", direction, count, field)
+                .cyan()
+                .bold()
+        );
+        let thresholds = self.thresholds();
+        let mut any = false;
+        for (i, (value, name)) in ranked.enumerate() {
+            any = true;
+            let place = match i {
+                0 => "🥇".to_string(),
+                1 => "🥈".to_string(),
+                2 => "🥉".to_string(),
+                _ => format!("{:>2}.", i + 1).bright_black().to_string(),
+            };
+            let value_str = if field == "health" {
+                health_color(value as i32, &thresholds).to_string()
+            } else {
+                value.to_string().bright_white().to_string()
+            };
+            println!("  {} {} {}", place, name.bright_cyan(), value_str);
+        }
+        if !any {
+            println!("    {}", format!("No entities have {}", field).yellow());
+        }
+    }
+
+    // Named entities by last_modified, newest first; entities never stamped go last.
+    // Ties are ordered by name.
+    fn recent_activity(&self) -> Vec<(String, Option<f64>)> {
+        let mut entries: Vec<(String, Option<f64>)> =
+            Query::new((components::name(), last_modified().opt()))
+                .borrow(&self.world)
+                .iter()
+                .map(|(name, modified_at)| (name.clone(), modified_at.copied()))
+                .collect();
+        entries.sort_by(|a, b| match (a.1, b.1) {
+            (Some(x), Some(y)) => y.total_cmp(&x).then_with(|| a.0.cmp(&b.0)),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => a.0.cmp(&b.0),
+        });
+        entries
+    }
+
+    fn show_recent(&self, count: usize) {
+        let now = self.get_current_time();
+        let entries = self.recent_activity().into_iter().take(count);
+
+        if self.output == OutputMode::Json {
+            let rows: Vec<_> = entries
+                .map(|(name, modified_at)| {
+                    json!({
+                        "name": name,
+                        "last_modified": modified_at,
+                        "age_secs": modified_at.map(|at| (now - at).max(0.0)),
+                    })
+                })
+                .collect();
+            print_json(&rows);
+            return;
+        }
+
+        println!("{}", "🕒 Recent activity:".cyan().bold());
+        let mut any = false;
+        for (name, modified_at) in entries {
+            any = true;
+            let age = match modified_at {
+                Some(at) => format_age(now, at).bright_white(),
+                None => "never modified".bright_black().italic(),
+            };
+            println!("  {} {} {}", "•".bright_blue(), name.bright_cyan(), age);
+        }
+        if !any {
+            println!("    {}", "No entities created yet".yellow());
+        }
+    }
+
+    // Casters whose mana is at zero, by name
+    fn find_depleted(&self) -> Vec<String> {
+        let mut found: Vec<String> = Query::new((components::name(), depleted()))
+            .borrow(&self.world)
+            .iter()
+            .filter(|(_, is_empty)| **is_empty)
+            .map(|(name, _)| name.clone())
+            .collect();
+        found.sort();
+        found
+    }
+
+    // Most active casters first, ties by name
+    fn find_by_cast_count(&self, min: u32) -> Vec<(u32, String)> {
+        let mut found: Vec<(u32, String)> = Query::new((components::name(), spells_cast()))
+            .borrow(&self.world)
+            .iter()
+            .filter(|(_, count)| **count >= min)
+            .map(|(name, count)| (*count, name.clone()))
+            .collect();
+        found.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+        found
+    }
+
+    // Tags in a stable order for display
+    fn sorted_tags(&self, entity: Entity) -> Vec<String> {
+        let mut labels: Vec<String> = self
+            .world
+            .get(entity, tags())
+            .map(|labels| labels.iter().cloned().collect())
+            .unwrap_or_default();
+        labels.sort();
+        labels
+    }
+
+    fn take_checkpoint(&self) -> Checkpoint {
+        let mut names: Vec<(&String, &Entity)> = self.entity_names.iter().collect();
+        names.sort();
+
+        let entities = names
+            .into_iter()
+            .map(|(name, &entity)| {
+                let parents = Query::new(relations_like(components::child_of))
+                    .borrow(&self.world)
+                    .get(entity)
+                    .map(|relations| {
+                        relations
+                            .filter_map(|(parent, _)| {
+                                let parent_name =
+                                    self.world.get(parent, components::name()).ok()?.clone();
+                                let info = self
+                                    .world
+                                    .get(parent, has_child(entity))
+                                    .map(|info| info.clone())
+                                    .unwrap_or_else(|_| RelationInfo {
+                                        since: self.get_current_time(),
+                                        label: "child".to_string(),
+                                    });
+                                Some((parent_name, info))
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+
+                EntityRecord {
+                    name: name.clone(),
+                    health: self.world.get(entity, health()).ok().map(|h| *h),
+                    max_health: self.world.get(entity, max_health()).ok().map(|m| *m),
+                    mana: self
+                        .world
+                        .get(entity, mana())
+                        .ok()
+                        .map(|m| (m.current, m.maximum)),
+                    mana_regen: self.world.get(entity, mana_regen()).ok().map(|r| *r),
+                    spells_cast: self.world.get(entity, spells_cast()).ok().map(|c| *c),
+                    tags: self.world.get(entity, tags()).ok().map(|t| t.clone()),
+                    floats: self.world.get(entity, floats()).ok().map(|f| f.clone()),
+                    parents,
+                }
+            })
+            .collect();
+
+        Checkpoint {
+            entities,
+            spellbook: self
+                .world
+                .get(resources(), spellbook())
+                .map(|book| book.clone())
+                .unwrap_or_default(),
+        }
+    }
+
+    // Replaces every named entity with the checkpoint's. Entity ids change, so
+    // entity_names is rebuilt and relations are re-linked by name in a second pass
+    fn restore_checkpoint(&mut self, checkpoint: &Checkpoint) -> Result<usize, String> {
+        // Despawning drops the old Mana, which announces it like `rm` does
+        for (_, entity) in self.entity_names.drain() {
+            self.world.despawn(entity).ok();
+        }
+        // Snapshot entries refer to the despawned ids
+        self.health_snapshot.lock().unwrap().clear();
+
+        for record in &checkpoint.entities {
+            let entity = self.add_entity(&record.name)?;
+            if let Some(health_val) = record.health {
+                self.world.set(entity, health(), health_val).ok();
+            }
+            if let Some(maximum) = record.max_health {
+                self.world.set(entity, max_health(), maximum).ok();
+            }
+            if let Some((current, maximum)) = record.mana {
+                let mana_component = Mana {
+                    current,
+                    maximum,
+                    entity_name: record.name.clone(),
+                };
+                self.world.set(entity, mana(), mana_component).ok();
+                self.sync_depleted(entity);
+            }
+            if let Some(rate) = record.mana_regen {
+                self.world.set(entity, mana_regen(), rate).ok();
+            }
+            if let Some(count) = record.spells_cast {
+                self.world.set(entity, spells_cast(), count).ok();
+            }
+            if let Some(labels) = &record.tags {
+                self.world.set(entity, tags(), labels.clone()).ok();
+            }
+            if let Some(values) = &record.floats {
+                self.world.set(entity, floats(), values.clone()).ok();
+            }
+        }
+
+        for record in &checkpoint.entities {
+            let child = self.get_entity(&record.name)?;
+            for (parent_name, info) in &record.parents {
+                let parent = self.get_entity(parent_name)?;
+                self.world
+                    .set(child, components::child_of(parent), ())
+                    .map_err(|e| format!("Failed to set child_of relation: {:?}", e))?;
+                self.world
+                    .set(parent, has_child(child), info.clone())
+                    .map_err(|e| format!("Failed to set has_child relation: {:?}", e))?;
+            }
+        }
+
+        self.world
+            .set(resources(), spellbook(), checkpoint.spellbook.clone())
+            .ok();
+
+        Ok(checkpoint.entities.len())
+    }
+
+    fn get_current_time(&self) -> f64 {
+        current_time()
+    }
+
+    fn dump_changes(&mut self, filter: Option<&str>) {
+        let title = match filter {
             Some("added") => "=== Added Components ===".green().bold(),
             Some("modified") => "=== Modified Components ===".blue().bold(),
             Some("removed") => "=== Removed Components ===".red().bold(),
@@ -720,155 +2479,226 @@ impl ReplState {
 
         println!("\n{}", title);
 
-        match filter {
-            Some("added") => {
-                self.added_system.run(&mut self.world).unwrap();
-            }
-            Some("modified") => {
-                self.modified_system.run(&mut self.world).unwrap();
-            }
-            Some("removed") => {
-                self.removed_system.run(&mut self.world).unwrap();
-            }
-            _ => {
-                self.show_relations();
-            }
+        let selected = match filter {
+            Some("added") => Some(ADDED_SYSTEM),
+            Some("modified") => Some(MODIFIED_SYSTEM),
+            Some("removed") => Some(REMOVED_SYSTEM),
+            _ => None,
+        };
+        self.run_change_pass(ChangePass {
+            system: selected,
+            quiet: false,
+        });
+
+        if selected.is_none() {
+            page_lines(&self.relation_lines());
         }
 
         println!("{}\n", "========================".bright_black());
+
+        self.snapshot_health();
+    }
+
+    // Acknowledges every pending change: each change query is borrowed and drained
+    // without printing, which moves its tracking cursor to now. The next `dump`
+    // then only reports changes made after this point
+    fn mark_seen(&mut self) {
+        self.run_change_pass(ChangePass {
+            system: None,
+            quiet: true,
+        });
+        self.snapshot_health();
+    }
+
+    fn run_change_pass(&mut self, pass: ChangePass) {
+        *self.change_selection.lock().unwrap() = pass;
+        self.change_schedule.execute_seq(&mut self.world).unwrap();
     }
 
-    fn show_relations(&self) {
-        // Show relations for entities that were modified via last_modified changes
-        Query::new((entity_ids(), components::name()))
+    fn snapshot_health(&mut self) {
+        // Record current health values so the next `dump modified` can show old -> new
+        let snapshot: HashMap<Entity, i32> = Query::new((entity_ids(), health()))
             .borrow(&self.world)
-            .for_each(|(entity, name)| {
-                // First print the entity
-                println!(
-                    "  {} {} ({})",
-                    "Entity".white(),
-                    name.bright_cyan(),
-                    format!("{:?}", entity).bright_magenta()
-                );
-                // Then show its relations
-                self.display_entity_relations(entity);
-            });
-        
+            .iter()
+            .map(|(entity, health_val)| (entity, *health_val))
+            .collect();
+
+        *self.health_snapshot.lock().unwrap() = snapshot;
+    }
+
+    fn relation_lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+
+        // Sorted by name so the output is stable between runs and easy to diff
+        let mut entities: Vec<(Entity, String)> = Query::new((entity_ids(), components::name()))
+            .borrow(&self.world)
+            .iter()
+            .map(|(entity, name)| (entity, name.clone()))
+            .collect();
+        entities.sort_by(|a, b| a.1.cmp(&b.1));
+
+        for (entity, name) in entities {
+            // First the entity
+            lines.push(format!(
+                "  {} {} ({})",
+                "Entity".white(),
+                name.bright_cyan(),
+                format!("{:?}", entity).bright_magenta()
+            ));
+            // Then its relations
+            self.entity_relation_lines(entity, &mut lines);
+        }
+
         // Show entities without any relationships using without_relation
-        println!();
-        println!("{}", "  Entities without relationships:".bright_black().bold());
-        
+        lines.push(String::new());
+        lines.push(format!(
+            "{}",
+            "  Entities without relationships:".bright_black().bold()
+        ));
+
         let mut orphan_query = Query::new((entity_ids(), components::name()))
             .without_relation(components::child_of)
             .without_relation(has_child);
-            
+
         let mut query_borrow = orphan_query.borrow(&self.world);
-        let orphaned_entities: Vec<_> = query_borrow.iter().collect();
-            
+        let mut orphaned_entities: Vec<_> = query_borrow.iter().collect();
+        orphaned_entities.sort_by_key(|&(_, name)| name);
+
         if orphaned_entities.is_empty() {
-            println!("{}", "    (All entities have relationships)".bright_black().italic());
+            lines.push(format!(
+                "{}",
+                "    (All entities have relationships)"
+                    .bright_black()
+                    .italic()
+            ));
         } else {
             for (entity, name) in orphaned_entities {
-                println!(
+                lines.push(format!(
                     "    {} {} ({}) - {}",
                     format!("{}.", entity.index()).bright_black(),
                     name.bright_white(),
                     format!("{:?}", entity).bright_magenta(),
                     "standalone entity".bright_black().italic()
-                );
+                ));
             }
         }
+
+        lines
+    }
+
+    // Name of a relation target, falling back to its id for unnamed entities
+    fn name_or_id(&self, entity: Entity) -> String {
+        self.world
+            .get(entity, components::name())
+            .map(|n| n.clone())
+            .unwrap_or_else(|_| format!("{:?}", entity))
     }
 
-    fn display_entity_relations(&self, entity: Entity) {
-        // Show parent relationships
-        if let Ok(child_of_relations) = Query::new(relations_like(components::child_of))
-            .with_relation(components::child_of)
+    // Names of the entity's child_of targets, in relation order
+    fn parent_names(&self, entity: Entity) -> Vec<String> {
+        Query::new(relations_like(components::child_of))
             .borrow(&self.world)
             .get(entity)
-        {
-            let parents: Vec<String> = child_of_relations
-                .map(|(parent, _)| {
-                    self.world
-                        .get(parent, components::name())
-                        .map(|n| n.clone())
-                        .unwrap_or_else(|_| format!("{:?}", parent))
-                })
-                .collect();
-
-            if !parents.is_empty() {
-                println!(
-                    "      {} {}",
-                    "Parents:".bright_black(),
-                    parents.join(", ").bright_yellow()
-                );
-            }
-        }
+            .map(|relations| {
+                relations
+                    .map(|(parent, _)| self.name_or_id(parent))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
 
-        // Show child relationships
-        if let Ok(has_child_relations) = Query::new(relations_like(has_child))
+    // Names of the entity's has_child targets with the data on each edge, in relation order
+    fn child_relations(&self, entity: Entity) -> Vec<(String, RelationInfo)> {
+        Query::new(relations_like(has_child))
             .borrow(&self.world)
             .get(entity)
-        {
-            let children: Vec<String> = has_child_relations
-                .map(|(child, rel_data): (Entity, &String)| {
-                    let child_name = self
-                        .world
-                        .get(child, components::name())
-                        .map(|n| n.clone())
-                        .unwrap_or_else(|_| format!("{:?}", child));
-                    format!("{} ({})", child_name, rel_data)
-                })
-                .collect();
+            .map(|relations| {
+                relations
+                    .map(|(child, info): (Entity, &RelationInfo)| {
+                        (self.name_or_id(child), info.clone())
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
 
-            if !children.is_empty() {
-                println!(
-                    "      {} {}",
-                    "Children:".bright_black(),
-                    children.join(", ").bright_green()
-                );
-            }
+    fn child_names_with_labels(&self, entity: Entity) -> Vec<(String, String)> {
+        self.child_relations(entity)
+            .into_iter()
+            .map(|(name, info)| (name, info.label))
+            .collect()
+    }
+
+    // Parents and children are listed by name rather than relation order
+    fn entity_relation_lines(&self, entity: Entity, lines: &mut Vec<String>) {
+        let mut parents = self.parent_names(entity);
+        parents.sort();
+        if !parents.is_empty() {
+            lines.push(format!(
+                "      {} {}",
+                "Parents:".bright_black(),
+                parents.join(", ").bright_yellow()
+            ));
+        }
+
+        let now = self.get_current_time();
+        let mut child_relations = self.child_relations(entity);
+        child_relations.sort_by(|a, b| a.0.cmp(&b.0));
+        let children: Vec<String> = child_relations
+            .into_iter()
+            .map(|(child_name, info)| format!("{} ({})", child_name, info.describe(now)))
+            .collect();
+        if !children.is_empty() {
+            lines.push(format!(
+                "      {} {}",
+                "Children:".bright_black(),
+                children.join(", ").bright_green()
+            ));
         }
     }
 
     fn get_entity_info(&self, name: &str) -> Result<String, String> {
-        let entity = self.get_entity(name)?;
+        let report = self.entity_report(name)?;
+        let summary = &report.summary;
 
         let mut info = String::new();
         info.push_str(&format!(
             "{} {} ({})\n",
             "Entity:".white().bold(),
-            name.bright_cyan().bold(),
-            format!("{:?}", entity).bright_magenta()
+            report.name.bright_cyan().bold(),
+            report.id.bright_magenta()
+        ));
+        info.push_str(&format!(
+            "  {} {}\n",
+            "Slot:".bright_black(),
+            report.slot.bright_yellow()
         ));
 
-        if let Ok(health_val) = self.world.get(entity, health()) {
-            let health_color = if *health_val > 75 {
-                format!("{}", *health_val).green()
-            } else if *health_val > 30 {
-                format!("{}", *health_val).yellow()
-            } else {
-                format!("{}", *health_val).red()
-            };
+        if let Some(health_val) = summary.health {
+            let health_color = health_color(health_val, &self.thresholds());
+            let maximum = summary.max_health.unwrap_or(DEFAULT_MAX_HEALTH);
+            let (health_bar, empty_bar) = bar_segments(fill_percentage(health_val, maximum));
             info.push_str(&format!(
-                "  {} {}\n",
+                "  {} {}/{} [{}{}]\n",
                 "Health:".bright_black(),
-                health_color
+                health_color,
+                maximum,
+                health_bar.green(),
+                empty_bar.bright_black()
             ));
         }
 
-        if let Ok(mana_val) = self.world.get(entity, mana()) {
-            let mana_percentage =
-                (mana_val.current as f32 / mana_val.maximum as f32 * 100.0) as i32;
+        if let Some(ManaLevel { current, maximum }) = summary.mana {
+            // Guard against a zero maximum and keep the bar within its 10 cells
+            let mana_percentage = fill_percentage(current, maximum);
             let mana_color = if mana_percentage > 75 {
-                format!("{}/{}", mana_val.current, mana_val.maximum).bright_blue()
+                format!("{}/{}", current, maximum).bright_blue()
             } else if mana_percentage > 25 {
-                format!("{}/{}", mana_val.current, mana_val.maximum).blue()
+                format!("{}/{}", current, maximum).blue()
             } else {
-                format!("{}/{}", mana_val.current, mana_val.maximum).bright_magenta()
+                format!("{}/{}", current, maximum).bright_magenta()
             };
-            let mana_bar = "█".repeat((mana_percentage / 10).max(0) as usize);
-            let empty_bar = "░".repeat(10 - (mana_percentage / 10).max(0) as usize);
+            let (mana_bar, empty_bar) = bar_segments(mana_percentage);
             info.push_str(&format!(
                 "  {} {} [{}{}]\n",
                 "Mana:".bright_black(),
@@ -878,127 +2708,1765 @@ impl ReplState {
             ));
         }
 
-        if let Ok(child_of_relations) = Query::new(relations_like(components::child_of))
-            .with_relation(components::child_of)
-            .borrow(&self.world)
-            .get(entity)
-        {
-            let parents: Vec<String> = child_of_relations
-                .map(|(parent, _)| {
-                    self.world
-                        .get(parent, components::name())
-                        .map(|n| n.clone())
-                        .unwrap_or_else(|_| format!("{:?}", parent))
-                })
-                .collect();
+        // Shown whenever it matters: with mana (defaulting to 0) or when explicitly set
+        if summary.mana_regen.is_some() || summary.mana.is_some() {
+            info.push_str(&format!(
+                "  {} {}\n",
+                "Regen:".bright_black(),
+                format!("{:+}/tick", summary.mana_regen.unwrap_or(0)).bright_blue()
+            ));
+        }
 
-            if !parents.is_empty() {
-                info.push_str(&format!(
-                    "  {} {}\n",
-                    "Parents:".bright_black(),
-                    parents.join(", ").bright_yellow()
-                ));
-            }
+        if let Some(count) = summary.spells_cast {
+            info.push_str(&format!(
+                "  {} {}\n",
+                "Spells cast:".bright_black(),
+                count.to_string().bright_yellow()
+            ));
         }
 
-        if let Ok(has_child_relations) = Query::new(relations_like(has_child))
-            .borrow(&self.world)
-            .get(entity)
-        {
-            let children: Vec<String> = has_child_relations
-                .map(|(child, rel_data): (Entity, &String)| {
-                    let child_name = self
-                        .world
-                        .get(child, components::name())
-                        .map(|n| n.clone())
-                        .unwrap_or_else(|_| format!("{:?}", child));
-                    format!("{} ({})", child_name, rel_data)
-                })
+        if !summary.parents.is_empty() {
+            info.push_str(&format!(
+                "  {} {}\n",
+                "Parents:".bright_black(),
+                summary.parents.join(", ").bright_yellow()
+            ));
+        }
+
+        if !summary.children.is_empty() {
+            let now = self.get_current_time();
+            let children: Vec<String> = summary
+                .children
+                .iter()
+                .map(|child| format!("{} ({})", child.name, child.relation.describe(now)))
                 .collect();
+            info.push_str(&format!(
+                "  {} {}\n",
+                "Children:".bright_black(),
+                children.join(", ").bright_green()
+            ));
+        }
 
-            if !children.is_empty() {
-                info.push_str(&format!(
-                    "  {} {}\n",
-                    "Children:".bright_black(),
-                    children.join(", ").bright_green()
-                ));
-            }
+        if !summary.tags.is_empty() {
+            info.push_str(&format!(
+                "  {} {}\n",
+                "Tags:".bright_black(),
+                summary.tags.join(", ").bright_white()
+            ));
+        }
+
+        if !summary.floats.is_empty() {
+            let values: Vec<String> = summary
+                .floats
+                .iter()
+                .map(|(key, value)| format!("{} = {:.*}", key, FLOAT_PRECISION, value))
+                .collect();
+            info.push_str(&format!(
+                "  {} {}\n",
+                "Floats:".bright_black(),
+                values.join(", ").bright_white()
+            ));
         }
 
         Ok(info)
     }
 
-    fn show_tree(&self, mode: &str) {
-        println!(
-            "\n{}",
-            format!("=== {} Tree View ===", mode.to_uppercase())
-                .cyan()
-                .bold()
-        );
+    // The values `get` shows, minus presentation
+    fn entity_summary(&self, entity: Entity) -> EntitySummary {
+        let mut summary = EntitySummary {
+            health: self.world.get(entity, health()).ok().map(|h| *h),
+            max_health: self.world.get(entity, max_health()).ok().map(|m| *m),
+            mana: self.world.get(entity, mana()).ok().map(|m| ManaLevel {
+                current: m.current,
+                maximum: m.maximum,
+            }),
+            mana_regen: self.world.get(entity, mana_regen()).ok().map(|r| *r),
+            spells_cast: self.world.get(entity, spells_cast()).ok().map(|c| *c),
+            tags: self.sorted_tags(entity),
+            floats: self
+                .world
+                .get(entity, floats())
+                .map(|values| values.iter().map(|(k, v)| (k.clone(), *v)).collect())
+                .unwrap_or_default(),
+            parents: self.parent_names(entity),
+            children: self
+                .child_relations(entity)
+                .into_iter()
+                .map(|(name, relation)| ChildEntry { name, relation })
+                .collect(),
+        };
 
-        match mode {
-            "dfs" => self.show_dfs_tree(),
-            "topo" => self.show_topo_tree(),
-            _ => println!("{}", "Invalid tree mode. Use 'dfs' or 'topo'".red()),
-        }
+        // Relation order follows storage, so sort to compare by content
+        summary.parents.sort();
+        summary.children.sort_by(|a, b| a.name.cmp(&b.name));
+        summary
+    }
 
-        println!("{}\n", "========================".bright_black());
+    fn entity_report(&self, name: &str) -> Result<EntityReport, String> {
+        let entity = self.get_entity(name)?;
+        Ok(EntityReport {
+            name: name.to_string(),
+            id: format!("{:?}", entity),
+            slot: entity_slot(entity),
+            summary: self.entity_summary(entity),
+        })
     }
 
-    fn show_dfs_tree(&self) {
-        // Use Flax's built-in DFS traversal
-        let mut query = Query::new((entity_ids(), components::name()))
-            .with_strategy(Dfs::new(components::child_of));
+    fn diff_entities(&self, left_name: &str, right_name: &str) -> Result<String, String> {
+        let left = self.entity_summary(self.get_entity(left_name)?);
+        let right = self.entity_summary(self.get_entity(right_name)?);
 
-        println!("{}", "DFS Traversal (depth-first search):".green().bold());
+        let missing = || "—".to_string();
+        let list = |names: &[String]| {
+            if names.is_empty() {
+                missing()
+            } else {
+                names.join(", ")
+            }
+        };
+        let rows = [
+            (
+                "Health",
+                left.health.map_or_else(missing, |h| h.to_string()),
+                right.health.map_or_else(missing, |h| h.to_string()),
+            ),
+            (
+                "Mana",
+                left.mana
+                    .map_or_else(missing, |m| format!("{}/{}", m.current, m.maximum)),
+                right
+                    .mana
+                    .map_or_else(missing, |m| format!("{}/{}", m.current, m.maximum)),
+            ),
+            ("Parents", list(&left.parents), list(&right.parents)),
+            (
+                "Children",
+                list(&child_names(&left.children)),
+                list(&child_names(&right.children)),
+            ),
+        ];
 
-        for (entity, name) in query.borrow(&self.world).iter() {
-            // Calculate depth by tracking parent chain
-            let mut depth = 0;
-            let mut current = entity;
+        let width = rows
+            .iter()
+            .map(|(_, value, _)| value.chars().count())
+            .chain([left_name.chars().count()])
+            .max()
+            .unwrap_or(0);
 
-            while let Ok(mut child_of_relations) = Query::new(relations_like(components::child_of))
-                .with_relation(components::child_of)
-                .borrow(&self.world)
-                .get(current)
-            {
-                if let Some((parent, _)) = child_of_relations.next() {
-                    depth += 1;
-                    current = parent;
-                } else {
-                    break;
+        let mut out = format!(
+            "{:<10} {}   {}\n",
+            "",
+            format!("{:<width$}", left_name, width = width)
+                .bright_cyan()
+                .bold(),
+            right_name.bright_cyan().bold()
+        );
+        for (label, left_value, right_value) in rows {
+            // Pad before colouring so escape codes don't throw off the columns
+            let padded = format!("{:<width$}", left_value, width = width);
+            let (left_value, right_value) = if left_value == right_value {
+                (padded.normal(), right_value.normal())
+            } else {
+                (padded.yellow(), right_value.yellow())
+            };
+            out.push_str(&format!(
+                "{} {}   {}\n",
+                format!("{:<10}", format!("{}:", label)).bright_black(),
+                left_value,
+                right_value
+            ));
+        }
+        Ok(out)
+    }
+
+    fn thresholds(&self) -> HealthThresholds {
+        *self.health_thresholds.lock().unwrap()
+    }
+
+    fn set_thresholds(&mut self, good: i32, warn: i32) -> Result<(), String> {
+        if good <= warn {
+            return Err(format!(
+                "good ({}) must be greater than warn ({})",
+                good, warn
+            ));
+        }
+        *self.health_thresholds.lock().unwrap() = HealthThresholds { good, warn };
+        Ok(())
+    }
+
+    // Re-runs the watched command, if any; called by the main loop after every command
+    fn run_watch(&mut self) {
+        if let Some(watch) = self.watch.clone() {
+            println!("{}", format!("👁 watch: {}", watch).bright_black());
+            self.execute_line(&watch);
+        }
+    }
+
+    // Replaces the first token if it is an alias; later tokens are never rewritten,
+    // and the expansion itself is not expanded again
+    fn expand_alias(&self, mut tokens: Vec<String>) -> Vec<String> {
+        let Some(expansion) = tokens.first().and_then(|first| self.aliases.get(first)) else {
+            return tokens;
+        };
+        // Expansions were tokenized when defined, so this can't fail
+        let mut expanded = tokenize(expansion).unwrap_or_default();
+        expanded.extend(tokens.drain(1..));
+        expanded
+    }
+
+    fn define_alias(&mut self, alias: &str, expansion: &[&str]) -> Result<(), String> {
+        if alias == "alias" {
+            return Err("'alias' itself can't be redefined".to_string());
+        }
+        let expansion: Vec<String> = expansion.iter().map(|token| quote(token)).collect();
+        self.aliases.insert(alias.to_string(), expansion.join(" "));
+        Ok(())
+    }
+
+    // One `alias expansion...` per line; unreadable files just mean no saved aliases
+    fn load_aliases(&mut self, path: &Path) {
+        let Ok(contents) = std::fs::read_to_string(path) else {
+            return;
+        };
+        for line in contents.lines() {
+            let Ok(tokens) = tokenize(line) else {
+                continue;
+            };
+            if let [alias, expansion @ ..] = tokens.as_slice() {
+                if !expansion.is_empty() {
+                    let expansion: Vec<&str> = expansion.iter().map(String::as_str).collect();
+                    self.define_alias(alias, &expansion).ok();
+                }
+            }
+        }
+    }
+
+    fn save_aliases(&self, path: &Path) -> std::io::Result<()> {
+        let mut aliases: Vec<(&String, &String)> = self.aliases.iter().collect();
+        aliases.sort();
+        let contents: String = aliases
+            .into_iter()
+            .map(|(alias, expansion)| format!("{} {}\n", quote(alias), expansion))
+            .collect();
+        std::fs::write(path, contents)
+    }
+
+    // Runs one REPL command, timing it when `time on` is active
+    fn execute_line(&mut self, input: &str) {
+        let tokens = match tokenize(input) {
+            Ok(tokens) => self.expand_alias(tokens),
+            Err(e) => {
+                println!("{} {}", "✗".red().bold(), e.red());
+                return;
+            }
+        };
+        // Patterns match on string slices, so borrow the owned tokens as &str
+        let parts: Vec<&str> = tokens.iter().map(String::as_str).collect();
+
+        let start = Instant::now();
+        let tick_before = self.world.change_tick();
+        self.dispatch(input, &parts);
+        if self.audit_log.is_some() && should_log(&parts, self.world.change_tick() != tick_before) {
+            self.append_to_audit_log(&parts);
+        }
+        if self.timing {
+            println!(
+                "{}",
+                format!("⏱ {} µs", start.elapsed().as_micros()).bright_black()
+            );
+        }
+    }
+
+    fn open_audit_log(&mut self, path: &Path) -> std::io::Result<()> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        self.audit_log = Some(file);
+        Ok(())
+    }
+
+    // Writes the alias-expanded command so the log replays without the aliases.
+    // An `rm` that got this far was confirmed, so it is logged with -f to replay
+    // without asking again
+    fn append_to_audit_log(&mut self, parts: &[&str]) {
+        let mut tokens: Vec<String> = parts.iter().map(|token| quote(token)).collect();
+        if let ["rm", name] = parts {
+            tokens = vec!["rm".to_string(), "-f".to_string(), quote(name)];
+        }
+        let Some(log) = self.audit_log.as_mut() else {
+            return;
+        };
+        if let Err(err) = writeln!(log, "{}", tokens.join(" ")) {
+            println!(
+                "{} Failed to write audit log, logging disabled: {}",
+                "⚠".yellow().bold(),
+                err
+            );
+            self.audit_log = None;
+        }
+    }
+
+    // Runs every line of a script through execute_line, skipping blanks and comments
+    fn source_file(&mut self, path: &str) -> Result<usize, String> {
+        if self.source_depth >= MAX_SOURCE_DEPTH {
+            return Err(format!(
+                "source nested more than {} deep, stopping at '{}'",
+                MAX_SOURCE_DEPTH, path
+            ));
+        }
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read '{}': {}", path, e))?;
+
+        self.source_depth += 1;
+        let mut executed = 0;
+        for line in contents.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            println!("{}", format!("► {}", line).bright_black());
+            self.execute_line(line);
+            executed += 1;
+        }
+        self.source_depth -= 1;
+        Ok(executed)
+    }
+
+    // Entity names matching a `*`/`?` pattern, sorted. An entity literally named
+    // like the pattern wins, so such names stay addressable
+    fn resolve_pattern(&self, pat: &str) -> Vec<String> {
+        if self.entity_names.contains_key(pat) || !is_glob(pat) {
+            return vec![pat.to_string()];
+        }
+        let mut names: Vec<String> = self
+            .entity_names
+            .keys()
+            .filter(|name| glob_match(pat, name))
+            .cloned()
+            .collect();
+        names.sort();
+        names
+    }
+
+    // `get`, `rm`, `set health` and `set mana` accept glob patterns for entity names.
+    // Returns false if the command had none, so normal dispatch should handle it.
+    // Expanded names skip this step: a name may itself contain `*` or `?`
+    fn dispatch_patterns(&mut self, input: &str, parts: &[&str]) -> bool {
+        if let ["get", names @ ..] = parts {
+            if !names.iter().any(|name| is_glob(name)) {
+                return false;
+            }
+            let mut expanded = vec!["get".to_string()];
+            for name in *names {
+                let matches = self.resolve_pattern(name);
+                if matches.is_empty() {
+                    println!("{} No entities match '{}'", "✗".red().bold(), name.red());
+                }
+                expanded.extend(matches);
+            }
+            if expanded.len() > 1 {
+                let expanded: Vec<&str> = expanded.iter().map(String::as_str).collect();
+                self.dispatch_command(input, &expanded);
+            }
+            return true;
+        }
+
+        let index = match parts {
+            ["rm", _] => 1,
+            ["rm", "-f" | "--force", _] => 2,
+            ["set", "health" | "mana", _, ..] => 2,
+            _ => return false,
+        };
+        let pattern = parts[index];
+        if !is_glob(pattern) {
+            return false;
+        }
+
+        let matches = self.resolve_pattern(pattern);
+        if matches.is_empty() {
+            println!("{} No entities match '{}'", "✗".red().bold(), pattern.red());
+            return true;
+        }
+        if parts == ["rm", pattern]
+            && matches.len() > 1
+            && !confirm(&format!(
+                "Remove {} entities ({})?",
+                matches.len(),
+                matches.join(", ")
+            ))
+        {
+            println!("{}", "Cancelled".bright_black());
+            return true;
+        }
+
+        for name in &matches {
+            let mut expanded = parts.to_vec();
+            expanded[index] = name;
+            self.dispatch_command(input, &expanded);
+        }
+        true
+    }
+
+    fn dispatch(&mut self, input: &str, parts: &[&str]) {
+        if !self.dispatch_patterns(input, parts) {
+            self.dispatch_command(input, parts);
+        }
+    }
+
+    // Runs one command whose entity names need no further expansion
+    fn dispatch_command(&mut self, input: &str, parts: &[&str]) {
+        match parts {
+            ["help"] => {
+                print_help();
+            }
+            ["help", topic @ ..] => {
+                print_command_help(&topic.join(" "));
+            }
+            ["alias"] => {
+                let mut aliases: Vec<(&String, &String)> = self.aliases.iter().collect();
+                aliases.sort();
+                println!("{}", "🔗 Aliases:".cyan().bold());
+                for (alias, expansion) in aliases {
+                    println!(
+                        "  {} {} {}",
+                        alias.bright_cyan(),
+                        "→".bright_black(),
+                        expansion.bright_white()
+                    );
                 }
             }
+            ["alias", alias, expansion @ ..] if !expansion.is_empty() => {
+                match self.define_alias(alias, expansion) {
+                    Ok(_) => println!(
+                        "{} '{}' now expands to '{}'",
+                        "✓".green().bold(),
+                        alias.bright_cyan(),
+                        expansion.join(" ").bright_white()
+                    ),
+                    Err(e) => println!("{} {}", "✗".red().bold(), e.red()),
+                }
+            }
+            ["time", "on"] => {
+                self.timing = true;
+                println!("{} Command timing enabled", "✓".green().bold());
+            }
+            ["time", "off"] => {
+                self.timing = false;
+                println!("{} Command timing disabled", "✓".green().bold());
+            }
+            ["add", "entity", name] => match self.add_entity(name) {
+                Ok(entity) => {
+                    println!(
+                        "{} Created entity '{}' with id {}",
+                        "✓".green().bold(),
+                        name.bright_cyan(),
+                        format!("{:?}", entity).bright_magenta()
+                    );
+                }
+                Err(e) => println!("{} {}", "✗".red().bold(), e.red()),
+            },
+            ["spawn", prefix, count_str] => match count_str.parse::<usize>() {
+                Ok(0) => println!("{}", "Nothing to spawn".yellow()),
+                Ok(count) => match self.spawn_entities(prefix, count) {
+                    Ok(_) => {
+                        println!(
+                            "{} Created {} entities {}..{}",
+                            "✓".green().bold(),
+                            count.to_string().bright_white(),
+                            format!("{}0", prefix).bright_cyan(),
+                            format!("{}{}", prefix, count - 1).bright_cyan()
+                        );
+                    }
+                    Err(e) => println!(
+                        "{} {} (no entities were created)",
+                        "✗".red().bold(),
+                        e.red()
+                    ),
+                },
+                Err(_) => println!(
+                    "{} Invalid count '{}', must be a non-negative number",
+                    "✗".red().bold(),
+                    count_str.red()
+                ),
+            },
+            ["clone", source_name, new_name]
+            | ["clone", source_name, new_name, "with", "parents"] => {
+                let with_parents = parts.len() == 5;
+                match self.clone_entity(source_name, new_name, with_parents) {
+                    Ok(entity) => {
+                        println!(
+                            "{} Cloned '{}' into '{}' with id {}{}",
+                            "✓".green().bold(),
+                            source_name.bright_cyan(),
+                            new_name.bright_cyan(),
+                            format!("{:?}", entity).bright_magenta(),
+                            if with_parents {
+                                " (parents copied)"
+                            } else {
+                                ""
+                            }
+                        );
+                    }
+                    Err(e) => println!("{} {}", "✗".red().bold(), e.red()),
+                }
+            }
+            ["tag", name, label] => match self.tag_entity(name, label) {
+                Ok(true) => println!(
+                    "{} Tagged '{}' as {}",
+                    "✓".green().bold(),
+                    name.bright_cyan(),
+                    label.bright_white()
+                ),
+                Ok(false) => println!(
+                    "{}",
+                    format!("'{}' is already tagged {}", name, label).yellow()
+                ),
+                Err(e) => println!("{} {}", "✗".red().bold(), e.red()),
+            },
+            ["untag", name, label] => match self.untag_entity(name, label) {
+                Ok(true) => println!(
+                    "{} Removed tag {} from '{}'",
+                    "✓".green().bold(),
+                    label.bright_white(),
+                    name.bright_cyan()
+                ),
+                Ok(false) => println!("{}", format!("'{}' is not tagged {}", name, label).yellow()),
+                Err(e) => println!("{} {}", "✗".red().bold(), e.red()),
+            },
+            ["watch", command @ ..] if !command.is_empty() => {
+                let expanded = self.expand_alias(command.iter().map(|t| t.to_string()).collect());
+                let expanded: Vec<&str> = expanded.iter().map(String::as_str).collect();
+                if is_read_only(&expanded) {
+                    // Re-quote so names with spaces survive being tokenized again
+                    let watch: Vec<String> = command.iter().map(|token| quote(token)).collect();
+                    let watch = watch.join(" ");
+                    println!(
+                        "{} Watching '{}' (stop with 'unwatch')",
+                        "✓".green().bold(),
+                        watch.bright_cyan()
+                    );
+                    self.watch = Some(watch);
+                } else {
+                    println!(
+                        "{} Only read-only commands can be watched: {}",
+                        "✗".red().bold(),
+                        READ_ONLY_COMMANDS.join(", ").red()
+                    );
+                }
+            }
+            ["mark-seen"] => {
+                self.mark_seen();
+                println!("{} Marked all current changes as seen", "✓".green().bold());
+            }
+            ["unwatch"] => match self.watch.take() {
+                Some(watch) => println!(
+                    "{} Stopped watching '{}'",
+                    "✓".green().bold(),
+                    watch.bright_cyan()
+                ),
+                None => println!("{}", "Nothing is being watched".yellow()),
+            },
+            ["find", "health", min_str, max_str] => {
+                match (min_str.parse::<i32>(), max_str.parse::<i32>()) {
+                    (Ok(min), Ok(max)) => {
+                        let found = self.find_by_health(min, max);
+                        if self.output == OutputMode::Json {
+                            let found: Vec<NamedHealth> = found
+                                .into_iter()
+                                .map(|(health, name)| NamedHealth { name, health })
+                                .collect();
+                            print_json(&found);
+                        } else if found.is_empty() {
+                            println!(
+                                "{}",
+                                format!("No entities with health in {}..={}", min, max).yellow()
+                            );
+                        } else {
+                            println!(
+                                "{}",
+                                format!("❤ Entities with health in {}..={}:", min, max)
+                                    .cyan()
+                                    .bold()
+                            );
+                            for (health_val, name) in found {
+                                println!(
+                                    "  {} {} {}",
+                                    "•".bright_blue(),
+                                    name.bright_cyan(),
+                                    health_val.to_string().bright_white()
+                                );
+                            }
+                        }
+                    }
+                    _ if self.output == OutputMode::Json => print_json(&json!({
+                        "error": format!("Invalid range '{} {}', expected two numbers", min_str, max_str)
+                    })),
+                    _ => println!(
+                        "{} Invalid range '{} {}', expected two numbers",
+                        "✗".red().bold(),
+                        min_str.red(),
+                        max_str.red()
+                    ),
+                }
+            }
+            ["source", path] => match self.source_file(path) {
+                Ok(count) => println!(
+                    "{} Ran {} commands from {}",
+                    "✓".green().bold(),
+                    count,
+                    path.bright_cyan()
+                ),
+                Err(e) => println!("{} {}", "✗".red().bold(), e.red()),
+            },
+            ["checkpoint", name] => {
+                let checkpoint = self.take_checkpoint();
+                let count = checkpoint.entities.len();
+                let replaced = self
+                    .checkpoints
+                    .insert(name.to_string(), checkpoint)
+                    .is_some();
+                println!(
+                    "{} {} checkpoint '{}' with {} entities",
+                    "✓".green().bold(),
+                    if replaced { "Overwrote" } else { "Saved" },
+                    name.bright_cyan(),
+                    count
+                );
+            }
+            ["restore", name] => match self.checkpoints.get(*name).cloned() {
+                Some(checkpoint) => match self.restore_checkpoint(&checkpoint) {
+                    Ok(count) => println!(
+                        "{} Restored checkpoint '{}' ({} entities)",
+                        "✓".green().bold(),
+                        name.bright_cyan(),
+                        count
+                    ),
+                    Err(e) => println!("{} {}", "✗".red().bold(), e.red()),
+                },
+                None => println!("{} No checkpoint named '{}'", "✗".red().bold(), name.red()),
+            },
+            ["checkpoints"] => {
+                if self.checkpoints.is_empty() {
+                    println!("{}", "No checkpoints saved yet".yellow());
+                } else {
+                    println!("{}", "💾 Checkpoints:".cyan().bold());
+                    for (name, checkpoint) in &self.checkpoints {
+                        println!(
+                            "  {} {} ({} entities)",
+                            "•".bright_blue(),
+                            name.bright_cyan(),
+                            checkpoint.entities.len()
+                        );
+                    }
+                }
+            }
+            ["find", "castcount", rest @ ..] if rest.len() <= 1 => {
+                let min_str = rest.first().copied().unwrap_or("1");
+                match min_str.parse::<u32>() {
+                    Ok(min) => {
+                        let found = self.find_by_cast_count(min);
+                        if self.output == OutputMode::Json {
+                            let found: Vec<_> = found
+                                .iter()
+                                .map(|(count, name)| json!({ "name": name, "spells_cast": count }))
+                                .collect();
+                            print_json(&found);
+                        } else if found.is_empty() {
+                            println!(
+                                "{}",
+                                format!("No entities have cast {} or more spells", min).yellow()
+                            );
+                        } else {
+                            println!(
+                                "{}",
+                                format!("✨ Entities that cast at least {} spells:", min)
+                                    .cyan()
+                                    .bold()
+                            );
+                            for (count, name) in found {
+                                println!(
+                                    "  {} {} {}",
+                                    "•".bright_blue(),
+                                    name.bright_cyan(),
+                                    count.to_string().bright_white()
+                                );
+                            }
+                        }
+                    }
+                    Err(_) if self.output == OutputMode::Json => print_json(&json!({
+                        "error": format!("Invalid minimum '{}', expected a non-negative number", min_str)
+                    })),
+                    Err(_) => println!(
+                        "{} Invalid minimum '{}', expected a non-negative number",
+                        "✗".red().bold(),
+                        min_str.red()
+                    ),
+                }
+            }
+            ["find", "depleted"] => {
+                let found = self.find_depleted();
+                if self.output == OutputMode::Json {
+                    print_json(&found);
+                } else if found.is_empty() {
+                    println!("{}", "No casters are out of mana".yellow());
+                } else {
+                    println!("{}", "💀 Casters out of mana:".cyan().bold());
+                    for name in found {
+                        println!("  {} {}", "•".bright_blue(), name.bright_cyan());
+                    }
+                }
+            }
+            ["find", "relation", words @ ..] => {
+                // Labels may contain spaces, so the rest of the line is the substring
+                let substring = words.join(" ");
+                let found = self.find_by_relation_label(&substring);
+                if self.output == OutputMode::Json {
+                    let found: Vec<_> = found
+                        .iter()
+                        .map(|(parent, child, label)| {
+                            json!({ "parent": parent, "child": child, "label": label })
+                        })
+                        .collect();
+                    print_json(&found);
+                } else if found.is_empty() {
+                    println!(
+                        "{}",
+                        format!("No relations labelled with '{}'", substring).yellow()
+                    );
+                } else {
+                    println!(
+                        "{}",
+                        format!("🔗 Relations labelled with '{}':", substring)
+                            .cyan()
+                            .bold()
+                    );
+                    for (parent, child, label) in found {
+                        println!(
+                            "  {} {} -> {} ({})",
+                            "•".bright_blue(),
+                            parent.bright_cyan(),
+                            child.bright_cyan(),
+                            label.bright_white()
+                        );
+                    }
+                }
+            }
+            ["find", "tag", label] => {
+                let found = self.find_by_tag(label);
+                if self.output == OutputMode::Json {
+                    let found: Vec<_> = found
+                        .iter()
+                        .map(
+                            |(name, entity)| json!({ "name": name, "id": format!("{:?}", entity) }),
+                        )
+                        .collect();
+                    print_json(&found);
+                } else if found.is_empty() {
+                    println!("{}", format!("No entities tagged {}", label).yellow());
+                } else {
+                    println!("{}", format!("🏷 Entities tagged {}:", label).cyan().bold());
+                    for (name, entity) in found {
+                        println!(
+                            "  {} {} ({})",
+                            "•".bright_blue(),
+                            name.bright_cyan(),
+                            format!("{:?}", entity).bright_magenta()
+                        );
+                    }
+                }
+            }
+            // One report per name; a missing name doesn't stop the rest
+            ["get", names @ ..] if !names.is_empty() && self.output == OutputMode::Json => {
+                for name in names {
+                    print_json_result(self.entity_report(name));
+                }
+            }
+            ["get", names @ ..] if !names.is_empty() => {
+                for (i, name) in names.iter().enumerate() {
+                    if i > 0 {
+                        println!("{}", "────────────────────────".bright_black());
+                    }
+                    match self.get_entity_info(name) {
+                        Ok(info) => print!("{}", info),
+                        Err(e) => println!("{} {}", "✗".red().bold(), e.red()),
+                    }
+                }
+            }
+            ["set", "output", "json"] => {
+                self.output = OutputMode::Json;
+                println!("{}", json!({ "output": "json" }));
+            }
+            ["set", "output", "human"] => {
+                self.output = OutputMode::Human;
+                println!("{} Output set to human-readable", "✓".green().bold());
+            }
+            ["set", "prompt"] => {
+                self.prompt = DEFAULT_PROMPT.to_string();
+                println!("{} Prompt reset to default", "✓".green().bold());
+            }
+            ["set", "prompt", text @ ..] => {
+                self.prompt = text.join(" ");
+                println!(
+                    "{} Prompt set to {}",
+                    "✓".green().bold(),
+                    quote(&self.prompt).bright_cyan()
+                );
+            }
+            ["diff", left_name, right_name] => match self.diff_entities(left_name, right_name) {
+                Ok(diff) => print!("{}", diff),
+                Err(e) => println!("{} {}", "✗".red().bold(), e.red()),
+            },
+            ["whereis", name] => match self.describe_components(name) {
+                Ok(description) => println!("{}", description),
+                Err(e) => println!("{} {}", "✗".red().bold(), e.red()),
+            },
+            ["rm", "-f" | "--force", name] => self.rm_command(name, true),
+            ["rm", name] => self.rm_command(name, false),
+            [
+                "set-relation",
+                "child",
+                child_name,
+                "parent",
+                parent_name,
+                rest @ ..,
+            ] if matches!(rest, [] | ["as", _, ..]) => {
+                // Optional `as [label]` names the edge; defaults to "child"
+                let label = match rest {
+                    ["as", words @ ..] => words.join(" "),
+                    _ => "child".to_string(),
+                };
+                match self.add_relation(child_name, parent_name, &label) {
+                    Ok(_) => {
+                        println!(
+                            "{} Created relation: {} {} {} ({}) {}",
+                            "✓".green().bold(),
+                            child_name.bright_cyan(),
+                            "is child of".white(),
+                            parent_name.bright_yellow(),
+                            label.bright_white(),
+                            "🔗".bright_blue()
+                        );
+                    }
+                    Err(e) => println!("{} {}", "✗".red().bold(), e.red()),
+                }
+            }
+            ["rm-relation", "child", child_name, "parent", parent_name] => {
+                match self.remove_relation(child_name, parent_name) {
+                    Ok(_) => {
+                        println!(
+                            "{} Removed relation: {} {} {} {}",
+                            "✓".green().bold(),
+                            child_name.bright_cyan(),
+                            "is no longer child of".white(),
+                            parent_name.bright_yellow(),
+                            "✂️".red()
+                        );
+                    }
+                    Err(e) => println!("{} {}", "✗".red().bold(), e.red()),
+                }
+            }
+            ["reparent", child_name, new_parent_name] => {
+                match self.reparent(child_name, new_parent_name) {
+                    Ok(old_parents) => {
+                        let from = if old_parents.is_empty() {
+                            "no parent".to_string()
+                        } else {
+                            old_parents.join(", ")
+                        };
+                        println!(
+                            "{} Moved {} from {} to {}",
+                            "✓".green().bold(),
+                            child_name.bright_cyan(),
+                            from.bright_black(),
+                            new_parent_name.bright_yellow()
+                        );
+                    }
+                    Err(e) => println!("{} {}", "✗".red().bold(), e.red()),
+                }
+            }
+            ["set", "thresholds", good_str, warn_str] => {
+                match (good_str.parse::<i32>(), warn_str.parse::<i32>()) {
+                    (Ok(good), Ok(warn)) => match self.set_thresholds(good, warn) {
+                        Ok(_) => println!(
+                            "{} Health is now {} above {}, {} above {}, {} otherwise",
+                            "✓".green().bold(),
+                            "green".green(),
+                            good,
+                            "yellow".yellow(),
+                            warn,
+                            "red".red()
+                        ),
+                        Err(e) => println!("{} {}", "✗".red().bold(), e.red()),
+                    },
+                    _ => println!(
+                        "{} Invalid thresholds '{} {}', expected two numbers",
+                        "✗".red().bold(),
+                        good_str.red(),
+                        warn_str.red()
+                    ),
+                }
+            }
+            ["set", "autodeath", "on"] => {
+                self.autodeath = true;
+                println!(
+                    "{} Entities at 0 health or below will be removed",
+                    "✓".green().bold()
+                );
+            }
+            ["set", "autodeath", "off"] => {
+                self.autodeath = false;
+                println!("{} Fallen entities will be kept", "✓".green().bold());
+            }
+            ["set", "health", name, number_str] => match number_str.parse::<i32>() {
+                Ok(health_value) => match self.set_health(name, health_value) {
+                    Ok(true) => println!(
+                        "{} Removed '{}' after its health reached {}",
+                        "✓".green().bold(),
+                        name.bright_cyan(),
+                        health_value.to_string().red()
+                    ),
+                    Ok(false) => {
+                        let thresholds = self.thresholds();
+                        let health_icon = if health_value > thresholds.good {
+                            "💚"
+                        } else if health_value > thresholds.warn {
+                            "💛"
+                        } else {
+                            "❤️"
+                        };
+                        println!(
+                            "{} Set health of '{}' to {} {}",
+                            "✓".green().bold(),
+                            name.bright_cyan(),
+                            health_value.to_string().bright_green(),
+                            health_icon
+                        );
+                    }
+                    Err(e) => println!("{} {}", "✗".red().bold(), e.red()),
+                },
+                Err(_) => println!(
+                    "{} Invalid health value '{}', must be a number",
+                    "✗".red().bold(),
+                    number_str.red()
+                ),
+            },
+            ["set", "mana", name, current_str, maximum_str] => {
+                match (current_str.parse::<i32>(), maximum_str.parse::<i32>()) {
+                    (Ok(current), Ok(maximum)) => {
+                        match self.set_mana_with_maximum(name, current, maximum) {
+                            Ok(stored) => {
+                                println!(
+                                    "{} {} now has {}/{} mana! {}",
+                                    "✓".green().bold(),
+                                    name.bright_cyan(),
+                                    stored.to_string().bright_blue(),
+                                    maximum.to_string().bright_blue(),
+                                    "🔮".bright_magenta()
+                                );
+                                if stored != current {
+                                    println!(
+                                        "  {}",
+                                        format!("(clamped from {})", current).bright_black()
+                                    );
+                                }
+                            }
+                            Err(e) => println!("{} {}", "✗".red().bold(), e.red()),
+                        }
+                    }
+                    _ => println!(
+                        "{} Invalid mana values '{} {}', must be numbers",
+                        "✗".red().bold(),
+                        current_str.red(),
+                        maximum_str.red()
+                    ),
+                }
+            }
+            ["set", "max-health", name, number_str] => match number_str.parse::<i32>() {
+                Ok(maximum) => match self.set_max_health(name, maximum) {
+                    Ok(_) => println!(
+                        "{} Max health of '{}' set to {}",
+                        "✓".green().bold(),
+                        name.bright_cyan(),
+                        maximum.to_string().bright_green()
+                    ),
+                    Err(e) => println!("{} {}", "✗".red().bold(), e.red()),
+                },
+                Err(_) => println!(
+                    "{} Invalid max health '{}', must be a number",
+                    "✗".red().bold(),
+                    number_str.red()
+                ),
+            },
+            ["set", "regen", name, rate_str] => match rate_str.parse::<i32>() {
+                Ok(rate) => match self.set_regen(name, rate) {
+                    Ok(_) => println!(
+                        "{} '{}' now regenerates {} mana per tick",
+                        "✓".green().bold(),
+                        name.bright_cyan(),
+                        format!("{:+}", rate).bright_blue()
+                    ),
+                    Err(e) => println!("{} {}", "✗".red().bold(), e.red()),
+                },
+                Err(_) => println!(
+                    "{} Invalid regen rate '{}', must be a number",
+                    "✗".red().bold(),
+                    rate_str.red()
+                ),
+            },
+            ["set", "float", name, key, value_str] => match parse_float(value_str) {
+                Ok(value) => match self.set_float(name, key, value) {
+                    Ok(_) => println!(
+                        "{} '{}' {} set to {}",
+                        "✓".green().bold(),
+                        name.bright_cyan(),
+                        key.bright_white(),
+                        format!("{:.*}", FLOAT_PRECISION, value).bright_green()
+                    ),
+                    Err(e) => println!("{} {}", "✗".red().bold(), e.red()),
+                },
+                Err(e) => println!("{} {}", "✗".red().bold(), e.red()),
+            },
+            ["tick"] | ["tick", _] => {
+                let count = match parts {
+                    ["tick", count_str] => count_str.parse::<usize>().ok(),
+                    _ => Some(1),
+                };
+                match count {
+                    Some(count) => match self.tick(count) {
+                        Ok(regenerating) => println!(
+                            "{} Advanced {} tick(s); {} entities regenerated",
+                            "✓".green().bold(),
+                            count.to_string().bright_white(),
+                            regenerating.to_string().bright_white()
+                        ),
+                        Err(e) => println!("{} {}", "✗".red().bold(), e.red()),
+                    },
+                    None => println!(
+                        "{} Invalid tick count '{}', must be a non-negative number",
+                        "✗".red().bold(),
+                        parts[1].red()
+                    ),
+                }
+            }
+            ["set", "mana", name, number_str] => match number_str.parse::<i32>() {
+                Ok(mana_value) => match self.set_mana(name, mana_value) {
+                    Ok(_) => {
+                        println!(
+                            "{} {} now has {} mana! {}",
+                            "✓".green().bold(),
+                            name.bright_cyan(),
+                            mana_value.to_string().bright_blue(),
+                            "🔮".bright_magenta()
+                        );
+                    }
+                    Err(e) => println!("{} {}", "✗".red().bold(), e.red()),
+                },
+                Err(_) => println!(
+                    "{} Invalid mana value '{}', must be a number",
+                    "✗".red().bold(),
+                    number_str.red()
+                ),
+            },
+            ["randomize", "health", min_str, max_str, seed_str] => {
+                match (
+                    min_str.parse::<i32>(),
+                    max_str.parse::<i32>(),
+                    seed_str.parse::<u64>(),
+                ) {
+                    (Ok(min), Ok(max), Ok(seed)) => match self.randomize_health(min, max, seed) {
+                        Ok(count) => println!(
+                            "{} Randomized health of {} entities in [{}, {}] (seed {})",
+                            "✓".green().bold(),
+                            count.to_string().bright_white(),
+                            min,
+                            max,
+                            seed.to_string().bright_magenta()
+                        ),
+                        Err(e) => println!("{} {}", "✗".red().bold(), e.red()),
+                    },
+                    _ => println!(
+                        "{} Invalid range '{} {}' or seed '{}', min/max must be numbers",
+                        "✗".red().bold(),
+                        min_str.red(),
+                        max_str.red(),
+                        seed_str.red()
+                    ),
+                }
+            }
+            ["cast", spell_name, "by", caster_name] | ["cast", spell_name, caster_name] => {
+                // Cost comes from the spellbook when not given explicitly
+                match self
+                    .spell_cost(spell_name)
+                    .and_then(|cost| self.cast_spell(caster_name, spell_name, cost))
+                {
+                    Ok(_) => {
+                        // Success message is printed in cast_spell method
+                    }
+                    Err(e) => println!("{} {}", "✗".red().bold(), e.red()),
+                }
+            }
+            ["set-spell-cost", spell_name, cost_str] => match cost_str.parse::<i32>() {
+                Ok(cost) => match self.set_spell_cost(spell_name, cost) {
+                    Ok(_) => println!(
+                        "{} {} now costs {} mana",
+                        "✓".green().bold(),
+                        spell_name.bright_yellow(),
+                        cost.to_string().bright_red()
+                    ),
+                    Err(e) => println!("{} {}", "✗".red().bold(), e.red()),
+                },
+                Err(_) => println!(
+                    "{} Invalid mana cost '{}', must be a number",
+                    "✗".red().bold(),
+                    cost_str.red()
+                ),
+            },
+            ["cast-aoe", spell_name, caster_name, cost_str] => match cost_str.parse::<i32>() {
+                Ok(cost_per_target) => {
+                    match self.cast_aoe(caster_name, spell_name, cost_per_target) {
+                        Ok(hit) => println!(
+                            "{} {} hit {} entities",
+                            "✓".green().bold(),
+                            spell_name.bright_yellow(),
+                            hit.to_string().bright_white()
+                        ),
+                        Err(e) => println!("{} {}", "✗".red().bold(), e.red()),
+                    }
+                }
+                Err(_) => println!(
+                    "{} Invalid mana cost '{}', must be a number",
+                    "✗".red().bold(),
+                    cost_str.red()
+                ),
+            },
+            ["cast", spell_name, caster_name, target_name, cost_str] if *caster_name != "by" => {
+                match cost_str.parse::<i32>() {
+                    Ok(mana_cost) => {
+                        if let Err(e) =
+                            self.cast_spell_on(caster_name, target_name, spell_name, mana_cost)
+                        {
+                            println!("{} {}", "✗".red().bold(), e.red());
+                        }
+                    }
+                    Err(_) => println!(
+                        "{} Invalid mana cost '{}', must be a number",
+                        "✗".red().bold(),
+                        cost_str.red()
+                    ),
+                }
+            }
+            ["cast", spell_name, "by", caster_name, "for", cost_str]
+            | ["cast", spell_name, caster_name, cost_str] => {
+                match cost_str.parse::<i32>() {
+                    Ok(mana_cost) => {
+                        match self.cast_spell(caster_name, spell_name, mana_cost) {
+                            Ok(_) => {
+                                // Success message is printed in cast_spell method
+                            }
+                            Err(e) => println!("{} {}", "✗".red().bold(), e.red()),
+                        }
+                    }
+                    Err(_) => println!(
+                        "{} Invalid mana cost '{}', must be a number",
+                        "✗".red().bold(),
+                        cost_str.red()
+                    ),
+                }
+            }
+            ["dump"] => {
+                self.dump_changes(None);
+            }
+            ["dump", "added"] => {
+                self.dump_changes(Some("added"));
+            }
+            ["dump", "modified"] => {
+                self.dump_changes(Some("modified"));
+            }
+            ["dump", "removed"] => {
+                self.dump_changes(Some("removed"));
+            }
+            ["list"] => {
+                // entity_names is a HashMap, so sort by name for stable, diffable output
+                let mut entities: Vec<(&String, &Entity)> = self.entity_names.iter().collect();
+                entities.sort_by_key(|&(name, _)| name);
+
+                if self.output == OutputMode::Json {
+                    let entries: Vec<_> = entities
+                        .iter()
+                        .map(|(name, entity)| {
+                            json!({
+                                "name": name,
+                                "id": format!("{:?}", entity),
+                                "slot": entity_slot(**entity),
+                                "tags": self.sorted_tags(**entity),
+                            })
+                        })
+                        .collect();
+                    print_json(&entries);
+                } else if entities.is_empty() {
+                    println!("{}", "No entities created yet".yellow());
+                } else {
+                    println!("{}", "📋 Entities:".cyan().bold());
+                    for (name, entity) in entities {
+                        let labels = self.sorted_tags(*entity);
+                        let labels = if labels.is_empty() {
+                            String::new()
+                        } else {
+                            format!(" [{}]", labels.join(", "))
+                        };
+                        println!(
+                            "  {} {} ({}) {}{}",
+                            "•".bright_blue(),
+                            name.bright_cyan(),
+                            format!("{:?}", entity).bright_magenta(),
+                            entity_slot(*entity).bright_yellow(),
+                            labels.bright_white()
+                        );
+                    }
+                }
+            }
+            ["stats"] => {
+                self.show_stats();
+            }
+            ["histogram"] => {
+                self.show_histogram();
+            }
+            ["recent"] => {
+                self.show_recent(DEFAULT_RECENT_COUNT);
+            }
+            ["recent", n] => match n.parse::<usize>() {
+                Ok(count) => self.show_recent(count),
+                Err(_) => println!(
+                    "{} Invalid count '{}', expected a number",
+                    "✗".red().bold(),
+                    n.red()
+                ),
+            },
+            ["top", field, rest @ ..] => {
+                let (count, ascending) = match rest {
+                    [] => (Ok(DEFAULT_LEADERBOARD_SIZE), false),
+                    ["asc"] => (Ok(DEFAULT_LEADERBOARD_SIZE), true),
+                    [n] => (n.parse::<usize>(), false),
+                    [n, "asc"] => (n.parse::<usize>(), true),
+                    _ => {
+                        println!("{} Usage: top [field] [n] [asc]", "✗".red().bold());
+                        return;
+                    }
+                };
+                match count {
+                    Ok(count) => self.show_leaderboard(field, count, ascending),
+                    Err(_) => println!(
+                        "{} Invalid count '{}', expected a number",
+                        "✗".red().bold(),
+                        rest[0].red()
+                    ),
+                }
+            }
+            ["components"] => {
+                self.show_connected_components();
+            }
+            ["tree", "dfs", root_name] => {
+                if let Err(e) = self.show_subtree(root_name) {
+                    println!("{} {}", "✗".red().bold(), e.red());
+                }
+            }
+            ["tree", mode] => {
+                self.show_tree(mode);
+            }
+            ["tree"] => {
+                // Default to DFS if no mode specified
+                self.show_tree("dfs");
+            }
+            ["echo", message @ ..] => {
+                // Join all the remaining parts as the message
+                let full_message = message.join(" ");
+                println!("{}", full_message.bright_white());
+            }
+            _ => {
+                println!("{} Unknown command: '{}'", "⚠".yellow().bold(), input.red());
+                println!("{}", "Type 'help' for available commands".bright_black());
+            }
+        }
+    }
 
-            let indent = "  ".repeat(depth);
-            let connector = if depth > 0 { "└─ " } else { "" };
+    fn gather_stats(&self) -> WorldStats {
+        // Only named entities; the resources entity is bookkeeping, not world content
+        let entities = Query::new(entity_ids())
+            .with(components::name())
+            .borrow(&self.world)
+            .iter()
+            .count();
+        let child_of_relations: usize = Query::new(relations_like(components::child_of))
+            .borrow(&self.world)
+            .iter()
+            .map(|relations| relations.count())
+            .sum();
 
-            // Get health info if available
-            let health_str = if let Ok(health_val) = self.world.get(entity, health()) {
-                let health_color = if *health_val > 75 {
-                    format!(" [Health: {}]", *health_val).green()
-                } else if *health_val > 30 {
-                    format!(" [Health: {}]", *health_val).yellow()
-                } else {
-                    format!(" [Health: {}]", *health_val).red()
-                };
-                health_color.to_string()
-            } else {
-                String::new()
-            };
+        // Counts and sums come from the parallel aggregation; only the min/max
+        // holders need their names, so they get a separate pass over named entities
+        let totals = world_totals(&self.world);
+        let mut lowest: Option<NamedHealth> = None;
+        let mut highest: Option<NamedHealth> = None;
+        Query::new((components::name(), health()))
+            .borrow(&self.world)
+            .for_each(|(name, health_val)| {
+                if lowest.as_ref().is_none_or(|min| *health_val < min.health) {
+                    lowest = Some(NamedHealth {
+                        name: name.clone(),
+                        health: *health_val,
+                    });
+                }
+                if highest.as_ref().is_none_or(|max| *health_val > max.health) {
+                    highest = Some(NamedHealth {
+                        name: name.clone(),
+                        health: *health_val,
+                    });
+                }
+            });
+
+        WorldStats {
+            entities,
+            with_health: totals.with_health,
+            with_mana: totals.with_mana,
+            child_of_relations,
+            total_mana: totals.mana,
+            average_health: (totals.with_health > 0)
+                .then(|| totals.health as f64 / totals.with_health as f64),
+            lowest_health: lowest,
+            highest_health: highest,
+        }
+    }
+
+    // How many entities carry each component, one query per row. Relation rows
+    // count entities with at least one edge of that kind
+    fn component_histogram(&self) -> Vec<(&'static str, usize)> {
+        vec![
+            (
+                "name",
+                Query::new(components::name())
+                    .borrow(&self.world)
+                    .iter()
+                    .count(),
+            ),
+            (
+                "health",
+                Query::new(health()).borrow(&self.world).iter().count(),
+            ),
+            (
+                "mana",
+                Query::new(mana()).borrow(&self.world).iter().count(),
+            ),
+            (
+                "last_modified",
+                Query::new(last_modified())
+                    .borrow(&self.world)
+                    .iter()
+                    .count(),
+            ),
+            (
+                "child_of",
+                Query::new(relations_like(components::child_of))
+                    .borrow(&self.world)
+                    .iter()
+                    .filter(|relations| relations.count() > 0)
+                    .count(),
+            ),
+            (
+                "has_child",
+                Query::new(relations_like(has_child))
+                    .borrow(&self.world)
+                    .iter()
+                    .filter(|relations| relations.count() > 0)
+                    .count(),
+            ),
+        ]
+    }
+
+    fn show_histogram(&self) {
+        let rows = self.component_histogram();
+        if self.output == OutputMode::Json {
+            let rows: Vec<_> = rows
+                .iter()
+                .map(|(component, count)| json!({ "component": component, "count": count }))
+                .collect();
+            print_json(&rows);
+            return;
+        }
+
+        let max = rows.iter().map(|(_, count)| *count).max().unwrap_or(0);
+        if max == 0 {
+            println!("{}", "World is empty".yellow());
+            return;
+        }
+
+        println!("{}", "📊 Component Histogram:".cyan().bold());
+        for (component, count) in rows {
+            // Round up so any non-zero count gets at least one cell
+            let cells = (count * HISTOGRAM_WIDTH).div_ceil(max);
+            // Pad before colouring so the escape codes don't count towards the width
+            println!(
+                "  {} {}{} {}",
+                format!("{:<14}", component).bright_black(),
+                "█".repeat(cells).bright_green(),
+                " ".repeat(HISTOGRAM_WIDTH - cells),
+                count.to_string().bright_white()
+            );
+        }
+    }
+
+    fn show_stats(&self) {
+        let stats = self.gather_stats();
+        if self.output == OutputMode::Json {
+            print_json(&stats);
+            return;
+        }
+
+        if stats.entities == 0 {
+            println!("{}", "World is empty".yellow());
+            return;
+        }
+
+        println!("{}", "📊 World Stats:".cyan().bold());
+        println!(
+            "  {} {}",
+            "Entities:".bright_black(),
+            stats.entities.to_string().bright_white()
+        );
+        println!(
+            "  {} {}",
+            "With health:".bright_black(),
+            stats.with_health.to_string().bright_white()
+        );
+        println!(
+            "  {} {}",
+            "With mana:".bright_black(),
+            stats.with_mana.to_string().bright_white()
+        );
+        if stats.with_mana > 0 {
+            println!(
+                "  {} {}",
+                "Total mana:".bright_black(),
+                stats.total_mana.to_string().bright_white()
+            );
+        }
+        println!(
+            "  {} {}",
+            "Child-of relations:".bright_black(),
+            stats.child_of_relations.to_string().bright_white()
+        );
 
+        let Some(average) = stats.average_health else {
+            println!("  {}", "(No entities with health)".bright_black().italic());
+            return;
+        };
+        println!(
+            "  {} {}",
+            "Average health:".bright_black(),
+            format!("{:.1}", average).bright_white()
+        );
+        if let Some(NamedHealth { name, health }) = stats.lowest_health {
             println!(
-                "{}{}{} ({}){}",
-                indent.bright_black(),
-                connector.bright_black(),
+                "  {} {} ({})",
+                "Lowest health:".bright_black(),
                 name.bright_cyan(),
-                format!("{:?}", entity).bright_magenta(),
-                health_str
+                health.to_string().red()
+            );
+        }
+        if let Some(NamedHealth { name, health }) = stats.highest_health {
+            println!(
+                "  {} {} ({})",
+                "Highest health:".bright_black(),
+                name.bright_cyan(),
+                health.to_string().green()
+            );
+        }
+    }
+
+    fn show_connected_components(&self) {
+        let mut entities: Vec<(Entity, String)> = Query::new((entity_ids(), components::name()))
+            .borrow(&self.world)
+            .iter()
+            .map(|(entity, name)| (entity, name.clone()))
+            .collect();
+        if entities.is_empty() {
+            println!("{}", "No entities created yet".yellow());
+            return;
+        }
+        entities.sort_by_key(|(entity, _)| entity.index());
+
+        // Undirected adjacency over both relation directions
+        let mut neighbors: HashMap<Entity, Vec<Entity>> = HashMap::new();
+        let mut link = |a: Entity, b: Entity| {
+            neighbors.entry(a).or_default().push(b);
+            neighbors.entry(b).or_default().push(a);
+        };
+        for (entity, child_of_relations) in
+            Query::new((entity_ids(), relations_like(components::child_of)))
+                .borrow(&self.world)
+                .iter()
+        {
+            for (parent, _) in child_of_relations {
+                link(entity, parent);
+            }
+        }
+        for (entity, has_child_relations) in Query::new((entity_ids(), relations_like(has_child)))
+            .borrow(&self.world)
+            .iter()
+        {
+            for (child, _) in has_child_relations {
+                link(entity, child);
+            }
+        }
+
+        // BFS from each unvisited entity to collect its component
+        let names: HashMap<Entity, &String> = entities
+            .iter()
+            .map(|(entity, name)| (*entity, name))
+            .collect();
+        let mut visited = HashSet::new();
+        let mut groups: Vec<Vec<Entity>> = Vec::new();
+        for (start, _) in &entities {
+            if !visited.insert(*start) {
+                continue;
+            }
+            let mut group = Vec::new();
+            let mut queue = VecDeque::from([*start]);
+            while let Some(entity) = queue.pop_front() {
+                group.push(entity);
+                for next in neighbors.get(&entity).into_iter().flatten() {
+                    if names.contains_key(next) && visited.insert(*next) {
+                        queue.push_back(*next);
+                    }
+                }
+            }
+            groups.push(group);
+        }
+
+        let largest = groups.iter().map(|group| group.len()).max().unwrap_or(0);
+        println!("{}", "🧩 Connected Components:".cyan().bold());
+        for (i, group) in groups.iter().enumerate() {
+            let members: Vec<&str> = group.iter().map(|entity| names[entity].as_str()).collect();
+            println!(
+                "  {} {} {}",
+                format!("{}.", i + 1).bright_black(),
+                format!("[{}]", group.len()).bright_magenta(),
+                members.join(", ").bright_cyan()
             );
         }
+        println!(
+            "  {} {}, {} {}",
+            "Components:".bright_black(),
+            groups.len().to_string().bright_white(),
+            "largest:".bright_black(),
+            largest.to_string().bright_white()
+        );
+    }
+
+    fn describe_components(&self, name: &str) -> Result<String, String> {
+        let entity = self.get_entity(name)?;
+
+        // Probe each known component, in the same spirit as flax_example's entity listing
+        let mut components = Vec::new();
+        if self.world.has(entity, components::name()) {
+            components.push("name".to_string());
+        }
+        if self.world.has(entity, health()) {
+            components.push("health".to_string());
+        }
+        if self.world.has(entity, mana()) {
+            components.push("mana".to_string());
+        }
+        if self.world.has(entity, last_modified()) {
+            components.push("last_modified".to_string());
+        }
+        if self.world.has(entity, health_modified_at()) {
+            components.push("health_modified_at".to_string());
+        }
+        if self.world.has(entity, mana_modified_at()) {
+            components.push("mana_modified_at".to_string());
+        }
+
+        for parent in self.parent_names(entity) {
+            components.push(format!("child_of({})", parent));
+        }
+        for (child, label) in self.child_names_with_labels(entity) {
+            components.push(format!("has_child({}): {}", child, label));
+        }
+
+        Ok(format!(
+            "{}: [{}]",
+            name.bright_cyan(),
+            components.join(", ").bright_white()
+        ))
+    }
+
+    fn show_tree(&self, mode: &str) {
+        println!(
+            "\n{}",
+            format!("=== {} Tree View ===", mode.to_uppercase())
+                .cyan()
+                .bold()
+        );
+
+        match mode {
+            "dfs" => self.show_dfs_tree(None),
+            "bfs" => self.show_bfs_tree(),
+            "topo" => self.show_topo_tree(),
+            _ => println!("{}", "Invalid tree mode. Use 'dfs', 'bfs' or 'topo'".red()),
+        }
+
+        println!("{}\n", "========================".bright_black());
+    }
+
+    fn show_subtree(&self, root_name: &str) -> Result<(), String> {
+        let root = self.get_entity(root_name)?;
+
+        println!(
+            "\n{}",
+            format!("=== DFS Tree View ({}) ===", root_name)
+                .cyan()
+                .bold()
+        );
+        self.show_dfs_tree(Some(root));
+        println!("{}\n", "========================".bright_black());
+        Ok(())
+    }
+
+    fn show_dfs_tree(&self, root: Option<Entity>) {
+        println!("{}", "DFS Traversal (depth-first search):".green().bold());
+
+        let nodes = traverse_child_of(&self.world);
+        let nodes: Vec<(Entity, usize)> = match root {
+            // Keep the root and the contiguous run of deeper entries after it,
+            // with depths re-based so the root sits at 0
+            Some(root) => match nodes.iter().position(|(entity, _)| *entity == root) {
+                Some(start) => {
+                    let base = nodes[start].1;
+                    nodes[start..]
+                        .iter()
+                        .enumerate()
+                        .take_while(|(i, (_, depth))| *i == 0 || *depth > base)
+                        .map(|(_, (entity, depth))| (*entity, depth - base))
+                        .collect()
+                }
+                None => Vec::new(),
+            },
+            // Unnamed roots (such as the resources entity) aren't part of the REPL world
+            None => nodes
+                .into_iter()
+                .filter(|(entity, depth)| *depth > 0 || self.world.has(*entity, components::name()))
+                .collect(),
+        };
+
+        // Walk backwards to find which nodes are the last of their siblings
+        let mut is_last = vec![false; nodes.len()];
+        let mut later_sibling: Vec<bool> = Vec::new();
+        for (i, (_, depth)) in nodes.iter().enumerate().rev() {
+            later_sibling.resize(depth + 1, false);
+            is_last[i] = !later_sibling[*depth];
+            later_sibling[*depth] = true;
+        }
+
+        // One flag per ancestor level below the root: whether that ancestor was last
+        let mut ancestors_last: Vec<bool> = Vec::new();
+        for (i, (entity, depth)) in nodes.iter().enumerate() {
+            ancestors_last.truncate(depth.saturating_sub(1));
+
+            let mut prefix = String::new();
+            for ancestor_is_last in &ancestors_last {
+                prefix.push_str(if *ancestor_is_last { "   " } else { "│  " });
+            }
+            if *depth > 0 {
+                prefix.push_str(if is_last[i] { "└─ " } else { "├─ " });
+                ancestors_last.push(is_last[i]);
+            }
+
+            self.print_dfs_node(*entity, &prefix);
+        }
+    }
+
+    fn show_bfs_tree(&self) {
+        println!("{}", "BFS Traversal (breadth-first search):".green().bold());
+
+        let mut roots: Vec<Entity> = Query::new(entity_ids())
+            .with(components::name())
+            .without_relation(components::child_of)
+            .borrow(&self.world)
+            .iter()
+            .collect();
+        roots.sort_by_key(|entity| entity.index());
+
+        let mut visited: HashSet<Entity> = roots.iter().copied().collect();
+        let mut queue: VecDeque<(Entity, usize)> =
+            roots.into_iter().map(|root| (root, 0)).collect();
+        let mut current_level = None;
+        let mut children_query = Query::new(relations_like(has_child));
+        let mut children_borrow = children_query.borrow(&self.world);
+        while let Some((entity, level)) = queue.pop_front() {
+            // The queue holds every level in order, so a new depth starts a new group
+            if current_level != Some(level) {
+                println!("{}", format!("Level {}:", level).bright_white().bold());
+                current_level = Some(level);
+            }
+            self.print_dfs_node(entity, "  ");
+
+            let mut children: Vec<Entity> = children_borrow
+                .get(entity)
+                .map(|relations| relations.map(|(child, _)| child).collect())
+                .unwrap_or_default();
+            children.sort_by_key(|child| child.index());
+            // An entity with several parents is listed once, at its shallowest level
+            for child in children {
+                if visited.insert(child) {
+                    queue.push_back((child, level + 1));
+                }
+            }
+        }
+    }
+
+    fn print_dfs_node(&self, entity: Entity, prefix: &str) {
+        let name = self
+            .world
+            .get(entity, components::name())
+            .map(|n| n.clone())
+            .unwrap_or_else(|_| format!("{:?}", entity));
+
+        // Get health info if available
+        let health_str = if let Ok(health_val) = self.world.get(entity, health()) {
+            format!(
+                " [Health: {}]",
+                health_color(*health_val, &self.thresholds())
+            )
+        } else {
+            String::new()
+        };
+
+        println!(
+            "{}{} ({}){}",
+            prefix.bright_black(),
+            name.bright_cyan(),
+            format!("{:?}", entity).bright_magenta(),
+            health_str
+        );
     }
 
     fn show_topo_tree(&self) {
+        // Topo ordering is meaningless on a loop, so report the loop instead
+        if let Some(cycle) = find_child_of_cycle(&self.world) {
+            let mut names: Vec<String> = cycle
+                .iter()
+                .map(|&entity| self.name_or_id(entity))
+                .collect();
+            names.push(names[0].clone());
+            println!(
+                "{} {}",
+                "✗".red().bold(),
+                "Cannot sort: the child_of relations form a cycle".red()
+            );
+            println!("  {}", names.join(" → ").red().bold());
+            return;
+        }
+
         // Use Flax's built-in topological traversal
         let mut query = Query::new((entity_ids(), components::name()))
             .with_strategy(Topo::new(components::child_of));
@@ -1008,123 +4476,904 @@ impl ReplState {
             "Topological Sort (parents before children):".green().bold()
         );
 
+        // Depth is the longest path from a root. Parents always come first, so
+        // theirs is already known; unnamed parents aren't listed and count as roots
+        let mut depths: HashMap<Entity, usize> = HashMap::new();
+        let mut parents_query = Query::new(relations_like(components::child_of));
+        let mut parents_borrow = parents_query.borrow(&self.world);
+
         for (entity, name) in query.borrow(&self.world).iter() {
+            let depth = parents_borrow
+                .get(entity)
+                .ok()
+                .and_then(|relations| {
+                    relations
+                        .map(|(parent, _)| depths.get(&parent).copied().unwrap_or(0) + 1)
+                        .max()
+                })
+                .unwrap_or(0);
+            depths.insert(entity, depth);
+
             // Get health info if available
             let health_str = if let Ok(health_val) = self.world.get(entity, health()) {
-                let health_color = if *health_val > 75 {
-                    format!(" [Health: {}]", *health_val).green()
-                } else if *health_val > 30 {
-                    format!(" [Health: {}]", *health_val).yellow()
-                } else {
-                    format!(" [Health: {}]", *health_val).red()
-                };
-                health_color.to_string()
+                format!(
+                    " [Health: {}]",
+                    health_color(*health_val, &self.thresholds())
+                )
+            } else {
+                String::new()
+            };
+
+            // Show parent relationships inline
+            let parents = self.parent_names(entity);
+            let parent_str = if !parents.is_empty() {
+                format!(" ← {}", parents.join(", ")).yellow().to_string()
             } else {
                 String::new()
             };
 
-            // Show parent relationships inline
-            let parent_str = if let Ok(child_of_relations) =
-                Query::new(relations_like(components::child_of))
-                    .with_relation(components::child_of)
-                    .borrow(&self.world)
-                    .get(entity)
-            {
-                let parents: Vec<String> = child_of_relations
-                    .map(|(parent, _)| {
-                        self.world
-                            .get(parent, components::name())
-                            .map(|n| n.clone())
-                            .unwrap_or_else(|_| format!("{:?}", parent))
-                    })
-                    .collect();
+            println!(
+                "  {}{} • {} ({}){}{}",
+                "  ".repeat(depth),
+                depth.to_string().bright_black(),
+                name.bright_cyan(),
+                format!("{:?}", entity).bright_magenta(),
+                health_str,
+                parent_str
+            );
+        }
+    }
+}
+
+// Seeded on startup; saved aliases load on top and may override them
+const DEFAULT_ALIASES: [(&str, &str); 2] = [("ls", "list"), ("q", "quit")];
+
+// Default `--log` target, next to the history file
+fn audit_log_path(history_path: &Path) -> PathBuf {
+    let mut path = history_path.as_os_str().to_owned();
+    path.push(".log");
+    PathBuf::from(path)
+}
+
+// Aliases are saved next to the history file, e.g. ~/.flax_repl_history.aliases
+fn aliases_path(history_path: &Path) -> PathBuf {
+    let mut path = history_path.as_os_str().to_owned();
+    path.push(".aliases");
+    PathBuf::from(path)
+}
+
+// Commands that only read the world, and so are safe to re-run from `watch`.
+// `dump` is excluded because it advances the change trackers
+const READ_ONLY_COMMANDS: [&str; 12] = [
+    "get",
+    "top",
+    "recent",
+    "histogram",
+    "diff",
+    "whereis",
+    "find",
+    "list",
+    "stats",
+    "components",
+    "tree",
+    "echo",
+];
+
+// How many `source` calls may be active at once
+const MAX_SOURCE_DEPTH: usize = 8;
+
+// Whether a command belongs in the audit log. Anything that changed the world is
+// logged, except `source` itself since the lines it runs are logged one by one.
+// Checkpoints and autodeath leave the world alone but change what later commands
+// do, so replay needs them too.
+fn should_log(parts: &[&str], world_changed: bool) -> bool {
+    match parts {
+        ["source", ..] => false,
+        ["checkpoint", _] | ["set", "autodeath", _] => true,
+        _ => world_changed && !is_read_only(parts) && !matches!(parts, ["dump", ..]),
+    }
+}
+
+fn is_read_only(parts: &[&str]) -> bool {
+    parts
+        .first()
+        .is_some_and(|command| READ_ONLY_COMMANDS.contains(command))
+}
+
+// Names are command arguments: inner spaces are fine since they can be quoted, but
+// other whitespace, surrounding spaces and control characters would make an entity
+// hard to reach or garble the coloured output
+fn validate_entity_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("Entity name must not be empty".to_string());
+    }
+    if name.trim() != name {
+        return Err(format!(
+            "Entity name {:?} must not start or end with whitespace",
+            name
+        ));
+    }
+    if let Some(bad) = name
+        .chars()
+        .find(|&c| c.is_control() || (c.is_whitespace() && c != ' '))
+    {
+        return Err(format!(
+            "Entity name {:?} contains {:?}; only plain spaces are allowed besides printable characters",
+            name, bad
+        ));
+    }
+    Ok(())
+}
+
+fn is_glob(pattern: &str) -> bool {
+    pattern.contains(['*', '?'])
+}
+
+// Shell-style matching over whole names: `*` is any run of characters (including
+// none) and `?` exactly one. On a mismatch, the most recent `*` absorbs one more
+// character and matching resumes from there
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Pattern index after the last `*`, and the text index it currently resumes from
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p + 1, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star_p, star_t)) => {
+                    backtrack = Some((star_p, star_t + 1));
+                    p = star_p;
+                    t = star_t + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    // Trailing stars match the empty rest
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+// Asks a y/N question on stdin. Without a terminal to answer (a piped or redirected
+// script) nothing is read, so the script's next line isn't consumed, and the
+// answer is no
+fn confirm(question: &str) -> bool {
+    use std::io::IsTerminal;
+
+    print!("{} {} ", question.yellow(), "[y/N]".bright_black());
+    if !std::io::stdin().is_terminal() {
+        println!(
+            "{}",
+            "n (stdin is not a terminal, use -f to skip)".bright_black()
+        );
+        return false;
+    }
+    std::io::stdout().flush().ok();
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer).ok();
+    matches!(answer.trim(), "y" | "Y" | "yes")
+}
+
+// Index/generation split of an entity id, so recycled slots are easy to spot
+// after `rm` and re-`add`
+fn entity_slot(entity: Entity) -> String {
+    format!("index {} gen {}", entity.index(), entity.r#gen())
+}
+
+// Prints `lines` a screenful at a time, waiting for Enter between pages
+// (`q` stops early). Each line carries its own color codes, so pages never
+// split a colored span. Like `confirm`, it never reads from a piped script, and
+// output that isn't going to a screen gets no pauses either.
+fn page_lines(lines: &[String]) {
+    use std::io::IsTerminal;
+
+    if !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+        for line in lines {
+            println!("{}", line);
+        }
+        return;
+    }
+
+    let page_size = terminal_size::terminal_size()
+        .map(|(_, terminal_size::Height(rows))| (rows as usize).saturating_sub(2))
+        .filter(|rows| *rows > 0)
+        .unwrap_or(40);
+
+    let mut pages = lines.chunks(page_size).peekable();
+    while let Some(page) = pages.next() {
+        for line in page {
+            println!("{}", line);
+        }
+        if pages.peek().is_none() {
+            break;
+        }
+
+        print!("{}", "-- more --".bright_black().reversed());
+        std::io::stdout().flush().ok();
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer).ok();
+        if answer.trim() == "q" {
+            break;
+        }
+    }
+}
+
+// Help for one command: every accepted form, a longer description for
+// `help [command]`, and example lines
+struct HelpEntry {
+    usage: &'static [(&'static str, &'static str)],
+    description: &'static str,
+    examples: &'static [&'static str],
+}
+
+// In listing order; `help_index` keys the same entries by command name
+const HELP_ENTRIES: &[(&str, HelpEntry)] = &[
+    (
+        "add entity",
+        HelpEntry {
+            usage: &[("add entity [name]", "Add a new entity with the given name")],
+            description: "Quote names containing spaces. Names must be unique and may not start or end with whitespace.",
+            examples: &["add entity goblin", "add entity \"Frodo Baggins\""],
+        },
+    ),
+    (
+        "spawn",
+        HelpEntry {
+            usage: &[(
+                "spawn [prefix] [count]",
+                "Add [count] entities named [prefix]0, [prefix]1, ...",
+            )],
+            description: "All or nothing: if any name is taken, the entities created so far are removed again.",
+            examples: &["spawn orc 5"],
+        },
+    ),
+    (
+        "clone",
+        HelpEntry {
+            usage: &[(
+                "clone [src] [newname] [with parents]",
+                "Copy an entity's components, optionally its parents too",
+            )],
+            description: "Copies health, max health, mana, regen, spell count, tags and floats. Children are never copied.",
+            examples: &["clone orc0 orc_chief", "clone frodo sam with parents"],
+        },
+    ),
+    (
+        "get",
+        HelpEntry {
+            usage: &[(
+                "get [name...]",
+                "Get information about one or more entities",
+            )],
+            description: "Shows health and mana bars, regen, parents, children and tags. Unknown names are reported without skipping the rest. Names may be patterns where * matches any run of characters and ? one character.",
+            examples: &["get frodo", "get frodo sam gandalf", "get orc*"],
+        },
+    ),
+    (
+        "diff",
+        HelpEntry {
+            usage: &[(
+                "diff [name1] [name2]",
+                "Compare two entities' health, mana and relations side by side",
+            )],
+            description: "Rows that differ are highlighted; a missing component shows as —.",
+            examples: &["diff orc0 orc1"],
+        },
+    ),
+    (
+        "whereis",
+        HelpEntry {
+            usage: &[(
+                "whereis [name]",
+                "List the components and relations on an entity",
+            )],
+            description: "Lists each known component, plus every relation with its target's name.",
+            examples: &["whereis frodo"],
+        },
+    ),
+    (
+        "set-relation",
+        HelpEntry {
+            usage: &[
+                (
+                    "set-relation child [name] parent [name]",
+                    "Create a parent-child relation",
+                ),
+                (
+                    "set-relation child [name] parent [name] as [label]",
+                    "Create a labelled parent-child relation",
+                ),
+            ],
+            description: "The label defaults to 'child' and may span several words.",
+            examples: &[
+                "set-relation child frodo parent bilbo",
+                "set-relation child frodo parent bilbo as heir of bag end",
+            ],
+        },
+    ),
+    (
+        "rm-relation",
+        HelpEntry {
+            usage: &[(
+                "rm-relation child [name] parent [name]",
+                "Remove a parent-child relation",
+            )],
+            description: "Removes both the child_of and has_child sides.",
+            examples: &["rm-relation child frodo parent bilbo"],
+        },
+    ),
+    (
+        "reparent",
+        HelpEntry {
+            usage: &[(
+                "reparent [child] [new_parent]",
+                "Move an entity, with its subtree, under a new parent",
+            )],
+            description: "Replaces any existing parents in one step and keeps the old edge label. Refused if the new parent is inside the child's subtree.",
+            examples: &["reparent frodo gandalf"],
+        },
+    ),
+    (
+        "set health",
+        HelpEntry {
+            usage: &[(
+                "set health [name] [number]",
+                "Set health value for an entity",
+            )],
+            description: "Health at 0 or below announces a death, and removes the entity when autodeath is on. The name may be a * / ? pattern to set every match.",
+            examples: &["set health frodo 80", "set health orc* 50"],
+        },
+    ),
+    (
+        "set max-health",
+        HelpEntry {
+            usage: &[(
+                "set max-health [name] [number]",
+                "Set the full health used by the health bar (default 100)",
+            )],
+            description: "Must be positive.",
+            examples: &["set max-health troll 300"],
+        },
+    ),
+    (
+        "set mana",
+        HelpEntry {
+            usage: &[
+                ("set mana [name] [number]", "Set mana value for an entity"),
+                (
+                    "set mana [name] [current] [maximum]",
+                    "Set current and maximum mana separately",
+                ),
+            ],
+            description: "With one number, current and maximum are both set to it. Current is clamped to [0, maximum]. The name may be a * / ? pattern to set every match.",
+            examples: &["set mana gandalf 100", "set mana gandalf 40 100"],
+        },
+    ),
+    (
+        "set regen",
+        HelpEntry {
+            usage: &[(
+                "set regen [name] [rate]",
+                "Set how much mana an entity regains per tick",
+            )],
+            description: "Negative rates drain mana. Entities without a rate don't regenerate.",
+            examples: &["set regen gandalf 5"],
+        },
+    ),
+    (
+        "set float",
+        HelpEntry {
+            usage: &[(
+                "set float [name] [key] [value]",
+                "Attach a named decimal value, such as a measurement",
+            )],
+            description: "Accepts scientific notation like 2.5e-3; NaN and infinities are rejected. Values are kept per key and shown by get with fixed precision.",
+            examples: &[
+                "set float probe temperature 21.75",
+                "set float probe mass 1.2e-5",
+            ],
+        },
+    ),
+    (
+        "tick",
+        HelpEntry {
+            usage: &[("tick [n]", "Apply mana regen once, or n times")],
+            description: "Mana stays within [0, maximum].",
+            examples: &["tick", "tick 10"],
+        },
+    ),
+    (
+        "set autodeath",
+        HelpEntry {
+            usage: &[(
+                "set autodeath [on|off]",
+                "Remove entities whose health drops to 0 or below",
+            )],
+            description: "Removal goes through the same path as rm, so relations are cleaned up.",
+            examples: &["set autodeath on"],
+        },
+    ),
+    (
+        "set thresholds",
+        HelpEntry {
+            usage: &[(
+                "set thresholds [good] [warn]",
+                "Change the health colour bands (default 75 30)",
+            )],
+            description: "Health above good is green, above warn yellow, otherwise red. good must exceed warn.",
+            examples: &["set thresholds 200 50"],
+        },
+    ),
+    (
+        "randomize health",
+        HelpEntry {
+            usage: &[(
+                "randomize health [min] [max] [seed]",
+                "Assign every entity a seeded random health",
+            )],
+            description: "The same seed always gives the same values.",
+            examples: &["randomize health 1 100 42"],
+        },
+    ),
+    (
+        "cast",
+        HelpEntry {
+            usage: &[
+                ("cast [spell] [caster]", "Cast using the spellbook cost"),
+                ("cast [spell] by [caster]", "Same, in long form"),
+                (
+                    "cast [spell] [caster] [cost]",
+                    "Cast a spell consuming mana",
+                ),
+                ("cast [spell] by [caster] for [cost]", "Same, in long form"),
+                (
+                    "cast [spell] [caster] [target] [cost]",
+                    "Damage (fireball, lightning) or heal (heal) a target",
+                ),
+            ],
+            description: "Without a cost, the spell must have one in the spellbook (see set-spell-cost). Fails without enough mana. Targeted spells change the target's health by the cost; healing stops at max health, and buffs like shield can only target the caster.",
+            examples: &[
+                "cast fireball gandalf",
+                "cast fireball by gandalf for 30",
+                "cast heal gandalf 10",
+                "cast fireball gandalf orc0 30",
+            ],
+        },
+    ),
+    (
+        "cast-aoe",
+        HelpEntry {
+            usage: &[(
+                "cast-aoe [spell] [caster] [cost]",
+                "Damage or heal every descendant of the caster",
+            )],
+            description: "Follows has_child edges below the caster and hits each descendant with health for the cost. The caster pays the cost once per target, up front, so nothing happens without enough mana for all of them.",
+            examples: &["cast-aoe heal gandalf 10", "cast-aoe fireball gandalf 25"],
+        },
+    ),
+    (
+        "set-spell-cost",
+        HelpEntry {
+            usage: &[(
+                "set-spell-cost [spell] [cost]",
+                "Change a spell's spellbook cost",
+            )],
+            description: "Spell names are case-insensitive.",
+            examples: &["set-spell-cost fireball 25"],
+        },
+    ),
+    (
+        "tag",
+        HelpEntry {
+            usage: &[("tag [name] [label]", "Label an entity with a tag")],
+            description: "Tags group entities without relations.",
+            examples: &["tag orc0 enemy"],
+        },
+    ),
+    (
+        "untag",
+        HelpEntry {
+            usage: &[("untag [name] [label]", "Remove a tag from an entity")],
+            description: "Removing the last tag removes the tags component.",
+            examples: &["untag orc0 enemy"],
+        },
+    ),
+    (
+        "find",
+        HelpEntry {
+            usage: &[
+                ("find tag [label]", "List the entities carrying a tag"),
+                (
+                    "find health [min] [max]",
+                    "List entities whose health is within the range",
+                ),
+                (
+                    "find castcount [min]",
+                    "List entities that cast at least min spells, most active first",
+                ),
+                ("find depleted", "List casters whose mana is at zero"),
+                (
+                    "find relation [substring]",
+                    "List parent -> child edges whose label contains the substring",
+                ),
+            ],
+            description: "The health range is inclusive. castcount defaults to a minimum of 1. The relation substring is case-sensitive and may contain spaces; without one every edge is listed.",
+            examples: &[
+                "find tag boss",
+                "find health 0 30",
+                "find castcount 3",
+                "find depleted",
+                "find relation mentor",
+            ],
+        },
+    ),
+    (
+        "watch",
+        HelpEntry {
+            usage: &[(
+                "watch [command]",
+                "Re-run a read-only command after every command",
+            )],
+            description: "Only read-only commands can be watched: {read_only}.",
+            examples: &["watch find health 0 30"],
+        },
+    ),
+    (
+        "unwatch",
+        HelpEntry {
+            usage: &[("unwatch", "Stop watching")],
+            description: "Clears the watched command.",
+            examples: &["unwatch"],
+        },
+    ),
+    (
+        "source",
+        HelpEntry {
+            usage: &[("source [path]", "Run every command in a file")],
+            description: "Blank lines and lines starting with # are skipped. A log written with --log [path] replays into the same world.",
+            examples: &["source session.log"],
+        },
+    ),
+    (
+        "checkpoint",
+        HelpEntry {
+            usage: &[(
+                "checkpoint [name]",
+                "Keep an in-memory copy of every entity and the spellbook",
+            )],
+            description: "Reusing a name overwrites the checkpoint.",
+            examples: &["checkpoint before_fight"],
+        },
+    ),
+    (
+        "restore",
+        HelpEntry {
+            usage: &[(
+                "restore [name]",
+                "Replace the world's entities with a checkpoint",
+            )],
+            description: "Entities get new ids; names and relations are restored.",
+            examples: &["restore before_fight"],
+        },
+    ),
+    (
+        "checkpoints",
+        HelpEntry {
+            usage: &[("checkpoints", "List saved checkpoints")],
+            description: "Shows each checkpoint with its entity count.",
+            examples: &["checkpoints"],
+        },
+    ),
+    (
+        "rm",
+        HelpEntry {
+            usage: &[
+                ("rm [name]", "Remove an entity"),
+                ("rm -f [name]", "Remove without asking for confirmation"),
+            ],
+            description: "Its relations are removed with it. Removing an entity with children, or a * / ? pattern matching more than one entity, asks for confirmation first; without a terminal on stdin the answer is no, so scripts should use -f.",
+            examples: &["rm orc0", "rm temp_*", "rm -f bilbo"],
+        },
+    ),
+    (
+        "dump",
+        HelpEntry {
+            usage: &[
+                ("dump", "Show all recent changes"),
+                ("dump added", "Show recently added entities"),
+                ("dump modified", "Show recently modified entities"),
+                ("dump removed", "Show recently removed entities"),
+            ],
+            description: "Changes are reported since the previous dump, grouped by the system that found them; dump modified shows old -> new health. A filter runs only that system, leaving the others' changes for later.",
+            examples: &["dump modified"],
+        },
+    ),
+    (
+        "mark-seen",
+        HelpEntry {
+            usage: &[(
+                "mark-seen",
+                "Acknowledge all pending changes without printing",
+            )],
+            description: "Drains the added and modified change queries, resetting their tracking cursor to now, and refreshes the health baseline. The next dump only shows changes made afterwards.",
+            examples: &["mark-seen"],
+        },
+    ),
+    (
+        "list",
+        HelpEntry {
+            usage: &[("list", "List all entities")],
+            description: "Shows each entity's id, slot and tags.",
+            examples: &["list"],
+        },
+    ),
+    (
+        "top",
+        HelpEntry {
+            usage: &[(
+                "top [field] [n] [asc]",
+                "Rank entities by health, mana, spells_cast or descendants",
+            )],
+            description: "Shows the top n (5 by default), with medals for the first three. asc shows the bottom n instead. Ties are ordered by name.",
+            examples: &["top health", "top spells_cast 3", "top mana 10 asc"],
+        },
+    ),
+    (
+        "recent",
+        HelpEntry {
+            usage: &[("recent [n]", "List the n most recently modified entities")],
+            description: "Ordered by last_modified, newest first, with the age of each change. Shows 10 by default; entities that were never modified come last.",
+            examples: &["recent", "recent 3"],
+        },
+    ),
+    (
+        "stats",
+        HelpEntry {
+            usage: &[("stats", "Summarize entity counts and health")],
+            description: "Includes the lowest, highest and average health.",
+            examples: &["stats"],
+        },
+    ),
+    (
+        "histogram",
+        HelpEntry {
+            usage: &[("histogram", "Chart how many entities carry each component")],
+            description: "Covers name, health, mana, last_modified, and entities with child_of or has_child edges. Bars are scaled to the largest count.",
+            examples: &["histogram"],
+        },
+    ),
+    (
+        "components",
+        HelpEntry {
+            usage: &[(
+                "components",
+                "Group entities into connected relation components",
+            )],
+            description: "Entities linked by any parent-child relation end up in the same group.",
+            examples: &["components"],
+        },
+    ),
+    (
+        "tree",
+        HelpEntry {
+            usage: &[
+                ("tree [dfs|bfs|topo]", "Show entity tree with DFS traversal"),
+                (
+                    "tree dfs [root_name]",
+                    "Show only the subtree under an entity",
+                ),
+            ],
+            description: "dfs draws the hierarchy; bfs groups entities by depth from the roots; topo lists parents before children.",
+            examples: &["tree", "tree bfs", "tree topo", "tree dfs bilbo"],
+        },
+    ),
+    (
+        "echo",
+        HelpEntry {
+            usage: &[("echo [message]", "Print a message to the console")],
+            description: "Useful for annotating scripted sessions.",
+            examples: &["echo round two"],
+        },
+    ),
+    (
+        "set output",
+        HelpEntry {
+            usage: &[(
+                "set output [json|human]",
+                "Print get, list, find and stats as JSON (or start with --json)",
+            )],
+            description: "JSON output is one compact document per line; errors from those commands become {\"error\": ...}.",
+            examples: &["set output json", "get frodo"],
+        },
+    ),
+    (
+        "set prompt",
+        HelpEntry {
+            usage: &[(
+                "set prompt [text]",
+                "Change the prompt (or start with --prompt); no text restores ►",
+            )],
+            description: "{count} becomes the number of entities, {checkpoints} the number of checkpoints and {output} the output mode. The prompt is re-rendered before every line.",
+            examples: &[
+                "set prompt [{count}]►",
+                "set prompt \"ecs ({output}) >\"",
+                "set prompt",
+            ],
+        },
+    ),
+    (
+        "set editmode",
+        HelpEntry {
+            usage: &[(
+                "set editmode [vi|emacs]",
+                "Switch keybindings (or start with --vi)",
+            )],
+            description: "Applies from the next prompt.",
+            examples: &["set editmode vi"],
+        },
+    ),
+    (
+        "alias",
+        HelpEntry {
+            usage: &[
+                ("alias", "List aliases"),
+                (
+                    "alias [name] [expansion]",
+                    "Make [name] run [expansion] when typed as the first word",
+                ),
+            ],
+            description: "Only the first word of a line is expanded, and only once. Aliases are saved next to the history file; ls and q are built in.",
+            examples: &["alias ll list", "alias kill set health"],
+        },
+    ),
+    (
+        "time",
+        HelpEntry {
+            usage: &[("time [on|off]", "Print how long each command takes")],
+            description: "Timings are in microseconds.",
+            examples: &["time on"],
+        },
+    ),
+    (
+        "help",
+        HelpEntry {
+            usage: &[
+                ("help", "Show this help message"),
+                ("help [command]", "Show usage and examples for one command"),
+            ],
+            description: "help set shows every set command.",
+            examples: &["help cast", "help set"],
+        },
+    ),
+    (
+        "quit",
+        HelpEntry {
+            usage: &[("quit", "Exit the REPL")],
+            description: "exit works too.",
+            examples: &["quit"],
+        },
+    ),
+];
+
+fn help_index() -> HashMap<&'static str, &'static HelpEntry> {
+    HELP_ENTRIES
+        .iter()
+        .map(|(command, entry)| (*command, entry))
+        .collect()
+}
+
+fn print_help() {
+    println!("{}", "Available commands:".cyan().bold());
+    for (_, entry) in HELP_ENTRIES {
+        for (usage, summary) in entry.usage {
+            println!("  {} - {}", usage.green(), summary);
+        }
+    }
+}
+
+// Descriptions are consts, so lists kept elsewhere are spliced in when printed
+fn expand_description(description: &str) -> String {
+    description.replace("{read_only}", &READ_ONLY_COMMANDS.join(", "))
+}
+
+// `help set` covers every `set ...` entry when there's no exact match
+fn print_command_help(topic: &str) {
+    let matches: Vec<(&str, &HelpEntry)> = match help_index().get(topic) {
+        Some(entry) => vec![(topic, *entry)],
+        None => HELP_ENTRIES
+            .iter()
+            .filter(|(command, _)| command.starts_with(&format!("{} ", topic)))
+            .map(|(command, entry)| (*command, entry))
+            .collect(),
+    };
 
-                if !parents.is_empty() {
-                    format!(" ← {}", parents.join(", ")).yellow().to_string()
-                } else {
-                    String::new()
-                }
-            } else {
-                String::new()
-            };
+    if matches.is_empty() {
+        println!(
+            "{} No help for '{}'. Type 'help' for available commands.",
+            "✗".red().bold(),
+            topic.red()
+        );
+        return;
+    }
 
-            println!(
-                "  • {} ({}){}{}",
-                name.bright_cyan(),
-                format!("{:?}", entity).bright_magenta(),
-                health_str,
-                parent_str
-            );
+    for (command, entry) in matches {
+        println!("{}", command.cyan().bold());
+        for (usage, summary) in entry.usage {
+            println!("  {} - {}", usage.green(), summary);
+        }
+        println!("  {}", expand_description(entry.description));
+        println!("  {}", "Examples:".bright_black());
+        for example in entry.examples {
+            println!("    {}", example.bright_white());
         }
     }
 }
 
-fn print_help() {
-    println!("{}", "Available commands:".cyan().bold());
-    println!(
-        "  {} - Add a new entity with the given name",
-        "add entity [name]".green()
-    );
-    println!(
-        "  {} - Get information about an entity",
-        "get [name]".green()
-    );
-    println!(
-        "  {} - Create a parent-child relation",
-        "set-relation child [name] parent [name]".green()
-    );
-    println!(
-        "  {} - Remove a parent-child relation",
-        "rm-relation child [name] parent [name]".green()
-    );
-    println!(
-        "  {} - Set health value for an entity",
-        "set health [name] [number]".green()
-    );
-    println!(
-        "  {} - Set mana value for an entity",
-        "set mana [name] [number]".green()
-    );
-    println!(
-        "  {} - Cast a spell consuming mana",
-        "cast [spell] [caster] [cost]".green()
-    );
-    println!("  {} - Remove an entity", "rm [name]".green());
-    println!("  {} - Show all recent changes", "dump".green());
-    println!("  {} - Show recently added entities", "dump added".green());
-    println!(
-        "  {} - Show recently modified entities",
-        "dump modified".green()
-    );
-    println!(
-        "  {} - Show recently removed entities",
-        "dump removed".green()
-    );
-    println!("  {} - List all entities", "list".green());
-    println!(
-        "  {} - Show entity tree with DFS traversal",
-        "tree [dfs|topo]".green()
-    );
-    println!(
-        "  {} - Print a message to the console",
-        "echo [message]".green()
-    );
-    println!("  {} - Show this help message", "help".green());
-    println!("  {} - Exit the REPL", "quit".green());
+// History file location, overridable with FLAX_REPL_HISTORY
+fn history_path() -> PathBuf {
+    if let Some(path) = std::env::var_os("FLAX_REPL_HISTORY") {
+        return PathBuf::from(path);
+    }
+
+    std::env::var_os("HOME")
+        .map(PathBuf::from)
+        .unwrap_or_default()
+        .join(".flax_repl_history")
+}
+
+// Colour only when a person is likely reading: NO_COLOR (any non-empty value) or a
+// redirected stdout turns it off, while CLICOLOR_FORCE keeps `colored`'s own say
+fn configure_colors() {
+    use std::io::IsTerminal;
+
+    if std::env::var_os("CLICOLOR_FORCE").is_some_and(|force| force != "0") {
+        return;
+    }
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    if no_color || !std::io::stdout().is_terminal() {
+        colored::control::set_override(false);
+    }
 }
 
 fn main() -> rustyline::Result<()> {
+    configure_colors();
     let mut state = ReplState::new();
+    // `--prompt TEXT` replaces the prompt template, tokens included
+    if let Some(template) = std::env::args().skip_while(|arg| arg != "--prompt").nth(1) {
+        state.prompt = template;
+    }
+    // `--panes` explores the pane/dataset model instead of the entity REPL
+    let pane_mode = std::env::args().any(|arg| arg == "--panes");
     let h = MyHelper {
-        completer: MyCompleter::new(),
+        completer: if pane_mode {
+            MyCompleter::for_panes()
+        } else {
+            MyCompleter::new()
+        },
         highlighter: MatchingBracketHighlighter::new(),
         hinter: HistoryHinter::new(),
         validator: MatchingBracketValidator::new(),
         colored_prompt: format!("{} ", "►".bright_green().bold()),
     };
 
+    // `--vi` switches to vi keybindings; `set editmode` can change it later
+    let edit_mode = if std::env::args().any(|arg| arg == "--vi") {
+        EditMode::Vi
+    } else {
+        EditMode::Emacs
+    };
+
     let config = Config::builder()
-        .edit_mode(EditMode::Emacs)
+        .edit_mode(edit_mode)
         .completion_type(rustyline::config::CompletionType::Circular)
         .auto_add_history(true)
         .build();
@@ -1132,32 +5381,71 @@ fn main() -> rustyline::Result<()> {
     let mut rl = Editor::with_config(config)?;
     rl.set_helper(Some(h));
 
+    // A missing history file on first run is fine, we just start empty
+    let history_path = history_path();
+    rl.load_history(&history_path).ok();
+    let aliases_path = aliases_path(&history_path);
+    state.load_aliases(&aliases_path);
+
+    // `--log [path]` appends every mutating command, replayable with `source`
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(index) = args.iter().position(|arg| arg == "--log") {
+        let log_path = match args.get(index + 1) {
+            Some(path) if !path.starts_with("--") => PathBuf::from(path),
+            _ => audit_log_path(&history_path),
+        };
+        if let Err(err) = state.open_audit_log(&log_path) {
+            println!(
+                "{} Failed to open audit log {}: {}",
+                "⚠".yellow().bold(),
+                log_path.display(),
+                err
+            );
+        }
+    }
+
     // Bind Command-E (Alt-E on some systems) to complete and move to end of line
     rl.bind_sequence(KeyEvent::alt('e'), Cmd::CompleteHint);
 
     // Also bind it to Ctrl-E for compatibility
     rl.bind_sequence(KeyEvent::ctrl('E'), Cmd::CompleteHint);
 
-    println!("{}", "╔═══════════════════════════╗".bright_magenta());
-    println!("{}", "║     Flax ECS REPL v1.0   ║".bright_magenta().bold());
-    println!("{}", "╚═══════════════════════════╝".bright_magenta());
-    println!("{}\n", "Type 'help' for available commands".bright_black());
-    println!(
-        "{}",
-        "Tab completion is available for commands and entity names!".bright_cyan()
-    );
-    println!(
-        "{}",
-        "Use Tab to cycle completions, Cmd-E/Ctrl-E for hint completion".bright_black()
-    );
+    if pane_mode {
+        pane_repl::run(&mut rl);
+        save_history(&mut rl, &history_path);
+        return Ok(());
+    }
+
+    // Scripts driving `--json` read stdout line by line, so skip the banner for them
+    if std::env::args().any(|arg| arg == "--json") {
+        state.output = OutputMode::Json;
+    } else {
+        println!("{}", "╔═══════════════════════════╗".bright_magenta());
+        println!("{}", "║     Flax ECS REPL v1.0   ║".bright_magenta().bold());
+        println!("{}", "╚═══════════════════════════╝".bright_magenta());
+        println!("{}\n", "Type 'help' for available commands".bright_black());
+        println!(
+            "{}",
+            "Tab completion is available for commands and entity names!".bright_cyan()
+        );
+        println!(
+            "{}",
+            "Use Tab to cycle completions, Cmd-E/Ctrl-E for hint completion".bright_black()
+        );
+    }
 
     loop {
+        // The prompt may show live state, so render it fresh for every line
+        let prompt = state.render_prompt();
+
         // Update entity completion list
         if let Some(helper) = rl.helper_mut() {
             helper.completer.update_entities(&state.entity_names);
+            helper.completer.update_float_keys(state.float_keys());
+            helper.colored_prompt = format!("{} ", prompt.bright_green().bold());
         }
 
-        let readline = rl.readline("► ");
+        let readline = rl.readline(&format!("{} ", prompt));
         match readline {
             Ok(line) => {
                 let input = line.trim();
@@ -1166,182 +5454,45 @@ fn main() -> rustyline::Result<()> {
                 }
                 rl.add_history_entry(input).ok();
 
-                let parts: Vec<&str> = input.split_whitespace().collect();
+                // Malformed quoting is reported by execute_line below, which also
+                // expands aliases itself, so this expansion only serves `q` and friends
+                let tokens = state.expand_alias(tokenize(input).unwrap_or_default());
+                let parts: Vec<&str> = tokens.iter().map(String::as_str).collect();
 
+                // Loop control and editor settings live here; every other command
+                // goes through ReplState::execute_line
                 match parts.as_slice() {
                     ["quit"] | ["exit"] => {
                         println!("{}", "👋 Goodbye!".bright_cyan());
                         break;
                     }
-                    ["help"] => {
-                        print_help();
-                    }
-                    ["add", "entity", name] => match state.add_entity(name) {
-                        Ok(entity) => {
-                            println!(
-                                "{} Created entity '{}' with id {}",
-                                "✓".green().bold(),
-                                name.bright_cyan(),
-                                format!("{:?}", entity).bright_magenta()
-                            );
-                        }
-                        Err(e) => println!("{} {}", "✗".red().bold(), e.red()),
-                    },
-                    ["get", name] => match state.get_entity_info(name) {
-                        Ok(info) => print!("{}", info),
-                        Err(e) => println!("{} {}", "✗".red().bold(), e.red()),
-                    },
-                    ["rm", name] => match state.remove_entity(name) {
-                        Ok(_) => {
-                            println!(
-                                "{} Removed entity '{}'",
-                                "✓".green().bold(),
-                                name.bright_cyan()
-                            );
-                        }
-                        Err(e) => println!("{} {}", "✗".red().bold(), e.red()),
-                    },
-                    ["set-relation", "child", child_name, "parent", parent_name] => {
-                        match state.add_relation(child_name, parent_name) {
-                            Ok(_) => {
-                                println!(
-                                    "{} Created relation: {} {} {} {}",
-                                    "✓".green().bold(),
-                                    child_name.bright_cyan(),
-                                    "is child of".white(),
-                                    parent_name.bright_yellow(),
-                                    "🔗".bright_blue()
-                                );
-                            }
-                            Err(e) => println!("{} {}", "✗".red().bold(), e.red()),
-                        }
-                    }
-                    [
-                        "rm-relation",
-                        "child",
-                        child_name,
-                        "parent",
-                        parent_name,
-                    ] => match state.remove_relation(child_name, parent_name) {
-                        Ok(_) => {
-                            println!(
-                                "{} Removed relation: {} {} {} {}",
-                                "✓".green().bold(),
-                                child_name.bright_cyan(),
-                                "is no longer child of".white(),
-                                parent_name.bright_yellow(),
-                                "✂️".red()
-                            );
-                        }
-                        Err(e) => println!("{} {}", "✗".red().bold(), e.red()),
-                    },
-                    ["set", "health", name, number_str] => match number_str.parse::<i32>() {
-                        Ok(health_value) => match state.set_health(name, health_value) {
-                            Ok(_) => {
-                                let health_icon = if health_value > 75 {
-                                    "💚"
-                                } else if health_value > 30 {
-                                    "💛"
-                                } else {
-                                    "❤️"
-                                };
-                                println!(
-                                    "{} Set health of '{}' to {} {}",
-                                    "✓".green().bold(),
-                                    name.bright_cyan(),
-                                    health_value.to_string().bright_green(),
-                                    health_icon
-                                );
-                            }
-                            Err(e) => println!("{} {}", "✗".red().bold(), e.red()),
-                        },
-                        Err(_) => println!(
-                            "{} Invalid health value '{}', must be a number",
-                            "✗".red().bold(),
-                            number_str.red()
-                        ),
-                    },
-                    ["set", "mana", name, number_str] => match number_str.parse::<i32>() {
-                        Ok(mana_value) => match state.set_mana(name, mana_value) {
-                            Ok(_) => {
+                    ["set", "editmode", mode] => {
+                        // The Editor re-reads its config on every readline, so the
+                        // new mode applies from the next prompt without rebuilding it
+                        let edit_mode = match *mode {
+                            "vi" => Some(EditMode::Vi),
+                            "emacs" => Some(EditMode::Emacs),
+                            _ => None,
+                        };
+                        match edit_mode {
+                            Some(edit_mode) => {
+                                rl.set_edit_mode(edit_mode);
                                 println!(
-                                    "{} {} now has {} mana! {}",
+                                    "{} Edit mode set to {}",
                                     "✓".green().bold(),
-                                    name.bright_cyan(),
-                                    mana_value.to_string().bright_blue(),
-                                    "🔮".bright_magenta()
+                                    mode.bright_cyan()
                                 );
                             }
-                            Err(e) => println!("{} {}", "✗".red().bold(), e.red()),
-                        },
-                        Err(_) => println!(
-                            "{} Invalid mana value '{}', must be a number",
-                            "✗".red().bold(),
-                            number_str.red()
-                        ),
-                    },
-                    ["cast", spell_name, "by", caster_name, "for", cost_str]
-                    | ["cast", spell_name, caster_name, cost_str] => {
-                        match cost_str.parse::<i32>() {
-                            Ok(mana_cost) => {
-                                match state.cast_spell(caster_name, spell_name, mana_cost) {
-                                    Ok(_) => {
-                                        // Success message is printed in cast_spell method
-                                    }
-                                    Err(e) => println!("{} {}", "✗".red().bold(), e.red()),
-                                }
-                            }
-                            Err(_) => println!(
-                                "{} Invalid mana cost '{}', must be a number",
+                            None => println!(
+                                "{} Invalid edit mode '{}', use 'vi' or 'emacs'",
                                 "✗".red().bold(),
-                                cost_str.red()
+                                mode.red()
                             ),
                         }
                     }
-                    ["dump"] => {
-                        state.dump_changes(None);
-                    }
-                    ["dump", "added"] => {
-                        state.dump_changes(Some("added"));
-                    }
-                    ["dump", "modified"] => {
-                        state.dump_changes(Some("modified"));
-                    }
-                    ["dump", "removed"] => {
-                        state.dump_changes(Some("removed"));
-                    }
-                    ["list"] => {
-                        if state.entity_names.is_empty() {
-                            println!("{}", "No entities created yet".yellow());
-                        } else {
-                            println!("{}", "📋 Entities:".cyan().bold());
-                            for (name, entity) in &state.entity_names {
-                                println!(
-                                    "  {} {} ({})",
-                                    "•".bright_blue(),
-                                    name.bright_cyan(),
-                                    format!("{:?}", entity).bright_magenta()
-                                );
-                            }
-                        }
-                    }
-                    ["tree", mode] => {
-                        state.show_tree(mode);
-                    }
-                    ["tree"] => {
-                        // Default to DFS if no mode specified
-                        state.show_tree("dfs");
-                    }
-                    ["echo", message @ ..] => {
-                        // Join all the remaining parts as the message
-                        let full_message = message.join(" ");
-                        println!("{}", full_message.bright_white());
-                    }
-                    _ => {
-                        println!("{} Unknown command: '{}'", "⚠".yellow().bold(), input.red());
-                        println!("{}", "Type 'help' for available commands".bright_black());
-                    }
+                    _ => state.execute_line(input),
                 }
+                state.run_watch();
             }
             Err(ReadlineError::Interrupted) => {
                 println!("CTRL-C");
@@ -1357,5 +5508,471 @@ fn main() -> rustyline::Result<()> {
             }
         }
     }
+
+    // Every exit path breaks out of the loop, so saving here covers them all
+    save_history(&mut rl, &history_path);
+    if let Err(err) = state.save_aliases(&aliases_path) {
+        println!(
+            "{} Failed to save aliases to {}: {}",
+            "⚠".yellow().bold(),
+            aliases_path.display(),
+            err
+        );
+    }
     Ok(())
 }
+
+fn save_history(rl: &mut Editor<MyHelper, DefaultHistory>, history_path: &Path) {
+    if let Err(err) = rl.save_history(history_path) {
+        println!(
+            "{} Failed to save history to {}: {}",
+            "⚠".yellow().bold(),
+            history_path.display(),
+            err
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn health_of(state: &ReplState, name: &str) -> Option<i32> {
+        let entity = state.get_entity(name).unwrap();
+        state.world.get(entity, health()).ok().map(|h| *h)
+    }
+
+    fn mana_of(state: &ReplState, name: &str) -> i32 {
+        let entity = state.get_entity(name).unwrap();
+        state.world.get(entity, mana()).unwrap().current
+    }
+
+    #[test]
+    fn fireball_damages_target_by_its_cost() {
+        let mut state = ReplState::new();
+        state.add_entity("wizard").unwrap();
+        state.add_entity("goblin").unwrap();
+        state.set_mana("wizard", 100).unwrap();
+        state.set_health("goblin", 100).unwrap();
+
+        let after = state
+            .cast_spell_on("wizard", "goblin", "fireball", 30)
+            .unwrap();
+
+        assert_eq!(after, Some(70));
+        assert_eq!(health_of(&state, "goblin"), Some(70));
+        assert_eq!(mana_of(&state, "wizard"), 70);
+    }
+
+    #[test]
+    fn shield_on_self_needs_no_health() {
+        let mut state = ReplState::new();
+        state.add_entity("wizard").unwrap();
+        state.set_mana("wizard", 50).unwrap();
+
+        let after = state
+            .cast_spell_on("wizard", "wizard", "shield", 15)
+            .unwrap();
+
+        assert_eq!(after, None);
+        assert_eq!(health_of(&state, "wizard"), None);
+        assert_eq!(mana_of(&state, "wizard"), 35);
+    }
+
+    #[test]
+    fn shield_on_another_entity_is_rejected() {
+        let mut state = ReplState::new();
+        state.add_entity("wizard").unwrap();
+        state.add_entity("knight").unwrap();
+        state.set_mana("wizard", 50).unwrap();
+
+        assert!(
+            state
+                .cast_spell_on("wizard", "knight", "shield", 15)
+                .is_err()
+        );
+        assert_eq!(mana_of(&state, "wizard"), 50);
+    }
+
+    #[test]
+    fn target_without_health_is_rejected_before_mana_is_spent() {
+        let mut state = ReplState::new();
+        state.add_entity("wizard").unwrap();
+        state.add_entity("rock").unwrap();
+        state.set_mana("wizard", 100).unwrap();
+
+        let err = state
+            .cast_spell_on("wizard", "rock", "fireball", 30)
+            .unwrap_err();
+
+        assert!(err.contains("has no health"), "{}", err);
+        assert_eq!(mana_of(&state, "wizard"), 100);
+    }
+
+    #[test]
+    fn insufficient_mana_is_rejected_without_changes() {
+        let mut state = ReplState::new();
+        state.add_entity("wizard").unwrap();
+        state.add_entity("goblin").unwrap();
+        state.set_mana("wizard", 10).unwrap();
+        state.set_health("goblin", 100).unwrap();
+
+        let err = state
+            .cast_spell_on("wizard", "goblin", "fireball", 30)
+            .unwrap_err();
+
+        assert!(err.contains("doesn't have enough mana"), "{}", err);
+        assert_eq!(mana_of(&state, "wizard"), 10);
+        assert_eq!(health_of(&state, "goblin"), Some(100));
+    }
+
+    #[test]
+    fn heal_restores_its_cost_up_to_max_health() {
+        let mut state = ReplState::new();
+        state.add_entity("cleric").unwrap();
+        state.add_entity("knight").unwrap();
+        state.set_mana("cleric", 100).unwrap();
+        state.set_health("knight", 40).unwrap();
+
+        assert_eq!(
+            state.cast_spell_on("cleric", "knight", "heal", 25),
+            Ok(Some(65))
+        );
+        assert_eq!(mana_of(&state, "cleric"), 75);
+
+        // 65 + 50 would overshoot the default maximum
+        assert_eq!(
+            state.cast_spell_on("cleric", "knight", "heal", 50),
+            Ok(Some(DEFAULT_MAX_HEALTH))
+        );
+        assert_eq!(health_of(&state, "knight"), Some(DEFAULT_MAX_HEALTH));
+        assert_eq!(mana_of(&state, "cleric"), 25);
+
+        // An explicit maximum caps the heal too
+        state.set_max_health("knight", 120).unwrap();
+        assert_eq!(
+            state.cast_spell_on("cleric", "knight", "heal", 25),
+            Ok(Some(120))
+        );
+    }
+
+    #[test]
+    fn clone_copies_values_and_stays_independent() {
+        let mut state = ReplState::new();
+        state.add_entity("tower").unwrap();
+        state.add_entity("wizard").unwrap();
+        state.set_health("wizard", 80).unwrap();
+        state.store_mana("wizard", 40, 60).unwrap();
+        state.tag_entity("wizard", "boss").unwrap();
+        state.set_float("wizard", "speed", 1.5).unwrap();
+        state.cast_spell("wizard", "shield", 5).unwrap();
+        state.add_relation("wizard", "tower", "guard").unwrap();
+
+        let source = state.get_entity("wizard").unwrap();
+        let copy = state.clone_entity("wizard", "apprentice", true).unwrap();
+
+        // Children aren't copied, so everything else reads the same
+        let source_summary = state.entity_summary(source);
+        let copy_summary = state.entity_summary(copy);
+        assert_eq!(copy_summary, source_summary);
+        assert_eq!(copy_summary.health, Some(80));
+        assert_eq!(copy_summary.tags, vec!["boss".to_string()]);
+        assert_eq!(
+            copy_summary.floats,
+            BTreeMap::from([("speed".to_string(), 1.5)])
+        );
+        assert_eq!(copy_summary.spells_cast, Some(1));
+        assert_eq!(copy_summary.parents, vec!["tower".to_string()]);
+        assert_eq!(
+            state.world.get(copy, mana()).unwrap().entity_name,
+            "apprentice"
+        );
+        let tower = state.get_entity("tower").unwrap();
+        assert_eq!(
+            state.world.get(tower, has_child(copy)).unwrap().label,
+            "guard"
+        );
+
+        state.set_health("apprentice", 10).unwrap();
+        state.store_mana("apprentice", 5, 60).unwrap();
+        state.untag_entity("apprentice", "boss").unwrap();
+        state.set_float("apprentice", "speed", 3.0).unwrap();
+        state.cast_spell("apprentice", "shield", 5).unwrap();
+
+        assert_eq!(state.entity_summary(source), source_summary);
+    }
+
+    #[test]
+    fn autodeath_despawns_entity_at_zero_health() {
+        let mut state = ReplState::new();
+        state.autodeath = true;
+        let entity = state.add_entity("goblin").unwrap();
+
+        assert!(state.set_health("goblin", 0).unwrap());
+        assert!(!state.entity_names.contains_key("goblin"));
+        assert!(!state.world.is_alive(entity));
+    }
+
+    #[test]
+    fn without_autodeath_entity_survives_negative_health() {
+        let mut state = ReplState::new();
+        let entity = state.add_entity("goblin").unwrap();
+
+        assert!(!state.set_health("goblin", -5).unwrap());
+        assert!(state.entity_names.contains_key("goblin"));
+        assert!(state.world.is_alive(entity));
+        assert_eq!(health_of(&state, "goblin"), Some(-5));
+    }
+
+    #[test]
+    fn mana_current_is_clamped_to_maximum() {
+        let mut state = ReplState::new();
+        state.add_entity("wizard").unwrap();
+
+        for (current, stored) in [(0, 0), (50, 50), (51, 50), (-1, 0)] {
+            assert_eq!(
+                state.set_mana_with_maximum("wizard", current, 50).unwrap(),
+                stored
+            );
+            assert_eq!(mana_of(&state, "wizard"), stored);
+        }
+        assert!(state.set_mana_with_maximum("wizard", 10, -1).is_err());
+    }
+
+    #[test]
+    fn bar_fill_is_clamped_to_the_bar() {
+        let max = DEFAULT_MAX_HEALTH;
+        assert_eq!(fill_percentage(0, max), 0);
+        assert_eq!(fill_percentage(max, max), 100);
+        assert_eq!(fill_percentage(max + 1, max), 100);
+        assert_eq!(fill_percentage(-1, max), 0);
+        assert_eq!(fill_percentage(10, 0), 0);
+
+        let (filled, empty) = bar_segments(fill_percentage(max + 1, max));
+        assert_eq!((filled.chars().count(), empty.chars().count()), (10, 0));
+    }
+
+    #[test]
+    fn relation_helpers_name_parents_and_labelled_children() {
+        let mut state = ReplState::new();
+        state.add_entity("shire").unwrap();
+        state.add_entity("bag_end").unwrap();
+        state.add_entity("frodo").unwrap();
+        state.add_relation("frodo", "bag_end", "resident").unwrap();
+        state.add_relation("bag_end", "shire", "hole").unwrap();
+
+        let frodo = state.get_entity("frodo").unwrap();
+        let bag_end = state.get_entity("bag_end").unwrap();
+        assert_eq!(state.parent_names(frodo), vec!["bag_end".to_string()]);
+        assert_eq!(state.parent_names(bag_end), vec!["shire".to_string()]);
+        assert_eq!(
+            state.child_names_with_labels(bag_end),
+            vec![("frodo".to_string(), "resident".to_string())]
+        );
+        assert!(state.child_names_with_labels(frodo).is_empty());
+
+        // A parent without a name falls back to its id
+        let unnamed = Entity::builder().spawn(&mut state.world);
+        state
+            .world
+            .set(frodo, components::child_of(unnamed), ())
+            .unwrap();
+        let mut parents = state.parent_names(frodo);
+        parents.sort();
+        let mut expected = vec!["bag_end".to_string(), format!("{:?}", unnamed)];
+        expected.sort();
+        assert_eq!(parents, expected);
+    }
+
+    #[test]
+    fn glob_match_handles_stars_question_marks_and_literals() {
+        assert!(glob_match("player*", "player"));
+        assert!(glob_match("player*", "player_one"));
+        assert!(glob_match("*_one", "player_one"));
+        assert!(glob_match("p*r*e", "player_one"));
+        assert!(!glob_match("player*", "the_player"));
+
+        assert!(glob_match("orc?", "orc1"));
+        assert!(!glob_match("orc?", "orc"));
+        assert!(!glob_match("orc?", "orc12"));
+
+        assert!(glob_match("frodo", "frodo"));
+        assert!(!glob_match("frodo", "frodo2"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("?", ""));
+    }
+
+    #[test]
+    fn resolve_pattern_expands_sorted_matches() {
+        let mut state = ReplState::new();
+        for name in ["temp_b", "temp_a", "keep"] {
+            state.add_entity(name).unwrap();
+        }
+
+        assert_eq!(state.resolve_pattern("temp_*"), vec!["temp_a", "temp_b"]);
+        assert_eq!(state.resolve_pattern("*"), vec!["keep", "temp_a", "temp_b"]);
+        assert!(state.resolve_pattern("nope*").is_empty());
+        // Plain names pass through even when no entity has them
+        assert_eq!(state.resolve_pattern("ghost"), vec!["ghost"]);
+    }
+
+    #[test]
+    fn audit_log_replays_into_an_identical_world() {
+        let log_path = std::env::temp_dir().join(format!("repl-audit-{}.log", std::process::id()));
+        std::fs::remove_file(&log_path).ok();
+
+        let mut recorded = ReplState::new();
+        recorded.open_audit_log(&log_path).unwrap();
+        for line in [
+            "add entity gandalf",
+            "add entity \"Frodo Baggins\"",
+            "spawn orc 3",
+            "get orc*",
+            "set health orc* 50",
+            "set mana gandalf 40 100",
+            "set-relation child \"Frodo Baggins\" parent gandalf as ward",
+            "cast fireball gandalf orc0 30",
+            "tag orc1 enemy",
+            "rm -f orc2",
+            "list",
+            "stats",
+        ] {
+            recorded.execute_line(line);
+        }
+        recorded.audit_log = None;
+
+        let mut replayed = ReplState::new();
+        replayed.source_file(log_path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&log_path).ok();
+
+        assert_eq!(replayed.gather_stats(), recorded.gather_stats());
+        let mut names: Vec<&String> = recorded.entity_names.keys().collect();
+        names.sort();
+        let mut replayed_names: Vec<&String> = replayed.entity_names.keys().collect();
+        replayed_names.sort();
+        assert_eq!(replayed_names, names);
+        // Relation timestamps are taken at replay time, so compare everything else
+        let summary_without_times = |state: &ReplState, name: &str| {
+            let mut summary = state.entity_summary(state.get_entity(name).unwrap());
+            for child in &mut summary.children {
+                child.relation.since = 0.0;
+            }
+            summary
+        };
+        for name in names {
+            assert_eq!(
+                summary_without_times(&replayed, name),
+                summary_without_times(&recorded, name),
+                "{} differs after replay",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn casting_to_zero_mana_marks_caster_depleted() {
+        let mut state = ReplState::new();
+        state.add_entity("gandalf").unwrap();
+        state.add_entity("saruman").unwrap();
+        state.set_mana("gandalf", 30).unwrap();
+        state.set_mana("saruman", 100).unwrap();
+        assert!(state.find_depleted().is_empty());
+
+        state.cast_spell("gandalf", "fireball", 30).unwrap();
+        state.cast_spell("saruman", "fireball", 30).unwrap();
+        assert_eq!(state.find_depleted(), vec!["gandalf".to_string()]);
+
+        // Refilling clears the flag again
+        state.set_mana("gandalf", 10).unwrap();
+        assert!(state.find_depleted().is_empty());
+    }
+
+    #[test]
+    fn negative_spell_cost_is_rejected() {
+        let mut state = ReplState::new();
+        let before = state.spell_cost("fireball").unwrap();
+
+        assert!(state.set_spell_cost("fireball", -5).is_err());
+        assert_eq!(state.spell_cost("fireball").unwrap(), before);
+        state.set_spell_cost("fireball", 0).unwrap();
+        assert_eq!(state.spell_cost("fireball").unwrap(), 0);
+    }
+
+    #[test]
+    fn add_relation_rejects_edges_that_close_a_loop() {
+        let mut state = ReplState::new();
+        for name in ["a", "b", "c"] {
+            state.add_entity(name).unwrap();
+        }
+        state.add_relation("b", "a", "child").unwrap();
+        state.add_relation("c", "b", "child").unwrap();
+
+        let err = state.add_relation("a", "c", "child").unwrap_err();
+        assert!(err.contains("cycle"), "{}", err);
+        assert!(state.add_relation("a", "a", "child").is_err());
+
+        let a = state.get_entity("a").unwrap();
+        let c = state.get_entity("c").unwrap();
+        assert!(state.parent_names(a).is_empty());
+        assert!(state.child_names_with_labels(c).is_empty());
+        assert!(find_child_of_cycle(&state.world).is_none());
+
+        // Re-adding an existing edge only relabels it
+        state.add_relation("c", "b", "heir").unwrap();
+        let b = state.get_entity("b").unwrap();
+        assert_eq!(
+            state.child_names_with_labels(b),
+            vec![("c".to_string(), "heir".to_string())]
+        );
+    }
+
+    #[test]
+    fn add_relation_ignores_unrelated_cycles() {
+        let mut state = ReplState::new();
+        for name in ["x", "y", "a", "b"] {
+            state.add_entity(name).unwrap();
+        }
+        // A loop between x and y, as a loaded world might already have
+        let x = state.get_entity("x").unwrap();
+        let y = state.get_entity("y").unwrap();
+        state.world.set(x, components::child_of(y), ()).unwrap();
+        state.world.set(y, components::child_of(x), ()).unwrap();
+
+        state.add_relation("b", "a", "child").unwrap();
+        assert!(state.add_relation("a", "b", "child").is_err());
+        // Hanging a new entity under the loop adds no cycle of its own
+        state.add_relation("a", "x", "child").unwrap();
+    }
+
+    #[test]
+    fn names_with_glob_characters_stay_addressable() {
+        let mut state = ReplState::new();
+        state.add_entity("a*").unwrap();
+        state.add_entity("ab").unwrap();
+
+        // The literal name wins over the pattern, and is not expanded again
+        state.execute_line("get a*");
+        state.execute_line("rm a*");
+        assert!(state.get_entity("a*").is_err());
+        assert!(state.get_entity("ab").is_ok());
+    }
+
+    #[test]
+    fn watch_help_lists_every_read_only_command() {
+        let description = expand_description(help_index()["watch"].description);
+        for command in READ_ONLY_COMMANDS {
+            assert!(description.contains(command), "{}", description);
+        }
+        assert!(!description.contains('{'), "{}", description);
+    }
+
+    #[test]
+    fn fuzzy_prefix_bonus_ignores_case() {
+        assert_eq!(fuzzy_score("list", "Li"), fuzzy_score("list", "li"));
+        assert_eq!(fuzzy_score("Lich", "li"), fuzzy_score("lich", "li"));
+        // A prefix in another case still outranks a closer-looking subsequence
+        let prefix = fuzzy_score("LIST_all", "li").unwrap();
+        let subsequence = fuzzy_score("blink", "Li").unwrap();
+        assert!(prefix > subsequence, "{} <= {}", prefix, subsequence);
+    }
+}
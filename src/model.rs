@@ -0,0 +1,58 @@
+// The pane/dataset domain model every example shares. Each example declares
+// `mod model;` next to `mod report;` and adds whatever its library needs on top,
+// e.g. a component impl for `DatasetId`, instead of redeclaring the types.
+
+// Identifies a dataset independently of the entity a library stores it in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DatasetId(pub &'static str);
+
+// Weight a subscription gets when the pane request doesn't give one
+pub const DEFAULT_DATASET_WEIGHT: f32 = 1.0;
+
+// Commands the demo queues, generic over each library's handle types. Examples
+// alias it as `Command` with their own PaneHandle and DatasetHandle, and report
+// the variants they don't implement instead of silently dropping them.
+#[derive(Debug, Clone)]
+pub enum Command<P, D> {
+    // Each dataset id is paired with the weight of the pane's subscription to it.
+    // Omitted dimensions fall back to the example's defaults; only the Flax
+    // example currently reads the weights and dimensions
+    CreatePaneWithDatasets {
+        datasets: Vec<(DatasetId, f32)>,
+        width: Option<u32>,
+        height: Option<u32>,
+    },
+    DeletePane {
+        pane: P,
+    },
+    // Drops one pane -> dataset subscription, leaving the pane's others
+    UnsubscribeDataset {
+        pane: P,
+        dataset: D,
+    },
+    // Overwrites the dimensions of an existing pane
+    ResizePane {
+        pane: P,
+        width: u32,
+        height: u32,
+    },
+}
+
+impl<P, D> Command<P, D> {
+    // CreatePaneWithDatasets with default weights and the example's default dimensions
+    pub fn create_pane(dataset_ids: Vec<DatasetId>) -> Self {
+        Command::CreatePaneWithDatasets {
+            datasets: dataset_ids
+                .into_iter()
+                .map(|id| (id, DEFAULT_DATASET_WEIGHT))
+                .collect(),
+            width: None,
+            height: None,
+        }
+    }
+}
+
+// The ids of a CreatePaneWithDatasets request, for examples that ignore the weights
+pub fn dataset_ids(datasets: &[(DatasetId, f32)]) -> Vec<DatasetId> {
+    datasets.iter().map(|&(id, _)| id).collect()
+}
@@ -0,0 +1,244 @@
+use super::MyHelper;
+use super::flax_example::{self, DatasetHandle, DatasetId, PaneHandle, dataset, pane};
+use colored::*;
+use flax::*;
+use rustyline::Editor;
+use rustyline::error::ReadlineError;
+use rustyline::history::DefaultHistory;
+use std::collections::{BTreeMap, HashMap};
+
+// Live pane/dataset world driven by `--panes`, built on the Flax example's functions
+pub struct PaneReplState {
+    world: World,
+    // Panes keep the number they were created with, so `delpane 2` stays stable
+    panes: BTreeMap<usize, PaneHandle>,
+    next_pane: usize,
+    // Dataset ids are `&'static str` in the example; intern them so repeats don't leak again
+    interned_ids: HashMap<String, &'static str>,
+}
+
+impl PaneReplState {
+    pub fn new() -> Self {
+        Self {
+            world: World::new(),
+            panes: BTreeMap::new(),
+            next_pane: 1,
+            interned_ids: HashMap::new(),
+        }
+    }
+
+    fn intern(&mut self, id: &str) -> &'static str {
+        if let Some(interned) = self.interned_ids.get(id) {
+            return interned;
+        }
+        let interned: &'static str = Box::leak(id.to_string().into_boxed_str());
+        self.interned_ids.insert(id.to_string(), interned);
+        interned
+    }
+
+    pub fn dataset_ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = Query::new(dataset::id())
+            .borrow(&self.world)
+            .iter()
+            .map(|id| id.0.to_string())
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    pub fn pane_numbers(&self) -> Vec<String> {
+        self.panes.keys().map(|number| number.to_string()).collect()
+    }
+
+    pub fn execute_line(&mut self, input: &str) {
+        let parts: Vec<&str> = input.split_whitespace().collect();
+        match parts.as_slice() {
+            ["help"] => print_pane_help(),
+            ["pane"] => self.create_pane(""),
+            ["pane", list] => self.create_pane(list),
+            ["delpane"] => match self.panes.keys().next_back() {
+                Some(&number) => self.delete_pane(number),
+                None => println!("{} No panes to delete", "✗".red().bold()),
+            },
+            ["delpane", number] => match number.parse() {
+                Ok(number) => self.delete_pane(number),
+                Err(_) => println!(
+                    "{} Invalid pane number '{}'",
+                    "✗".red().bold(),
+                    number.red()
+                ),
+            },
+            ["subs"] => flax_example::dump_subscriptions_by_dataset(&self.world),
+            ["stats"] => self.show_stats(),
+            _ => println!(
+                "{} Unknown command. Type 'help' for available commands.",
+                "✗".red().bold()
+            ),
+        }
+    }
+
+    // `list` is a comma-separated list of `id[:weight]` entries; empty entries are ignored
+    fn create_pane(&mut self, list: &str) {
+        let mut datasets: Vec<(DatasetId, f32)> = Vec::new();
+        for entry in list
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+        {
+            let (id, weight) = match entry.split_once(':') {
+                Some((id, weight)) => match weight.parse() {
+                    Ok(weight) => (id, weight),
+                    Err(_) => {
+                        println!(
+                            "{} Invalid weight '{}' for dataset '{}'",
+                            "✗".red().bold(),
+                            weight.red(),
+                            id
+                        );
+                        return;
+                    }
+                },
+                None => (entry, flax_example::DEFAULT_DATASET_WEIGHT),
+            };
+            let id = DatasetId(self.intern(id));
+            if !datasets.iter().any(|(existing, _)| *existing == id) {
+                datasets.push((id, weight));
+            }
+        }
+
+        let number = self.next_pane;
+        let count = datasets.len();
+        let width = 100 * number as u32;
+        let pane = flax_example::create_pane_with_datasets(&mut self.world, datasets, width, 200);
+        self.panes.insert(number, pane);
+        self.next_pane += 1;
+
+        println!(
+            "{} Created pane {} ({:?}) using {} datasets",
+            "✓".green().bold(),
+            number.to_string().bright_cyan(),
+            pane,
+            count
+        );
+    }
+
+    fn delete_pane(&mut self, number: usize) {
+        let Some(pane) = self.panes.remove(&number) else {
+            println!("{} No pane {}", "✗".red().bold(), number.to_string().red());
+            return;
+        };
+
+        flax_example::delete_pane(&mut self.world, pane);
+        flax_example::cleanup_orphan_datasets(&mut self.world);
+        println!(
+            "{} Deleted pane {} ({:?})",
+            "✓".green().bold(),
+            number.to_string().bright_cyan(),
+            pane
+        );
+    }
+
+    fn show_stats(&self) {
+        let datasets: Vec<DatasetHandle> = Query::new(entity_ids())
+            .with(dataset::id())
+            .borrow(&self.world)
+            .iter()
+            .map(DatasetHandle::new)
+            .collect();
+        let subscriptions: usize = Query::new(relations_like(pane::uses_dataset))
+            .borrow(&self.world)
+            .iter()
+            .map(|relations| relations.count())
+            .sum();
+
+        println!("{}", "=== Pane Stats ===".bright_blue().bold());
+        println!("  Panes:         {}", self.panes.len());
+        println!("  Datasets:      {}", datasets.len());
+        println!("  Subscriptions: {}", subscriptions);
+        println!("  Archetypes:    {}", self.world.archetype_info().len());
+
+        // The busiest dataset is the one a relation-heavy layout should worry about
+        if let Some((dataset, subscribers)) = datasets
+            .iter()
+            .map(|&dataset| {
+                let count = flax_example::get_panes_for_dataset(&self.world, dataset).len();
+                (dataset, count)
+            })
+            .max_by_key(|(_, count)| *count)
+        {
+            let id = self.world.get(dataset.entity(), dataset::id()).unwrap();
+            println!(
+                "  Most used:     {} ({} panes)",
+                id.0.bright_cyan(),
+                subscribers
+            );
+        }
+    }
+}
+
+fn print_pane_help() {
+    println!("{}", "Pane REPL commands:".bright_blue().bold());
+    println!(
+        "  {} - create a pane using the comma-separated datasets, optionally weighted",
+        "pane [d1[:w],d2,...]".bright_cyan()
+    );
+    println!(
+        "  {} - delete pane n (the newest pane if omitted)",
+        "delpane [n]".bright_cyan()
+    );
+    println!(
+        "  {} - list every dataset and its subscribing panes",
+        "subs".bright_cyan()
+    );
+    println!(
+        "  {} - count panes, datasets, subscriptions and archetypes",
+        "stats".bright_cyan()
+    );
+    println!("  {} - leave the REPL", "quit/exit".bright_cyan());
+}
+
+pub fn run(rl: &mut Editor<MyHelper, DefaultHistory>) {
+    let mut state = PaneReplState::new();
+
+    println!(
+        "{}",
+        "=== Flax Pane/Dataset REPL ===".bright_magenta().bold()
+    );
+    println!("{}\n", "Type 'help' for available commands".bright_black());
+
+    loop {
+        if let Some(helper) = rl.helper_mut() {
+            helper
+                .completer
+                .update_panes(state.dataset_ids(), state.pane_numbers());
+        }
+
+        match rl.readline("► ") {
+            Ok(line) => {
+                let input = line.trim();
+                if input.is_empty() || input.starts_with('#') {
+                    continue;
+                }
+                rl.add_history_entry(input).ok();
+
+                if matches!(input, "quit" | "exit") {
+                    println!("{}", "👋 Goodbye!".bright_cyan());
+                    break;
+                }
+                state.execute_line(input);
+            }
+            Err(ReadlineError::Interrupted) => {
+                println!("CTRL-C");
+                break;
+            }
+            Err(ReadlineError::Eof) => {
+                println!("CTRL-D");
+                break;
+            }
+            Err(err) => {
+                println!("{} Error: {:?}", "✗".red().bold(), err);
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,154 @@
+// Runs every example's pane/dataset demo and checks that all backends end up
+// with the same subscription structure (dataset id -> subscribing pane indices).
+// Also checks that panes sharing dataset ids never produce duplicate dataset entities,
+// and that the evenio demo state survives conversion into a Flax world.
+use std::process::ExitCode;
+
+#[path = "bevy_ecs_example.rs"]
+mod bevy_ecs_example;
+#[path = "evenio_example.rs"]
+mod evenio_example;
+#[path = "flax_example.rs"]
+mod flax_example;
+#[path = "flecs_example.rs"]
+mod flecs_example;
+#[path = "hecs_example.rs"]
+mod hecs_example;
+mod interop;
+mod report;
+#[path = "sparsey_example.rs"]
+mod sparsey_example;
+
+use report::{OVERLAPPING_PANES, SubscriptionReport};
+
+// Runs every example's demo, in a fixed order, and collects its report
+fn backend_reports() -> Vec<(&'static str, SubscriptionReport)> {
+    vec![
+        ("flax", flax_example::run()),
+        ("bevy_ecs", bevy_ecs_example::run()),
+        ("hecs", hecs_example::run()),
+        ("evenio", evenio_example::run()),
+        ("sparsey", sparsey_example::run()),
+        ("flecs", flecs_example::run()),
+    ]
+}
+
+fn main() -> ExitCode {
+    let reports = backend_reports();
+
+    println!("\n=== Subscription Parity ===");
+    for (name, subscriptions) in &reports {
+        println!("{:<10} {:?}", name, subscriptions);
+    }
+
+    // Compare every pair so drift is reported against each backend it differs from
+    let mut mismatches = 0;
+    for (i, (name_a, subscriptions_a)) in reports.iter().enumerate() {
+        for (name_b, subscriptions_b) in &reports[i + 1..] {
+            if subscriptions_a != subscriptions_b {
+                mismatches += 1;
+                println!("MISMATCH: {} and {} disagree", name_a, name_b);
+            }
+        }
+    }
+
+    if mismatches == 0 {
+        println!(
+            "All {} backends report identical subscriptions",
+            reports.len()
+        );
+    } else {
+        println!("{} mismatched backend pairs", mismatches);
+    }
+
+    let duplicates = check_dataset_dedup();
+    let converted = interop::check_evenio_to_flax();
+
+    if mismatches == 0 && duplicates == 0 && converted {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+// Dataset entity count of every backend after building the overlapping panes
+fn overlapping_dataset_counts() -> [(&'static str, usize); 6] {
+    [
+        ("flax", flax_example::overlapping_dataset_count()),
+        ("bevy_ecs", bevy_ecs_example::overlapping_dataset_count()),
+        ("hecs", hecs_example::overlapping_dataset_count()),
+        ("evenio", evenio_example::overlapping_dataset_count()),
+        ("sparsey", sparsey_example::overlapping_dataset_count()),
+        ("flecs", flecs_example::overlapping_dataset_count()),
+    ]
+}
+
+// Compares each backend's dataset entity count with the distinct ids requested.
+// Returns how many backends disagree.
+fn check_dataset_dedup() -> usize {
+    let expected = report::distinct_dataset_ids(&OVERLAPPING_PANES);
+    let counts = overlapping_dataset_counts();
+
+    println!("\n=== Dataset Dedup ===");
+    let mut duplicates = 0;
+    for (name, count) in counts {
+        if count == expected {
+            println!("{:<10} {} datasets", name, count);
+        } else {
+            duplicates += 1;
+            println!(
+                "{:<10} {} datasets, expected {} (DUPLICATES)",
+                name, count, expected
+            );
+        }
+    }
+
+    if duplicates == 0 {
+        println!(
+            "All backends create one dataset per id across {} overlapping panes",
+            OVERLAPPING_PANES.len()
+        );
+    }
+    duplicates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backends_report_identical_subscriptions() {
+        let reports = backend_reports();
+        let (first, expected) = &reports[0];
+        assert!(!expected.is_empty());
+        for (name, subscriptions) in &reports[1..] {
+            assert_eq!(subscriptions, expected, "{} and {} disagree", first, name);
+        }
+    }
+
+    #[test]
+    fn backends_create_one_dataset_per_id() {
+        let expected = report::distinct_dataset_ids(&OVERLAPPING_PANES);
+        assert_eq!(expected, 3);
+        for (name, count) in overlapping_dataset_counts() {
+            assert_eq!(count, expected, "{} created duplicate datasets", name);
+        }
+    }
+
+    #[test]
+    fn evenio_state_converts_to_flax() {
+        let (registry, panes) = evenio_example::demo_registry();
+        let expected = evenio_example::subscription_map(
+            &registry.world,
+            registry.dataset_lookup,
+            registry.pane_lookup,
+            &panes,
+        );
+
+        let (world, converted) = interop::evenio_to_flax(&registry);
+        assert_eq!(
+            interop::flax_subscription_map(&world, &converted, &panes),
+            expected
+        );
+    }
+}
@@ -0,0 +1,34 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+// Pane requests where every dataset id is shared with at least one other pane.
+// Every backend must end up with one dataset entity per distinct id.
+pub const OVERLAPPING_PANES: [&[&str]; 4] = [
+    &["temperature_sensor_1", "humidity_sensor_1"],
+    &["humidity_sensor_1", "temperature_sensor_1"],
+    &["pressure_sensor_1", "humidity_sensor_1"],
+    &["temperature_sensor_1", "pressure_sensor_1"],
+];
+
+// Normalized result of an example's demo: dataset id -> sorted indices of the
+// subscribing panes in creation order. Indices rather than pane debug strings keep
+// the report comparable across libraries, since each prints its entity ids
+// differently. Dataset ids are always string literals, so they stay borrowed. A
+// plain alias is enough: the map needs no methods of its own and compares with ==.
+pub type SubscriptionReport = BTreeMap<&'static str, Vec<usize>>;
+
+// Number of dataset entities a deduplicating backend should create for `panes`
+pub fn distinct_dataset_ids(panes: &[&[&str]]) -> usize {
+    panes
+        .iter()
+        .flat_map(|ids| ids.iter())
+        .collect::<BTreeSet<_>>()
+        .len()
+}
+
+// Prints one line per dataset with the panes still subscribed to it
+pub fn print_report(report: &SubscriptionReport) {
+    println!("\n=== Subscription Report ===");
+    for (dataset_id, panes) in report {
+        println!("{}: panes {:?}", dataset_id, panes);
+    }
+}
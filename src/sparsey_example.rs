@@ -1,48 +1,17 @@
 #![allow(unused)]
+#[macro_use]
+mod handles;
+mod model;
+mod report;
+
+use model::DatasetId;
+use report::{OVERLAPPING_PANES, SubscriptionReport};
 use sparsey::component::GroupLayout;
 use sparsey::*;
-use std::collections::{HashMap, VecDeque};
-
-// Macro to create type-safe entity handles
-macro_rules! entity_handles {
-    ($($handle_name:ident),* $(,)?) => {
-        $(
-            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-            pub struct $handle_name(Entity);
-
-            impl $handle_name {
-                pub fn new(entity: Entity) -> Self {
-                    Self(entity)
-                }
-
-                pub fn entity(&self) -> Entity {
-                    self.0
-                }
-            }
-
-            impl From<Entity> for $handle_name {
-                fn from(entity: Entity) -> Self {
-                    Self(entity)
-                }
-            }
-
-            impl From<$handle_name> for Entity {
-                fn from(handle: $handle_name) -> Self {
-                    handle.0
-                }
-            }
-        )*
-    };
-}
+use std::collections::{BTreeMap, HashMap, VecDeque};
 
 // Create type-safe handles
-entity_handles! {
-    PaneHandle,
-    DatasetHandle,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct DatasetId(&'static str);
+entity_handles!(Entity; PaneHandle, DatasetHandle);
 
 #[derive(Debug, Clone)]
 struct Pane {
@@ -75,11 +44,7 @@ struct CreatedPanes {
 }
 
 // Command types
-#[derive(Debug, Clone)]
-pub enum Command {
-    CreatePaneWithDatasets { dataset_ids: Vec<DatasetId> },
-    DeletePane { pane: PaneHandle },
-}
+pub type Command = model::Command<PaneHandle, DatasetHandle>;
 
 // Due to Sparsey's constraint system, we need to manage state manually
 struct SparseySim {
@@ -94,11 +59,13 @@ impl SparseySim {
     fn new() -> Self {
         // Create a new sparsey world with separate component groups
         let mut layout = GroupLayout::default();
-        layout.add_group::<(Pane, DatasetId)>(); // Group 1: Panes with DatasetId (limited by Sparsey)
-        layout.add_group::<(DatasetSubscription, SubscriptionMarker)>(); // Group 2: Subscriptions with marker
+        layout.add_group::<(DatasetSubscription, SubscriptionMarker)>(); // Subscriptions with marker
         // Note: CommandQueue and CreatedPanes require pairs, but we simulate them externally
 
-        let world = World::new(&layout);
+        // Groups need at least two components, so Pane lives in its own ungrouped
+        // sparse set rather than being paired with a placeholder component
+        let mut world = World::new(&layout);
+        world.register::<Pane>();
 
         Self {
             world,
@@ -110,20 +77,20 @@ impl SparseySim {
     }
 
     fn create_pane_with_datasets(&mut self, dataset_ids: Vec<DatasetId>) -> PaneHandle {
-        // Due to Sparsey constraints, we simulate pane creation
-        let pane_entity = self.world.create((
-            Pane {
-                width: 100,
-                height: 200,
-            },
-            DatasetId("placeholder"), // Sparsey requires paired components in groups
-        ));
+        // region: spawn
+        // Create the pane entity
+        let pane_entity = self.world.create((Pane {
+            width: 100,
+            height: 200,
+        },));
         let pane_handle = PaneHandle::new(pane_entity);
+        // endregion: spawn
 
         // Track dataset handles (simulated due to Sparsey limitations)
         let mut dataset_handles = Vec::new();
 
         for dataset_id in &dataset_ids {
+            // region: spawn
             let dataset_handle =
                 if let Some(&existing_handle) = self.created_datasets.get(dataset_id) {
                     existing_handle
@@ -139,16 +106,20 @@ impl SparseySim {
                     self.created_datasets.insert(*dataset_id, dataset_handle);
                     dataset_handle
                 };
+            // endregion: spawn
 
             dataset_handles.push(dataset_handle);
         }
 
+        // region: relate
         self.all_pane_dataset_relations
             .push((pane_handle, dataset_handles));
+        // endregion: relate
         pane_handle
     }
 
     fn get_panes_for_dataset(&self, dataset: DatasetHandle) -> Vec<PaneHandle> {
+        // region: query-reverse
         let mut subscribing_panes = Vec::new();
 
         for &(pane_handle, ref dataset_handles) in &self.all_pane_dataset_relations {
@@ -158,6 +129,32 @@ impl SparseySim {
         }
 
         subscribing_panes
+        // endregion: query-reverse
+    }
+
+    // Counts dataset entities in the world rather than entries in `created_datasets`
+    fn dataset_count(&self) -> usize {
+        let mut count = 0;
+        self.world
+            .for_each::<&DatasetSubscription>(|_subscription| count += 1);
+        count
+    }
+
+    // Normalized subscriptions: dataset id -> sorted indices (into `panes`) of subscribing panes
+    fn subscription_map(&self, panes: &[PaneHandle]) -> SubscriptionReport {
+        let mut subscriptions = BTreeMap::new();
+
+        for (&dataset_id, &dataset_handle) in &self.created_datasets {
+            let mut indices: Vec<usize> = self
+                .get_panes_for_dataset(dataset_handle)
+                .iter()
+                .filter_map(|pane| panes.iter().position(|p| p == pane))
+                .collect();
+            indices.sort();
+            subscriptions.insert(dataset_id.0, indices);
+        }
+
+        subscriptions
     }
 
     fn process_commands_system(&mut self) {
@@ -170,7 +167,8 @@ impl SparseySim {
 
         for cmd in commands {
             match cmd {
-                Command::CreatePaneWithDatasets { dataset_ids } => {
+                Command::CreatePaneWithDatasets { datasets, .. } => {
+                    let dataset_ids = model::dataset_ids(&datasets);
                     println!(
                         "[System] Processing CreatePaneWithDatasets command with {} datasets",
                         dataset_ids.len()
@@ -181,9 +179,13 @@ impl SparseySim {
                 }
                 Command::DeletePane { pane } => {
                     println!("[System] Processing DeletePane command for {:?}", pane);
-                    // Note: Due to Sparsey constraints, we simulate deletion
+                    // region: despawn
+                    self.world.destroy(pane.entity());
+                    // endregion: despawn
                     deleted_panes.push(pane);
-                    println!("[System] Note: Entity despawn simulated due to Sparsey constraints");
+                }
+                Command::UnsubscribeDataset { .. } | Command::ResizePane { .. } => {
+                    println!("[System] {:?} is not supported by this example", cmd);
                 }
             }
         }
@@ -194,8 +196,10 @@ impl SparseySim {
         }
         for deleted_pane in deleted_panes {
             self.created_panes.retain(|(_, h)| *h != deleted_pane);
+            // region: despawn
             self.all_pane_dataset_relations
                 .retain(|(h, _)| *h != deleted_pane);
+            // endregion: despawn
         }
     }
 
@@ -228,6 +232,24 @@ impl SparseySim {
 }
 
 pub fn main() {
+    let report = run();
+    report::print_report(&report);
+}
+
+// Dataset entities left after creating the overlapping panes in one command batch
+pub fn overlapping_dataset_count() -> usize {
+    let mut sim = SparseySim::new();
+    for dataset_ids in OVERLAPPING_PANES {
+        sim.enqueue_command(Command::create_pane(
+            dataset_ids.iter().map(|&id| DatasetId(id)).collect(),
+        ));
+    }
+    sim.process_commands_system();
+    sim.dataset_count()
+}
+
+// Runs the demo and returns the normalized subscriptions left after deletion
+pub fn run() -> SubscriptionReport {
     let mut sim = SparseySim::new();
 
     println!("=== Command-Based Pane Creation Demo ===\n");
@@ -237,23 +259,17 @@ pub fn main() {
 
     // Enqueue commands instead of direct creation
     println!("Enqueueing commands...");
-    sim.enqueue_command(Command::CreatePaneWithDatasets {
-        dataset_ids: vec![
-            DatasetId("temperature_sensor_1"),
-            DatasetId("humidity_sensor_1"),
-        ],
-    });
+    sim.enqueue_command(Command::create_pane(vec![
+        DatasetId("temperature_sensor_1"),
+        DatasetId("humidity_sensor_1"),
+    ]));
 
-    sim.enqueue_command(Command::CreatePaneWithDatasets {
-        dataset_ids: vec![DatasetId("humidity_sensor_1")],
-    });
+    sim.enqueue_command(Command::create_pane(vec![DatasetId("humidity_sensor_1")]));
 
-    sim.enqueue_command(Command::CreatePaneWithDatasets {
-        dataset_ids: vec![
-            DatasetId("temperature_sensor_1"),
-            DatasetId("pressure_sensor_1"),
-        ],
-    });
+    sim.enqueue_command(Command::create_pane(vec![
+        DatasetId("temperature_sensor_1"),
+        DatasetId("pressure_sensor_1"),
+    ]));
 
     // Process commands through the system
     println!("\nExecuting command processing system...\n");
@@ -268,10 +284,15 @@ pub fn main() {
 
     // Since sparsey has a different API, let's create a demonstration
     println!("\n=== Panes ===");
-    for &(ref dataset_ids, pane_handle) in &sim.created_panes {
-        println!("Pane Handle: {:?}", pane_handle);
-        println!("  Width: 100, Height: 200"); // Fixed values due to Sparsey constraints
-        println!("  Uses {} datasets: {:?}", dataset_ids.len(), dataset_ids);
+    {
+        let panes = sim.world.query_one::<&Pane>();
+        for &(ref dataset_ids, pane_handle) in &sim.created_panes {
+            println!("Pane Handle: {:?}", pane_handle);
+            if let Some(pane) = panes.get(pane_handle.entity()) {
+                println!("  Width: {}, Height: {}", pane.width, pane.height);
+            }
+            println!("  Uses {} datasets: {:?}", dataset_ids.len(), dataset_ids);
+        }
     }
 
     sim.dump_subscriptions_by_dataset();
@@ -286,19 +307,16 @@ pub fn main() {
     sim.process_commands_system();
 
     sim.dump_subscriptions_by_dataset();
+    let subscriptions = sim.subscription_map(&pane_handles);
 
-    // Query entities with both Pane and DatasetId components (limited by Sparsey grouping)
-    println!("\n=== Sparsey Group Queries ===");
+    // Query the ungrouped Pane storage directly
+    println!("\n=== Sparsey Queries ===");
     let mut pane_count = 0;
-    sim.world
-        .for_each::<(&Pane, &DatasetId)>(|(pane, dataset_id)| {
-            pane_count += 1;
-            println!(
-                "Pane {}x{}, Dataset: {:#?}",
-                pane.width, pane.height, dataset_id
-            );
-        });
-    println!("Found {} entities in Pane+DatasetId group", pane_count);
+    sim.world.for_each::<&Pane>(|pane| {
+        pane_count += 1;
+        println!("Pane {}x{}", pane.width, pane.height);
+    });
+    println!("Found {} entities with a Pane component", pane_count);
 
     // Query DatasetSubscription components
     let mut subscription_count = 0;
@@ -336,7 +354,7 @@ pub fn main() {
     });
 
     println!(
-        "Sparsey group entities with Pane component: {}",
+        "Sparsey entities with Pane component: {}",
         total_pane_entities
     );
     println!(
@@ -349,8 +367,13 @@ pub fn main() {
 
     // Query all panes and their dimensions
     println!("All panes and their dimensions:");
-    for &(_, pane_handle) in &sim.created_panes {
-        println!("  Pane: 100x200"); // Fixed due to constraints
+    {
+        let panes = sim.world.query_one::<&Pane>();
+        for &(_, pane_handle) in &sim.created_panes {
+            if let Some(pane) = panes.get(pane_handle.entity()) {
+                println!("  Pane: {}x{}", pane.width, pane.height);
+            }
+        }
     }
 
     // Query all datasets and show their IDs
@@ -379,4 +402,37 @@ pub fn main() {
     println!("- Complex setup - GroupLayout configuration required");
     println!("- Group constraints limit dynamic entity composition");
     println!("- Manual state management required due to API limitations");
+
+    subscriptions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delete_pane_destroys_only_the_pane() {
+        let mut sim = SparseySim::new();
+        for dataset_ids in [
+            &["temperature_sensor_1", "humidity_sensor_1"][..],
+            &["temperature_sensor_1", "pressure_sensor_1"],
+        ] {
+            sim.enqueue_command(Command::create_pane(
+                dataset_ids.iter().map(|&id| DatasetId(id)).collect(),
+            ));
+        }
+        sim.process_commands_system();
+        let panes: Vec<PaneHandle> = sim.created_panes.iter().map(|(_, h)| *h).collect();
+
+        let entities_before = sim.world.entities().len();
+        sim.enqueue_command(Command::DeletePane { pane: panes[1] });
+        sim.process_commands_system();
+
+        assert_eq!(sim.world.entities().len(), entities_before - 1);
+        assert_eq!(sim.dataset_count(), 3);
+        let pressure = sim.created_datasets[&DatasetId("pressure_sensor_1")];
+        assert!(sim.get_panes_for_dataset(pressure).is_empty());
+        let temperature = sim.created_datasets[&DatasetId("temperature_sensor_1")];
+        assert_eq!(sim.get_panes_for_dataset(temperature), [panes[0]]);
+    }
 }
@@ -0,0 +1,106 @@
+// Splits a REPL line into arguments. Whitespace separates tokens except inside
+// double quotes, so `add entity "Frodo Baggins"` yields three tokens. A backslash
+// escapes the next character anywhere, e.g. `echo "say \"hi\""` or `a\ b`.
+// `""` is an empty token. Unterminated quotes and a trailing backslash are errors.
+pub fn tokenize(input: &str) -> Result<Vec<String>, String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    // A quoted empty string still counts as a token, so track it separately
+    let mut in_token = false;
+    let mut in_quotes = false;
+    let mut chars = input.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(escaped) => {
+                    current.push(escaped);
+                    in_token = true;
+                }
+                None => return Err("Trailing backslash with nothing to escape".to_string()),
+            },
+            '"' => {
+                in_quotes = !in_quotes;
+                in_token = true;
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if in_token {
+                    tokens.push(std::mem::take(&mut current));
+                    in_token = false;
+                }
+            }
+            c => {
+                current.push(c);
+                in_token = true;
+            }
+        }
+    }
+
+    if in_quotes {
+        return Err("Unterminated quote".to_string());
+    }
+    if in_token {
+        tokens.push(current);
+    }
+    Ok(tokens)
+}
+
+// Inverse of `tokenize` for one token: quotes it only when it would otherwise split
+// or lose characters, so plain words round-trip unchanged
+pub fn quote(token: &str) -> String {
+    let needs_quotes = token.is_empty()
+        || token
+            .chars()
+            .any(|c| c.is_whitespace() || c == '"' || c == '\\');
+    if !needs_quotes {
+        return token.to_string();
+    }
+
+    let escaped = token.replace('\\', "\\\\").replace('"', "\\\"");
+    format!("\"{}\"", escaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_plain_words_on_whitespace() {
+        assert_eq!(
+            tokenize("  set health   frodo 10 ").unwrap(),
+            vec!["set", "health", "frodo", "10"]
+        );
+        assert!(tokenize("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn quotes_keep_spaces_in_one_token() {
+        assert_eq!(
+            tokenize(r#"add entity "Frodo Baggins""#).unwrap(),
+            vec!["add", "entity", "Frodo Baggins"]
+        );
+        assert_eq!(tokenize(r#"tag x """#).unwrap(), vec!["tag", "x", ""]);
+    }
+
+    #[test]
+    fn backslash_escapes_quotes_and_spaces() {
+        assert_eq!(
+            tokenize(r#"echo "say \"hi\"""#).unwrap(),
+            vec!["echo", r#"say "hi""#]
+        );
+        assert_eq!(tokenize(r"a\ b").unwrap(), vec!["a b"]);
+    }
+
+    #[test]
+    fn unterminated_quote_and_trailing_backslash_are_errors() {
+        assert!(tokenize(r#"add entity "Frodo"#).is_err());
+        assert!(tokenize(r"rm frodo\").is_err());
+    }
+
+    #[test]
+    fn quote_round_trips_through_tokenize() {
+        for token in ["plain", "two words", r#"say "hi""#, r"back\slash", ""] {
+            assert_eq!(tokenize(&quote(token)).unwrap(), vec![token]);
+        }
+    }
+}
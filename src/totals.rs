@@ -0,0 +1,105 @@
+use flax::*;
+use rayon::prelude::*;
+
+// Health and mana summed over every entity that has them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Totals {
+    pub health: i64,
+    pub mana: i64,
+    pub with_health: usize,
+    pub with_mana: usize,
+}
+
+// Health and mana sums with the archetype batches spread over the rayon pool. Each
+// batch is summed on its own thread and the partial sums are combined afterwards,
+// so the result is identical to a single-threaded pass. `current` reads the mana
+// amount out of the mana component, since the REPL and the benchmark store mana
+// differently.
+pub fn par_totals<M: ComponentValue + Sync>(
+    world: &World,
+    health: Component<i32>,
+    mana: Component<M>,
+    current: fn(&M) -> i32,
+) -> Totals {
+    let (health_sum, with_health) = Query::new(health)
+        .borrow(world)
+        .iter_batched()
+        .par_bridge()
+        .map(|batch| {
+            batch.fold((0i64, 0usize), |(sum, n), &value| {
+                (sum + value as i64, n + 1)
+            })
+        })
+        .reduce(|| (0, 0), |a, b| (a.0 + b.0, a.1 + b.1));
+    let (mana_sum, with_mana) = Query::new(mana)
+        .borrow(world)
+        .iter_batched()
+        .par_bridge()
+        .map(|batch| {
+            batch.fold((0i64, 0usize), |(sum, n), value| {
+                (sum + current(value) as i64, n + 1)
+            })
+        })
+        .reduce(|| (0, 0), |a, b| (a.0 + b.0, a.1 + b.1));
+
+    Totals {
+        health: health_sum,
+        mana: mana_sum,
+        with_health,
+        with_mana,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    component! {
+        health: i32,
+        mana: i32,
+    }
+
+    // Single-threaded reference for `par_totals`
+    fn serial_totals(world: &World) -> Totals {
+        let mut totals = Totals::default();
+        Query::new(health()).borrow(world).for_each(|&value| {
+            totals.health += value as i64;
+            totals.with_health += 1;
+        });
+        Query::new(mana()).borrow(world).for_each(|&value| {
+            totals.mana += value as i64;
+            totals.with_mana += 1;
+        });
+        totals
+    }
+
+    #[test]
+    fn parallel_totals_match_serial_totals() {
+        // Three archetypes (health only, mana only, both), so there are several
+        // batches to spread over the pool
+        let mut world = World::new();
+        for i in 0..10_000 {
+            let mut builder = Entity::builder();
+            if i % 3 != 1 {
+                builder.set(health(), (i % 100) as i32 - 20);
+            }
+            if i % 3 != 0 {
+                builder.set(mana(), (i % 50) as i32);
+            }
+            builder.spawn(&mut world);
+        }
+
+        let parallel = par_totals(&world, health(), mana(), |&value| value);
+        assert_eq!(parallel, serial_totals(&world));
+        assert_eq!((parallel.with_health, parallel.with_mana), (6_667, 6_666));
+    }
+
+    #[test]
+    fn empty_world_has_zero_totals() {
+        let world = World::new();
+        assert_eq!(
+            par_totals(&world, health(), mana(), |&value| value),
+            Totals::default()
+        );
+    }
+}
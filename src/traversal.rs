@@ -0,0 +1,125 @@
+use flax::*;
+use std::collections::{HashMap, HashSet};
+
+// Depth-first walk of the `child_of` forest, returning each entity with its depth
+// (roots at 0) in pre-order. Siblings are visited in entity index order, and an
+// entity reachable through several parents is only listed under the first one.
+pub fn traverse_child_of(world: &World) -> Vec<(Entity, usize)> {
+    let (children, roots) = invert_child_of(world);
+    walk_children(&children, roots)
+}
+
+// The same walk from `start` alone: `start` at depth 0, then everything below it.
+// Every descendant is listed, even one that also sits under another parent.
+pub fn traverse_child_of_from(world: &World, start: Entity) -> Vec<(Entity, usize)> {
+    let (children, _) = invert_child_of(world);
+    walk_children(&children, vec![start])
+}
+
+// Inverts child_of once so every node can enumerate its children. Returns the
+// children of each parent and the entities without a parent, both in index order.
+fn invert_child_of(world: &World) -> (HashMap<Entity, Vec<Entity>>, Vec<Entity>) {
+    let mut children: HashMap<Entity, Vec<Entity>> = HashMap::new();
+    let mut roots = Vec::new();
+    Query::new((entity_ids(), relations_like(components::child_of)))
+        .borrow(world)
+        .for_each(|(entity, child_of_relations)| {
+            let mut has_parent = false;
+            for (parent, _) in child_of_relations {
+                has_parent = true;
+                children.entry(parent).or_default().push(entity);
+            }
+            if !has_parent {
+                roots.push(entity);
+            }
+        });
+
+    roots.sort_by_key(|entity| entity.index());
+    for kids in children.values_mut() {
+        kids.sort_by_key(|child| child.index());
+    }
+    (children, roots)
+}
+
+fn walk_children(
+    children: &HashMap<Entity, Vec<Entity>>,
+    roots: Vec<Entity>,
+) -> Vec<(Entity, usize)> {
+    let mut order = Vec::new();
+    let mut visited = HashSet::new();
+    // Explicit stack so deep hierarchies can't overflow the call stack
+    let mut stack: Vec<(Entity, usize)> = roots.into_iter().rev().map(|root| (root, 0)).collect();
+    while let Some((entity, depth)) = stack.pop() {
+        // Guard against relation cycles
+        if !visited.insert(entity) {
+            continue;
+        }
+        order.push((entity, depth));
+
+        if let Some(kids) = children.get(&entity) {
+            stack.extend(kids.iter().rev().map(|child| (*child, depth + 1)));
+        }
+    }
+
+    order
+}
+
+// Looks for a loop in the `child_of` graph, following each entity up to its parents.
+// Returns the entities on the first loop found, starting from the one the walk hit
+// twice and listed child before parent, or None if the graph is acyclic. Entities
+// are tried in index order so the reported loop is stable.
+pub fn find_child_of_cycle(world: &World) -> Option<Vec<Entity>> {
+    let mut parents: HashMap<Entity, Vec<Entity>> = HashMap::new();
+    Query::new((entity_ids(), relations_like(components::child_of)))
+        .borrow(world)
+        .for_each(|(entity, child_of_relations)| {
+            let mut targets: Vec<Entity> = child_of_relations.map(|(parent, _)| parent).collect();
+            targets.sort_by_key(|parent| parent.index());
+            parents.insert(entity, targets);
+        });
+
+    let mut starts: Vec<Entity> = parents.keys().copied().collect();
+    starts.sort_by_key(|entity| entity.index());
+
+    // Entities whose ancestors are fully explored and loop-free
+    let mut finished = HashSet::new();
+    for start in starts {
+        if finished.contains(&start) {
+            continue;
+        }
+
+        // The stack is the current path; each frame remembers the next parent to try
+        let mut stack: Vec<(Entity, usize)> = vec![(start, 0)];
+        while let Some(top) = stack.last_mut() {
+            let entity = top.0;
+            let next = parents
+                .get(&entity)
+                .and_then(|targets| targets.get(top.1))
+                .copied();
+            top.1 += 1;
+
+            match next {
+                Some(parent) => {
+                    if let Some(position) = stack.iter().position(|(on_path, _)| *on_path == parent)
+                    {
+                        return Some(
+                            stack[position..]
+                                .iter()
+                                .map(|(entity, _)| *entity)
+                                .collect(),
+                        );
+                    }
+                    if !finished.contains(&parent) {
+                        stack.push((parent, 0));
+                    }
+                }
+                None => {
+                    finished.insert(entity);
+                    stack.pop();
+                }
+            }
+        }
+    }
+
+    None
+}